@@ -23,10 +23,26 @@ function MathComponent({ texExpression, ...props }: MathComponentProps) {
     return <span ref={containerRef} />;
 }
 
+interface Step {
+    pass: string;
+    path: number[];
+    before: string;
+    after: string;
+    description: string;
+}
+
 function App() {
     const [expression, setExpression] = useState('');
+    const [strategy, setStrategy] = useState('expand');
+    const [backend, setBackend] = useState('passes');
+    const [angle, setAngle] = useState('radians');
+    const [complex, setComplex] = useState('');
     const [latex, setLatex] = useState('');
     const [result, setResult] = useState('');
+    const [resultLatex, setResultLatex] = useState('');
+    const [steps, setSteps] = useState<Step[]>([]);
+    const [limit, setLimit] = useState('');
+    const [highlighted, setHighlighted] = useState('');
     const [error, setError] = useState('');
 
     const handleSubmit = async (e: React.FormEvent) => {
@@ -34,15 +50,24 @@ function App() {
         setError(''); // Clear any previous errors
         setLatex(''); // Clear previous result
         setResult(''); // Clear previous result
+        setResultLatex('');
+        setSteps([]); // Clear previous steps
+        setLimit('');
+        setHighlighted('');
 
         try {
-            const response = await fetch(`http://localhost:3000/simplify/${encodeURIComponent(expression)}`);
+            const response = await fetch(`http://localhost:3000/simplify/${encodeURIComponent(expression)}?strategy=${encodeURIComponent(strategy)}&backend=${encodeURIComponent(backend)}&angle=${encodeURIComponent(angle)}${complex ? `&complex=${encodeURIComponent(complex)}` : ''}`);
             if (!response.ok) {
                 throw new Error('Failed to fetch the LaTeX expression.');
             }
             const data = await response.json(); // Assuming the API returns plain text
             setLatex(data.simplified);
             setResult(data.result);
+            setResultLatex(data.result_latex ?? '');
+            setSteps(data.steps);
+            setLimit(data.limit ?? '');
+            // the simplified expression with the part that couldn't be evaluated in red
+            setHighlighted(data.highlighted ?? '');
         } catch (err) {
             setError('Error simplifying the expression. Please try again.');
         }
@@ -59,21 +84,73 @@ function App() {
                     placeholder="Enter a mathematical expression"
                     style={{ width: '300px', padding: '10px' }}
                 />
+                <select
+                    value={strategy}
+                    onChange={(e) => setStrategy(e.target.value)}
+                    style={{ marginLeft: '10px', padding: '10px' }}
+                >
+                    <option value="expand">Expand</option>
+                    <option value="factor">Factor</option>
+                    <option value="trig_reduce">Reduce trig</option>
+                    <option value="exponential">Exponential</option>
+                    <option value="simplest">Simplest</option>
+                </select>
+                <select
+                    value={backend}
+                    onChange={(e) => setBackend(e.target.value)}
+                    style={{ marginLeft: '10px', padding: '10px' }}
+                >
+                    <option value="passes">Passes</option>
+                    <option value="egraph">E-graph</option>
+                </select>
+                <select
+                    value={angle}
+                    onChange={(e) => setAngle(e.target.value)}
+                    style={{ marginLeft: '10px', padding: '10px' }}
+                >
+                    <option value="radians">Radians</option>
+                    <option value="degrees">Degrees</option>
+                    <option value="gradians">Gradians</option>
+                </select>
+                <select
+                    value={complex}
+                    onChange={(e) => setComplex(e.target.value)}
+                    style={{ marginLeft: '10px', padding: '10px' }}
+                >
+                    <option value="">Real</option>
+                    <option value="rectangular">Complex (a + bi)</option>
+                    <option value="polar">Complex (polar)</option>
+                </select>
                 <button type="submit" style={{ marginLeft: '10px', padding: '10px' }}>
                     Simplify
                 </button>
             </form>
             {error && <p style={{ color: 'red' }}>{error}</p>}
+            {limit && <p style={{ color: 'orange' }}>Stopped early because {limit}.</p>}
             {latex && (
                 <div>
                     <h2>Result:</h2>
                     <MathComponent texExpression={latex} displayMode={true} />
                 </div>
             )}
+            {steps.length > 0 && (
+                <div>
+                    <h2>Steps:</h2>
+                    <ol>
+                        {steps.map((step, i) => (
+                            <li key={i}>
+                                <p>{step.description}</p>
+                                <MathComponent texExpression={`${step.before} = ${step.after}`} />
+                            </li>
+                        ))}
+                    </ol>
+                </div>
+            )}
             {result && (
                 <div>
                     <h2>Result:</h2>
-                    <p>{result}</p>
+                    {resultLatex ? <MathComponent texExpression={resultLatex} displayMode={true} /> : <p>{result}</p>}
+                    {highlighted && <MathComponent texExpression={highlighted} displayMode={true} />}
                 </div>
             )}
         </div>
@@ -0,0 +1,342 @@
+use std::{
+    f64::consts::{FRAC_PI_2, PI},
+    fmt,
+    str::FromStr,
+};
+
+use crate::{
+    expression::{Division, Expression, Multiplication, Negation, Sqrt},
+    rational::gcd,
+};
+
+// how many units in the last place an angle can be off a multiple of 15° and still be that multiple,
+// as far as working out something like 5 * pi / 6 or 150 / 360 is off, and a value off an exact one
+const ULPS: f64 = 2.0;
+
+// the unit trig functions take their arguments in and inverse trig functions give their results in
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum AngleMode {
+    #[default]
+    Radians,
+    Degrees,
+    Gradians,
+}
+
+impl AngleMode {
+    // how many of this unit make a full turn
+    pub fn turn(self) -> f64 {
+        match self {
+            AngleMode::Radians => 2.0 * PI,
+            AngleMode::Degrees => 360.0,
+            AngleMode::Gradians => 400.0,
+        }
+    }
+
+    pub fn to_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            _ => angle / self.turn() * (2.0 * PI),
+        }
+    }
+
+    pub fn from_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            _ => angle / (2.0 * PI) * self.turn(),
+        }
+    }
+
+    // an angle written in degrees, like 30°, in this unit
+    pub fn from_degrees(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Degrees => angle,
+            _ => angle / 360.0 * self.turn(),
+        }
+    }
+
+    // k * 15° in this unit, pi / 6 rather than 0.5235... in radians
+    pub fn angle(self, twelfths: i64) -> Expression {
+        let fraction = |numerator: Expression, denominator: i64| match denominator {
+            1 => numerator,
+            _ => Expression::Division(Division {
+                numerator: Box::new(numerator),
+                denominator: Box::new(Expression::Number(denominator as f64)),
+            }),
+        };
+        let divisor = gcd(twelfths.abs() as i128, 12).max(1) as i64;
+        let (numerator, denominator) = (twelfths / divisor, 12 / divisor);
+        match self {
+            AngleMode::Radians => {
+                let pi = Expression::Number(PI);
+                let multiple = match numerator.abs() {
+                    0 => return Expression::Number(0.0),
+                    1 => pi,
+                    n => Expression::Multiplication(Multiplication {
+                        terms: vec![Box::new(Expression::Number(n as f64)), Box::new(pi)],
+                    }),
+                };
+                let angle = fraction(multiple, denominator);
+                if numerator < 0 {
+                    Expression::Negation(Negation {
+                        term: Box::new(angle),
+                    })
+                } else {
+                    angle
+                }
+            }
+            AngleMode::Degrees => Expression::Number(15.0 * twelfths as f64),
+            AngleMode::Gradians => {
+                let divisor = gcd(50 * twelfths as i128, 3) as i64;
+                fraction(
+                    Expression::Number((50 * twelfths / divisor) as f64),
+                    3 / divisor,
+                )
+            }
+        }
+    }
+}
+
+impl FromStr for AngleMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "radians" | "rad" => Ok(AngleMode::Radians),
+            "degrees" | "deg" => Ok(AngleMode::Degrees),
+            "gradians" | "grad" => Ok(AngleMode::Gradians),
+            s => Err(format!(
+                "Unknown angle mode \"{}\", expected radians, degrees or gradians",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for AngleMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AngleMode::Radians => write!(f, "radians"),
+            AngleMode::Degrees => write!(f, "degrees"),
+            AngleMode::Gradians => write!(f, "gradians"),
+        }
+    }
+}
+
+// a value of the form ±(numerator / denominator) * sqrt(root), which is how the trig functions of
+// multiples of 30° and 45° come out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Exact {
+    pub negative: bool,
+    pub numerator: u64,
+    pub denominator: u64,
+    pub root: u64,
+}
+
+impl Exact {
+    fn new(negative: bool, numerator: u64, denominator: u64, root: u64) -> Exact {
+        // sqrt(12) -> 2 * sqrt(3)
+        let (mut numerator, mut root) = (numerator, root);
+        for square in [9, 4] {
+            while root % square == 0 {
+                root /= square;
+                numerator *= (square as f64).sqrt() as u64;
+            }
+        }
+        let divisor = (gcd(numerator as i128, denominator as i128) as u64).max(1);
+        Exact {
+            negative: negative && numerator != 0,
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+            root,
+        }
+    }
+
+    // None when dividing by 0
+    fn divide(self, other: Exact) -> Option<Exact> {
+        if other.numerator == 0 {
+            return None;
+        }
+        // (a * sqrt(r)) / (b * sqrt(s)) = (a / (b * s)) * sqrt(r * s)
+        Some(Exact::new(
+            self.negative != other.negative,
+            self.numerator * other.denominator,
+            self.denominator * other.numerator * other.root,
+            self.root * other.root,
+        ))
+    }
+
+    pub fn value(self) -> f64 {
+        let value = self.numerator as f64 * (self.root as f64).sqrt() / self.denominator as f64;
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    pub fn to_expression(self) -> Expression {
+        let multiple = match (self.numerator, self.root) {
+            (0, _) => return Expression::Number(0.0),
+            (n, 1) => Expression::Number(n as f64),
+            (1, r) => Expression::Sqrt(Sqrt {
+                arg: Box::new(Expression::Number(r as f64)),
+            }),
+            (n, r) => Expression::Multiplication(Multiplication {
+                terms: vec![
+                    Box::new(Expression::Number(n as f64)),
+                    Box::new(Expression::Sqrt(Sqrt {
+                        arg: Box::new(Expression::Number(r as f64)),
+                    })),
+                ],
+            }),
+        };
+        let fraction = match self.denominator {
+            1 => multiple,
+            d => Expression::Division(Division {
+                numerator: Box::new(multiple),
+                denominator: Box::new(Expression::Number(d as f64)),
+            }),
+        };
+        if self.negative {
+            Expression::Negation(Negation {
+                term: Box::new(fraction),
+            })
+        } else {
+            fraction
+        }
+    }
+}
+
+// how many 15° an angle is, when it is a multiple of 30° or 45° where the trig functions have exact values
+pub fn special_angle(angle: f64, mode: AngleMode) -> Option<i64> {
+    let twelfths = angle / mode.turn() * 24.0;
+    let rounded = twelfths.round();
+    // far enough from 0 every float is a whole number of 15°s, but not because it was meant to be
+    // an angle a tiny bit off a multiple is that tiny bit off, sin(10^(-10)) isn't 0, only 0 is
+    if !rounded.is_finite()
+        || rounded.abs() > 1e12
+        || (twelfths - rounded).abs() > ULPS * f64::EPSILON * rounded.abs()
+    {
+        return None;
+    }
+    let twelfths = rounded as i64;
+    (twelfths % 2 == 0 || twelfths % 3 == 0).then_some(twelfths)
+}
+
+// sin, cos, tan, csc, sec or cot of k * 15°, None for other functions, angles without an exact value
+// and at poles
+pub fn exact_trig(function: &str, twelfths: i64) -> Option<Exact> {
+    if twelfths % 2 != 0 && twelfths % 3 != 0 {
+        return None;
+    }
+    let one = Exact::new(false, 1, 1, 1);
+    let sin = exact_sin(twelfths);
+    let cos = exact_sin(twelfths + 6);
+    match function {
+        "sin" => Some(sin),
+        "cos" => Some(cos),
+        "tan" => sin.divide(cos),
+        "csc" => one.divide(sin),
+        "sec" => one.divide(cos),
+        "cot" => cos.divide(sin),
+        _ => None,
+    }
+}
+
+fn exact_sin(twelfths: i64) -> Exact {
+    // sin of 0°, 15°, ..., 90°, 15° and 75° are never asked for
+    let first_quadrant = |k: i64| match k {
+        0 => Exact::new(false, 0, 1, 1),
+        2 => Exact::new(false, 1, 2, 1),
+        3 => Exact::new(false, 1, 2, 2),
+        4 => Exact::new(false, 1, 2, 3),
+        _ => Exact::new(false, 1, 1, 1),
+    };
+    let k = twelfths.rem_euclid(24);
+    let (negative, reference) = match k {
+        0..=6 => (false, k),
+        7..=12 => (false, 12 - k),
+        13..=18 => (true, k - 12),
+        _ => (true, 24 - k),
+    };
+    let value = first_quadrant(reference);
+    Exact::new(negative, value.numerator, value.denominator, value.root)
+}
+
+// the angle, as a number of 15°, that an inverse trig function gives for `value` when it is one of
+// the exact values, within the function's principal range
+pub fn exact_inverse(function: &str, value: f64) -> Option<i64> {
+    let (forward, range, value) = match function {
+        "arcsin" => ("sin", -6..=6, value),
+        "arccos" => ("cos", 0..=12, value),
+        "arctan" => ("tan", -5..=5, value),
+        "arccsc" => ("sin", -6..=6, 1.0 / value),
+        "arcsec" => ("cos", 0..=12, 1.0 / value),
+        // arccot(x) = arctan(1 / x), with arccot(0) = 90°
+        "arccot" => ("tan", -5..=6, 1.0 / value),
+        _ => return None,
+    };
+    let special = |twelfths: &i64| twelfths % 2 == 0 || twelfths % 3 == 0;
+    range
+        .into_iter()
+        .filter(special)
+        .find(|&twelfths| match exact_trig(forward, twelfths) {
+            Some(exact) => {
+                value.is_finite()
+                    && (exact.value() - value).abs() <= ULPS * f64::EPSILON * value.abs()
+            }
+            // only tan has a pole in these ranges, at 90° where arccot(0) lands
+            None => value.is_infinite() && forward == "tan",
+        })
+}
+
+// sin, cos, tan, csc, sec or cot of an angle in the given unit, exact at multiples of 30° and 45°,
+// None at a pole
+pub fn circular(function: &str, angle: f64, mode: AngleMode) -> Option<f64> {
+    if let Some(twelfths) = special_angle(angle, mode) {
+        return exact_trig(function, twelfths).map(Exact::value);
+    }
+    let x = mode.to_radians(angle);
+    match function {
+        "sin" => Some(x.sin()),
+        "cos" => Some(x.cos()),
+        "tan" => (!multiple_of_half_pi(x, true)).then(|| x.tan()),
+        "csc" => (!multiple_of_half_pi(x, false)).then(|| 1.0 / x.sin()),
+        "sec" => (!multiple_of_half_pi(x, true)).then(|| 1.0 / x.cos()),
+        "cot" => (!multiple_of_half_pi(x, false)).then(|| 1.0 / x.tan()),
+        _ => None,
+    }
+}
+
+// whether x is an odd or even multiple of pi / 2 to within an ulp, for angles too large for
+// special_angle, where sin(k * pi) comes out as something like 1.2e-16 rather than 0
+// anywhere else near a pole is not at it, tan(pi / 2 + 10^(-10)) is about -10^10
+fn multiple_of_half_pi(x: f64, odd: bool) -> bool {
+    let multiple = (x / FRAC_PI_2).round();
+    (multiple % 2.0 != 0.0) == odd && (x - multiple * FRAC_PI_2).abs() <= f64::EPSILON * x.abs()
+}
+
+// arcsin, arccos, arctan, arccsc, arcsec or arccot of a value, in the given unit and exact at the values
+// of multiples of 30° and 45°, None outside the domain
+pub fn inverse_circular(function: &str, value: f64, mode: AngleMode) -> Option<f64> {
+    if matches!(function, "arcsin" | "arccos") && value.abs() > 1.0
+        || matches!(function, "arccsc" | "arcsec") && value.abs() < 1.0
+    {
+        return None;
+    }
+    if let Some(twelfths) = exact_inverse(function, value) {
+        return Some(twelfths as f64 * mode.turn() / 24.0);
+    }
+    let angle = match function {
+        "arcsin" => value.asin(),
+        "arccos" => value.acos(),
+        "arctan" => value.atan(),
+        "arccsc" => (1.0 / value).asin(),
+        "arcsec" => (1.0 / value).acos(),
+        // arccot(x) = arctan(1 / x), with arccot(0) = pi / 2 so it only jumps at 0 and not around it
+        "arccot" if value == 0.0 => PI / 2.0,
+        "arccot" => (1.0 / value).atan(),
+        _ => return None,
+    };
+    Some(mode.from_radians(angle))
+}
@@ -0,0 +1,396 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use crate::expression::{eval, Expression};
+
+// something a variable can be assumed to be
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Predicate {
+    Real,
+    Positive,
+    Negative,
+    Nonnegative,
+    Nonpositive,
+    Nonzero,
+    Integer,
+}
+
+// one assumption about a variable, like x > 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assumption {
+    pub variable: String,
+    pub predicate: Predicate,
+}
+
+// what is known about the variables of an expression, with no assumptions a variable can be any real number
+// the simplifier asks it whether a rewrite is sound, like sqrt(x^2) -> x which needs x >= 0
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Assumptions {
+    variables: BTreeMap<String, Vec<Predicate>>,
+}
+
+// the signs a value could have, as a set of the bits below
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Signs(u8);
+
+const NEGATIVE: u8 = 1;
+const ZERO: u8 = 2;
+const POSITIVE: u8 = 4;
+const ANY: u8 = NEGATIVE | ZERO | POSITIVE;
+
+impl Predicate {
+    fn signs(self) -> Signs {
+        Signs(match self {
+            Predicate::Positive => POSITIVE,
+            Predicate::Negative => NEGATIVE,
+            Predicate::Nonnegative => ZERO | POSITIVE,
+            Predicate::Nonpositive => NEGATIVE | ZERO,
+            Predicate::Nonzero => NEGATIVE | POSITIVE,
+            Predicate::Real | Predicate::Integer => ANY,
+        })
+    }
+}
+
+impl Signs {
+    fn contains(self, sign: u8) -> bool {
+        self.0 & sign != 0
+    }
+
+    // every sign each combination of one sign from each side can produce
+    fn combine(self, other: Signs, table: fn(u8, u8) -> u8) -> Signs {
+        let mut signs = 0;
+        for a in [NEGATIVE, ZERO, POSITIVE] {
+            for b in [NEGATIVE, ZERO, POSITIVE] {
+                if self.contains(a) && other.contains(b) {
+                    signs |= table(a, b);
+                }
+            }
+        }
+        Signs(signs)
+    }
+
+    fn times(self, other: Signs) -> Signs {
+        self.combine(other, |a, b| match (a, b) {
+            (ZERO, _) | (_, ZERO) => ZERO,
+            (a, b) if a == b => POSITIVE,
+            _ => NEGATIVE,
+        })
+    }
+
+    fn plus(self, other: Signs) -> Signs {
+        self.combine(other, |a, b| match (a, b) {
+            (ZERO, b) => b,
+            (a, ZERO) => a,
+            (a, b) if a == b => a,
+            _ => ANY,
+        })
+    }
+
+    fn negated(self) -> Signs {
+        Signs((self.0 & ZERO) | ((self.0 & NEGATIVE) << 2) | ((self.0 & POSITIVE) >> 2))
+    }
+}
+
+impl Assumptions {
+    pub fn new() -> Assumptions {
+        Assumptions::default()
+    }
+
+    // adds an assumption, unless it contradicts the ones already made about the variable
+    pub fn assume(&mut self, assumption: Assumption) -> Result<(), String> {
+        let predicates = self
+            .variables
+            .entry(assumption.variable.clone())
+            .or_default();
+        let signs = predicates
+            .iter()
+            .fold(assumption.predicate.signs().0, |signs, predicate| {
+                signs & predicate.signs().0
+            });
+        if signs == 0 {
+            let known = predicates
+                .iter()
+                .map(|predicate| format!("{} {}", assumption.variable, predicate))
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(format!("{} contradicts {}", assumption, known));
+        }
+        if !predicates.contains(&assumption.predicate) {
+            predicates.push(assumption.predicate);
+        }
+        Ok(())
+    }
+
+    // whether the variable may have this value
+    pub fn allows(&self, variable: &str, value: f64) -> bool {
+        self.predicates(variable)
+            .iter()
+            .all(|predicate| match predicate {
+                Predicate::Real => true,
+                Predicate::Positive => value > 0.0,
+                Predicate::Negative => value < 0.0,
+                Predicate::Nonnegative => value >= 0.0,
+                Predicate::Nonpositive => value <= 0.0,
+                Predicate::Nonzero => value != 0.0,
+                Predicate::Integer => value.fract() == 0.0,
+            })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty()
+    }
+
+    // whether the expression always has the property for every value of its variables allowed by the
+    // assumptions: Some(true) if it always does, Some(false) if it never does and None if it depends
+    pub fn query(&self, expression: &Expression, predicate: Predicate) -> Option<bool> {
+        match predicate {
+            Predicate::Real => self.real(expression),
+            Predicate::Integer => self.integer(expression),
+            predicate => {
+                let signs = self.signs(expression).0;
+                let wanted = predicate.signs().0;
+                if signs & !wanted == 0 {
+                    Some(true)
+                } else if signs & wanted == 0 {
+                    Some(false)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    // shorthand for passes, which only rewrite when the property is known to hold
+    pub fn holds(&self, expression: &Expression, predicate: Predicate) -> bool {
+        self.query(expression, predicate) == Some(true)
+    }
+
+    // the signs an expression can have, worked out from the signs of its parts
+    fn signs(&self, expression: &Expression) -> Signs {
+        match expression {
+            Expression::Number(n) if *n > 0.0 => Signs(POSITIVE),
+            Expression::Number(n) if *n < 0.0 => Signs(NEGATIVE),
+            Expression::Number(_) => Signs(ZERO),
+            Expression::Variable(variable) => Signs(
+                self.predicates(variable)
+                    .iter()
+                    .fold(ANY, |signs, predicate| signs & predicate.signs().0),
+            ),
+            Expression::Negation(negation) => self.signs(&negation.term).negated(),
+            Expression::Multiplication(multiplication) => multiplication
+                .terms
+                .iter()
+                .fold(Signs(POSITIVE), |signs, term| signs.times(self.signs(term))),
+            Expression::Addition(addition) => addition
+                .terms
+                .iter()
+                .fold(Signs(ZERO), |signs, term| signs.plus(self.signs(term))),
+            Expression::Division(division) => {
+                // dividing by zero is undefined so the denominator can only be the other signs
+                let denominator = Signs(self.signs(&division.denominator).0 & !ZERO);
+                if denominator.0 == 0 {
+                    return Signs(ANY);
+                }
+                self.signs(&division.numerator).times(denominator)
+            }
+            Expression::Exponentiation(exponentiation) => {
+                let base = self.signs(&exponentiation.base);
+                match eval(&exponentiation.exponent).ok() {
+                    Some(0.0) => Signs(POSITIVE),
+                    // x^2 is never negative and only zero when x is
+                    Some(exponent) if exponent % 2.0 == 0.0 => Signs(POSITIVE | (base.0 & ZERO)),
+                    // x^3 has the sign of x
+                    Some(exponent) if exponent.fract() == 0.0 => base,
+                    // any other power is only real for x >= 0
+                    _ if base.0 == POSITIVE => Signs(POSITIVE),
+                    _ if base.0 & NEGATIVE == 0 => Signs(ZERO | POSITIVE),
+                    _ => Signs(ANY),
+                }
+            }
+            Expression::Sqrt(sqrt) => match self.signs(&sqrt.arg).0 {
+                POSITIVE => Signs(POSITIVE),
+                _ => Signs(ZERO | POSITIVE),
+            },
+            Expression::Function(_) => Signs(ANY),
+        }
+    }
+
+    fn real(&self, expression: &Expression) -> Option<bool> {
+        match expression {
+            Expression::Number(_) => Some(true),
+            Expression::Variable(variable) => {
+                (!self.predicates(variable).is_empty()).then_some(true)
+            }
+            Expression::Sqrt(sqrt) => match self.query(&sqrt.arg, Predicate::Negative) {
+                Some(true) => Some(false),
+                _ if self.holds(&sqrt.arg, Predicate::Nonnegative) => self.real(&sqrt.arg),
+                _ => None,
+            },
+            Expression::Negation(negation) => self.real(&negation.term),
+            Expression::Multiplication(multiplication) => {
+                all_true(multiplication.terms.iter().map(|t| self.real(t)))
+            }
+            Expression::Addition(addition) => all_true(addition.terms.iter().map(|t| self.real(t))),
+            Expression::Division(division) => all_true(
+                [
+                    self.real(&division.numerator),
+                    self.real(&division.denominator),
+                ]
+                .into_iter(),
+            ),
+            Expression::Exponentiation(exponentiation) => {
+                let integer_exponent = self.holds(&exponentiation.exponent, Predicate::Integer);
+                let nonnegative_base = self.holds(&exponentiation.base, Predicate::Nonnegative);
+                if integer_exponent || nonnegative_base {
+                    all_true(
+                        [
+                            self.real(&exponentiation.base),
+                            self.real(&exponentiation.exponent),
+                        ]
+                        .into_iter(),
+                    )
+                } else {
+                    None
+                }
+            }
+            Expression::Function(_) => None,
+        }
+    }
+
+    fn integer(&self, expression: &Expression) -> Option<bool> {
+        match expression {
+            Expression::Number(n) => Some(n.fract() == 0.0),
+            Expression::Variable(variable) => self
+                .predicates(variable)
+                .contains(&Predicate::Integer)
+                .then_some(true),
+            Expression::Negation(negation) => self.integer(&negation.term),
+            Expression::Multiplication(multiplication) => {
+                all_true(multiplication.terms.iter().map(|t| self.integer(t)))
+            }
+            Expression::Addition(addition) => {
+                all_true(addition.terms.iter().map(|t| self.integer(t)))
+            }
+            // 2^3 is an integer but 2^-1 isn't
+            Expression::Exponentiation(exponentiation)
+                if self.holds(&exponentiation.exponent, Predicate::Nonnegative) =>
+            {
+                all_true(
+                    [
+                        self.integer(&exponentiation.base),
+                        self.integer(&exponentiation.exponent),
+                    ]
+                    .into_iter(),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    fn predicates(&self, variable: &str) -> &[Predicate] {
+        self.variables
+            .get(variable)
+            .map(|predicates| &predicates[..])
+            .unwrap_or(&[])
+    }
+}
+
+// Some(true) when every part is known to have a property, otherwise nothing is known
+fn all_true(mut parts: impl Iterator<Item = Option<bool>>) -> Option<bool> {
+    parts.all(|part| part == Some(true)).then_some(true)
+}
+
+impl FromStr for Assumption {
+    type Err = String;
+
+    // x > 0, x >= 0, x < 0, x <= 0, x != 0, or x is real, integer, positive, negative, nonnegative or nonzero
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (variable, predicate) = if let Some((variable, property)) = s.split_once(" is ") {
+            let predicate = match property.trim() {
+                "real" => Predicate::Real,
+                "integer" => Predicate::Integer,
+                "positive" => Predicate::Positive,
+                "negative" => Predicate::Negative,
+                "nonnegative" => Predicate::Nonnegative,
+                "nonpositive" => Predicate::Nonpositive,
+                "nonzero" => Predicate::Nonzero,
+                property => {
+                    return Err(format!(
+                        "Unknown property \"{}\" in assumption \"{}\"",
+                        property, s
+                    ))
+                }
+            };
+            (variable, predicate)
+        } else {
+            // the two character operators go first so >= isn't read as >
+            let comparisons = [
+                (">=", Predicate::Nonnegative),
+                ("<=", Predicate::Nonpositive),
+                ("!=", Predicate::Nonzero),
+                (">", Predicate::Positive),
+                ("<", Predicate::Negative),
+            ];
+            let Some((variable, zero, predicate)) =
+                comparisons.iter().find_map(|(operator, predicate)| {
+                    s.split_once(operator).map(|(a, b)| (a, b, *predicate))
+                })
+            else {
+                return Err(format!(
+                    "Assumptions look like \"x > 0\" or \"x is integer\", found \"{}\"",
+                    s
+                ));
+            };
+            if zero.trim() != "0" {
+                return Err(format!(
+                    "Variables can only be compared with 0, found \"{}\"",
+                    s
+                ));
+            }
+            (variable, predicate)
+        };
+        let variable = variable.trim();
+        if variable.is_empty() || !variable.chars().all(|c| c.is_alphabetic()) {
+            return Err(format!("\"{}\" is not a variable", variable));
+        }
+        Ok(Assumption {
+            variable: variable.to_string(),
+            predicate,
+        })
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Predicate::Real => write!(f, "is real"),
+            Predicate::Positive => write!(f, "> 0"),
+            Predicate::Negative => write!(f, "< 0"),
+            Predicate::Nonnegative => write!(f, ">= 0"),
+            Predicate::Nonpositive => write!(f, "<= 0"),
+            Predicate::Nonzero => write!(f, "!= 0"),
+            Predicate::Integer => write!(f, "is integer"),
+        }
+    }
+}
+
+impl fmt::Display for Assumption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.variable, self.predicate)
+    }
+}
+
+impl fmt::Display for Assumptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let assumptions: Vec<String> = self
+            .variables
+            .iter()
+            .flat_map(|(variable, predicates)| {
+                predicates
+                    .iter()
+                    .map(move |predicate| format!("{} {}", variable, predicate))
+            })
+            .collect();
+        write!(f, "{}", assumptions.join(", "))
+    }
+}
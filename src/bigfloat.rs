@@ -0,0 +1,1062 @@
+use std::{
+    cell::{Cell, RefCell},
+    cmp::Ordering,
+    f64::consts::{E, LN_2, LOG10_2, LOG2_10, PI},
+    fmt,
+    ops::{Add, Div, Mul, Neg, Sub},
+    str::FromStr,
+    thread::LocalKey,
+};
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{Euclid, Signed, ToPrimitive, Zero};
+
+use crate::{
+    angle::AngleMode,
+    environment::Environment,
+    eval_error::EvalError,
+    expression::{eval_as, eval_as_with, Expression, Function},
+    numeric::Numeric,
+};
+
+// the bits functions work with past the precision, so their results come out right once rounded to it
+const GUARD: u64 = 32;
+
+// the largest power of two a value can reach before check calls it an overflow, so exponents
+// can be added without overflowing an i64
+const LARGEST_EXPONENT: i64 = 1 << 61;
+
+// angles past 2^(2^16) would need more bits of pi to reduce than is reasonable to work out
+const LARGEST_ANGLE: i64 = 1 << 16;
+
+// how many times arctan halves its argument before summing the series
+const HALVINGS: i64 = 4;
+
+// the most significant digits the repl and server will work a result out to, past this a
+// single request could take minutes
+pub const MAX_DIGITS: u32 = 1000;
+
+thread_local! {
+    // the bits every result is rounded to, f64's unless with_precision says otherwise
+    static PRECISION: Cell<u64> = const { Cell::new(f64::MANTISSA_DIGITS as u64) };
+    // the most precise pi and ln 2 worked out so far, with the bits they were worked out to
+    static PI_CACHE: RefCell<Option<(u64, BigFloat)>> = const { RefCell::new(None) };
+    static LN_2_CACHE: RefCell<Option<(u64, BigFloat)>> = const { RefCell::new(None) };
+}
+
+// a binary floating point number of any precision, mantissa * 2^exponent with an odd mantissa
+// unless it's zero, every result rounded to nearest at the precision with_precision sets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigFloat {
+    mantissa: BigInt,
+    exponent: i64,
+}
+
+// the bits of precision results are rounded to
+pub fn precision() -> u64 {
+    PRECISION.with(Cell::get)
+}
+
+// runs f with results rounded to `bits`, putting the precision back after
+pub fn with_precision<T>(bits: u64, f: impl FnOnce() -> T) -> T {
+    let previous = PRECISION.with(|precision| precision.replace(bits.max(2)));
+    let result = f();
+    PRECISION.with(|precision| precision.set(previous));
+    result
+}
+
+// `digits` if results can be worked out to that many significant digits
+pub fn check_digits(digits: u32) -> Result<u32, String> {
+    match digits {
+        0 => Err("results need at least 1 significant digit".to_string()),
+        digits if digits > MAX_DIGITS => Err(format!(
+            "results can be worked out to at most {} significant digits",
+            MAX_DIGITS
+        )),
+        digits => Ok(digits),
+    }
+}
+
+// the bits needed for `digits` significant decimal digits, with a few more so the last one shown is right
+pub fn digits_to_bits(digits: u32) -> u64 {
+    (digits as f64 * LOG2_10).ceil() as u64 + 16
+}
+
+// the digits that digits_to_bits turns into `bits`
+fn bits_to_digits(bits: u64) -> u32 {
+    (bits.saturating_sub(16) as f64 * LOG10_2).floor().max(1.0) as u32
+}
+
+// the value of expr to `digits` significant digits
+pub fn eval_big(
+    expr: &Expression,
+    digits: u32,
+    angle_mode: AngleMode,
+) -> Result<BigFloat, EvalError> {
+    with_precision(digits_to_bits(digits), || {
+        eval_as::<BigFloat>(expr, angle_mode)
+    })
+}
+
+// eval_big with the variables and angle mode of an environment
+pub fn eval_big_with(
+    expr: &Expression,
+    digits: u32,
+    environment: &Environment,
+) -> Result<BigFloat, EvalError> {
+    with_precision(digits_to_bits(digits), || {
+        eval_as_with::<BigFloat>(expr, environment)
+    })
+}
+
+// f worked out with GUARD + extra more bits, then rounded
+fn guarded(extra: u64, f: impl FnOnce() -> BigFloat) -> BigFloat {
+    with_precision(precision() + GUARD + extra, f).round()
+}
+
+// rounds magnitude >> shift to nearest, ties to even
+fn round_magnitude(magnitude: &BigUint, shift: u64) -> BigUint {
+    let kept = magnitude >> shift;
+    let half = shift > 0 && magnitude.bit(shift - 1);
+    let sticky = shift > 1
+        && magnitude
+            .trailing_zeros()
+            .is_some_and(|zeros| zeros < shift - 1);
+    if half && (sticky || kept.bit(0)) {
+        kept + 1u32
+    } else {
+        kept
+    }
+}
+
+impl BigFloat {
+    pub fn zero() -> BigFloat {
+        BigFloat {
+            mantissa: BigInt::zero(),
+            exponent: 0,
+        }
+    }
+
+    // mantissa * 2^exponent exactly
+    fn exact(mantissa: BigInt, exponent: i64) -> BigFloat {
+        match mantissa.trailing_zeros() {
+            Some(zeros) => BigFloat {
+                mantissa: mantissa >> zeros,
+                exponent: exponent.saturating_add(zeros as i64),
+            },
+            None => BigFloat::zero(),
+        }
+    }
+
+    // mantissa * 2^exponent rounded to the precision
+    fn new(mantissa: BigInt, exponent: i64) -> BigFloat {
+        BigFloat::exact(mantissa, exponent).round()
+    }
+
+    // infinity, as f64 would give for 1 / 0 or an overflowing literal, past where check calls it an overflow
+    fn infinity(negative: bool) -> BigFloat {
+        BigFloat::exact(
+            BigInt::from(if negative { -1 } else { 1 }),
+            LARGEST_EXPONENT + 1,
+        )
+    }
+
+    // exactly the value of a finite f64
+    pub fn from_f64_exact(value: f64) -> BigFloat {
+        assert!(value.is_finite(), "{} has no BigFloat", value);
+        if value == 0.0 {
+            return BigFloat::zero();
+        }
+        let bits = value.to_bits();
+        let biased = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+        let (mantissa, exponent) = match biased {
+            0 => (fraction, -1074),
+            _ => (fraction | (1 << 52), biased - 1075),
+        };
+        let mantissa = BigInt::from(mantissa);
+        BigFloat::exact(if value < 0.0 { -mantissa } else { mantissa }, exponent)
+    }
+
+    // the nearest f64
+    pub fn to_f64(&self) -> f64 {
+        let rounded = with_precision(f64::MANTISSA_DIGITS as u64, || self.clone().round());
+        let mut value = rounded.mantissa.to_f64().unwrap();
+        let mut exponent = rounded.exponent;
+        while exponent != 0 && value != 0.0 && value.is_finite() {
+            let step = exponent.clamp(-1000, 1000);
+            value *= 2f64.powi(step as i32);
+            exponent -= step;
+        }
+        value
+    }
+
+    // rounded to nearest, ties to even, at the precision
+    fn round(self) -> BigFloat {
+        let bits = self.mantissa.bits();
+        let precision = precision();
+        if bits <= precision {
+            return self;
+        }
+        let shift = bits - precision;
+        let rounded = round_magnitude(self.mantissa.magnitude(), shift);
+        BigFloat::exact(
+            BigInt::from_biguint(self.mantissa.sign(), rounded),
+            self.exponent + shift as i64,
+        )
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa.is_negative()
+    }
+
+    // whether it's a whole number, which with an odd mantissa is a non-negative exponent
+    pub fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+
+    pub fn abs(&self) -> BigFloat {
+        BigFloat {
+            mantissa: self.mantissa.abs(),
+            exponent: self.exponent,
+        }
+    }
+
+    // 2^(top - 1) <= |self| < 2^top
+    fn top(&self) -> i64 {
+        self.exponent.saturating_add(self.mantissa.bits() as i64)
+    }
+
+    // self * 2^power, exactly
+    fn times_power_of_two(&self, power: i64) -> BigFloat {
+        match self.is_zero() {
+            true => BigFloat::zero(),
+            false => BigFloat {
+                mantissa: self.mantissa.clone(),
+                exponent: self.exponent.saturating_add(power),
+            },
+        }
+    }
+
+    // the nearest integer, ties to even
+    fn round_to_integer(&self) -> BigInt {
+        if self.exponent >= 0 {
+            return &self.mantissa << self.exponent as u64;
+        }
+        BigInt::from_biguint(
+            self.mantissa.sign(),
+            round_magnitude(self.mantissa.magnitude(), self.exponent.unsigned_abs()),
+        )
+    }
+
+    // itself, or a stand-in of the same sign just below `limit` if it's all below it, which when
+    // added to something reaching past the precision only matters for which way that rounds
+    fn sticky_below(&self, limit: i64) -> BigFloat {
+        match self.top() < limit {
+            true => BigFloat::exact(self.mantissa.signum(), limit - 1),
+            false => self.clone(),
+        }
+    }
+
+    pub fn square_root(&self) -> BigFloat {
+        assert!(
+            !self.is_negative(),
+            "the square root of a negative BigFloat"
+        );
+        if self.is_zero() {
+            return BigFloat::zero();
+        }
+        // twice the bits the root needs and an even exponent left over
+        let mut shift = (2 * (precision() + 2)).saturating_sub(self.mantissa.bits());
+        if (self.exponent - shift as i64).rem_euclid(2) != 0 {
+            shift += 1;
+        }
+        let scaled = self.mantissa.magnitude() << shift;
+        let root = scaled.sqrt();
+        // a sticky bit for whether the root was exact, below where it rounds
+        let inexact = &root * &root != scaled;
+        BigFloat::new(
+            BigInt::from(root * 2u32 + u32::from(inexact)),
+            (self.exponent - shift as i64) / 2 - 1,
+        )
+    }
+
+    // log2 |self|, roughly, for deciding how big a power is before working it out
+    fn log2_estimate(&self) -> f64 {
+        let top = self.top();
+        top as f64 + self.times_power_of_two(-top).to_f64().abs().log2()
+    }
+
+    // the value to `digits` significant digits, written out in full unless that would need zeros
+    // that aren't significant or more than six after the point
+    pub fn to_digits(&self, digits: u32) -> String {
+        if self.is_zero() {
+            return String::from("0");
+        }
+        let digits = digits.max(1);
+        // floor(log10 |self|), or one less since the estimate can only be low
+        let mut exponent = ((self.top() - 1) as f64 * LOG10_2).floor() as i64;
+        let significand = loop {
+            let scaled = with_precision(digits_to_bits(digits) + 64, || {
+                &self.abs() * &power_of_ten(digits as i64 - 1 - exponent)
+            });
+            let significand = scaled.round_to_integer().to_string();
+            if significand.len() <= digits as usize {
+                break significand;
+            }
+            exponent += 1;
+        };
+        let significand = significand.trim_end_matches('0');
+        let sign = if self.is_negative() { "-" } else { "" };
+        if exponent < -7 || exponent >= digits as i64 {
+            let (first, rest) = significand.split_at(1);
+            let point = if rest.is_empty() { "" } else { "." };
+            return format!("{}{}{}{}e{}", sign, first, point, rest, exponent);
+        }
+        if exponent < 0 {
+            return format!(
+                "{}0.{}{}",
+                sign,
+                "0".repeat((-exponent - 1) as usize),
+                significand
+            );
+        }
+        let whole = exponent as usize + 1;
+        if significand.len() <= whole {
+            format!(
+                "{}{}{}",
+                sign,
+                significand,
+                "0".repeat(whole - significand.len())
+            )
+        } else {
+            format!(
+                "{}{}.{}",
+                sign,
+                &significand[..whole],
+                &significand[whole..]
+            )
+        }
+    }
+}
+
+impl From<i64> for BigFloat {
+    fn from(value: i64) -> BigFloat {
+        BigFloat::new(BigInt::from(value), 0)
+    }
+}
+
+impl Ord for BigFloat {
+    fn cmp(&self, other: &BigFloat) -> Ordering {
+        let (sign, other_sign) = (self.mantissa.sign(), other.mantissa.sign());
+        if sign != other_sign || sign == Sign::NoSign {
+            return sign.cmp(&other_sign);
+        }
+        let magnitudes = self.top().cmp(&other.top()).then_with(|| {
+            let exponent = self.exponent.min(other.exponent);
+            let magnitude = |x: &BigFloat| x.mantissa.magnitude() << (x.exponent - exponent) as u64;
+            magnitude(self).cmp(&magnitude(other))
+        });
+        match sign {
+            Sign::Minus => magnitudes.reverse(),
+            _ => magnitudes,
+        }
+    }
+}
+
+impl PartialOrd for BigFloat {
+    fn partial_cmp(&self, other: &BigFloat) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigFloat {
+    type Output = BigFloat;
+
+    fn add(self, other: &BigFloat) -> BigFloat {
+        if self.is_zero() {
+            return other.clone().round();
+        }
+        if other.is_zero() {
+            return self.clone().round();
+        }
+        let limit = self.top().max(other.top()) - precision() as i64 - 2;
+        let (a, b) = (self.sticky_below(limit), other.sticky_below(limit));
+        let exponent = a.exponent.min(b.exponent);
+        BigFloat::new(
+            (a.mantissa << (a.exponent - exponent) as u64)
+                + (b.mantissa << (b.exponent - exponent) as u64),
+            exponent,
+        )
+    }
+}
+
+impl Sub for &BigFloat {
+    type Output = BigFloat;
+
+    fn sub(self, other: &BigFloat) -> BigFloat {
+        self + &-other
+    }
+}
+
+impl Mul for &BigFloat {
+    type Output = BigFloat;
+
+    fn mul(self, other: &BigFloat) -> BigFloat {
+        BigFloat::new(
+            &self.mantissa * &other.mantissa,
+            self.exponent.saturating_add(other.exponent),
+        )
+    }
+}
+
+impl Div for &BigFloat {
+    type Output = BigFloat;
+
+    // infinite when dividing by zero, like f64, which Numeric::divide checks for first
+    fn div(self, other: &BigFloat) -> BigFloat {
+        if other.is_zero() {
+            return BigFloat::infinity(self.is_negative());
+        }
+        if self.is_zero() {
+            return BigFloat::zero();
+        }
+        // a quotient of at least precision + 2 bits, with a sticky bit below for whether it was exact
+        let shift = (precision() + 2 + other.mantissa.bits()).saturating_sub(self.mantissa.bits());
+        let numerator = self.mantissa.magnitude() << shift;
+        let quotient = &numerator / other.mantissa.magnitude();
+        let inexact = &quotient * other.mantissa.magnitude() != numerator;
+        let sign = if self.mantissa.sign() == other.mantissa.sign() {
+            Sign::Plus
+        } else {
+            Sign::Minus
+        };
+        BigFloat::new(
+            BigInt::from_biguint(sign, quotient * 2u32 + u32::from(inexact)),
+            self.exponent
+                .saturating_sub(other.exponent)
+                .saturating_sub(shift as i64 + 1),
+        )
+    }
+}
+
+impl Neg for &BigFloat {
+    type Output = BigFloat;
+
+    fn neg(self) -> BigFloat {
+        BigFloat {
+            mantissa: -&self.mantissa,
+            exponent: self.exponent,
+        }
+    }
+}
+
+impl Add for BigFloat {
+    type Output = BigFloat;
+
+    fn add(self, other: BigFloat) -> BigFloat {
+        &self + &other
+    }
+}
+
+impl Sub for BigFloat {
+    type Output = BigFloat;
+
+    fn sub(self, other: BigFloat) -> BigFloat {
+        &self - &other
+    }
+}
+
+impl Mul for BigFloat {
+    type Output = BigFloat;
+
+    fn mul(self, other: BigFloat) -> BigFloat {
+        &self * &other
+    }
+}
+
+impl Div for BigFloat {
+    type Output = BigFloat;
+
+    fn div(self, other: BigFloat) -> BigFloat {
+        &self / &other
+    }
+}
+
+impl Neg for BigFloat {
+    type Output = BigFloat;
+
+    fn neg(self) -> BigFloat {
+        -&self
+    }
+}
+
+impl FromStr for BigFloat {
+    type Err = String;
+
+    // a decimal like -12.5 or 1.5e-300, rounded to the precision
+    fn from_str(s: &str) -> Result<BigFloat, String> {
+        let invalid = || format!("{} isn't a decimal number", s);
+        let (negative, unsigned) = match s.trim().strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, s.trim()),
+        };
+        let (significand, exponent) = match unsigned.split_once(['e', 'E']) {
+            Some((significand, exponent)) => {
+                (significand, exponent.parse::<i64>().map_err(|_| invalid())?)
+            }
+            None => (unsigned, 0),
+        };
+        let (whole, fraction) = significand.split_once('.').unwrap_or((significand, ""));
+        let digits = format!("{}{}", whole, fraction);
+        if digits.is_empty() || !digits.bytes().all(|digit| digit.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let digits = BigFloat::exact(digits.parse::<BigInt>().map_err(|_| invalid())?, 0);
+        let exponent = exponent - fraction.len() as i64;
+        let magnitude = guarded(0, || match exponent {
+            0.. => &digits * &power_of_ten(exponent),
+            _ => &digits / &power_of_ten(-exponent),
+        });
+        Ok(if negative { -magnitude } else { magnitude })
+    }
+}
+
+impl fmt::Display for BigFloat {
+    // as many digits as the precision is good for, a value like 512 needs only a few bits
+    // but is no less exact than one that needs them all
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_digits(bits_to_digits(precision())))
+    }
+}
+
+// base^power by repeated squaring
+fn integer_power(base: &BigFloat, power: u64) -> BigFloat {
+    let mut result = BigFloat::from(1);
+    let mut square = base.clone();
+    let mut power = power;
+    while power > 0 {
+        if power & 1 == 1 {
+            result = &result * &square;
+        }
+        power >>= 1;
+        if power > 0 {
+            square = &square * &square;
+        }
+    }
+    result
+}
+
+// 10^power, exact while it fits in the precision
+fn power_of_ten(power: i64) -> BigFloat {
+    let magnitude = integer_power(&BigFloat::from(10), power.unsigned_abs());
+    match power {
+        0.. => magnitude,
+        _ => &BigFloat::from(1) / &magnitude,
+    }
+}
+
+// a constant worked out once for the most bits asked of it, and rounded from that after
+fn cached(
+    cache: &'static LocalKey<RefCell<Option<(u64, BigFloat)>>>,
+    compute: impl FnOnce() -> BigFloat,
+) -> BigFloat {
+    let bits = precision() + GUARD;
+    if let Some((cached_bits, value)) = cache.with(|cache| cache.borrow().clone()) {
+        if cached_bits >= bits {
+            return value.round();
+        }
+    }
+    let value = with_precision(bits, compute);
+    cache.with(|cache| *cache.borrow_mut() = Some((bits, value.clone())));
+    value.round()
+}
+
+// the sum of x^(2k + 1) / (2k + 1), alternating in sign for arctan x and not for arctanh x, for |x| < 1
+fn odd_series(x: &BigFloat, alternating: bool) -> BigFloat {
+    let square = x * x;
+    let square = if alternating { -square } else { square };
+    let mut power = x.clone();
+    let mut sum = x.clone();
+    for k in 1.. {
+        power = &power * &square;
+        let term = &power / &BigFloat::from(2 * k + 1);
+        if term.is_zero() || term.top() < sum.top() - precision() as i64 - 2 {
+            break;
+        }
+        sum = &sum + &term;
+    }
+    sum
+}
+
+// pi, from Machin's pi = 16 arctan(1/5) - 4 arctan(1/239)
+pub fn pi() -> BigFloat {
+    cached(&PI_CACHE, || {
+        let arctan_inverse = |n: i64| odd_series(&(&BigFloat::from(1) / &BigFloat::from(n)), true);
+        &(&BigFloat::from(16) * &arctan_inverse(5)) - &(&BigFloat::from(4) * &arctan_inverse(239))
+    })
+}
+
+// ln 2 = 2 arctanh(1/3)
+fn ln_2() -> BigFloat {
+    cached(&LN_2_CACHE, || {
+        odd_series(&(&BigFloat::from(1) / &BigFloat::from(3)), false).times_power_of_two(1)
+    })
+}
+
+fn right_angle() -> BigFloat {
+    pi().times_power_of_two(-1)
+}
+
+// e^x = 2^k e^r with r = x - k ln 2, and e^r the square of e^(r / 2) as many times as r was halved,
+// zero past the smallest value and an overflow past the largest
+fn exp(x: &BigFloat) -> Result<BigFloat, EvalError> {
+    if x.is_zero() {
+        return Ok(BigFloat::from(1));
+    }
+    let k = (x.to_f64() / LN_2).round();
+    if k.abs() >= LARGEST_EXPONENT as f64 {
+        return match x.is_negative() {
+            true => Ok(BigFloat::zero()),
+            false => Err(EvalError::Overflow { path: Vec::new() }),
+        };
+    }
+    let k = k as i64;
+    let halvings = (precision() as f64).sqrt() as i64 / 2;
+    let power = guarded(64 + halvings as u64, || {
+        let r = (x - &(&ln_2() * &BigFloat::from(k))).times_power_of_two(-halvings);
+        let mut term = BigFloat::from(1);
+        let mut sum = BigFloat::from(1);
+        for n in 1.. {
+            term = &(&term * &r) / &BigFloat::from(n);
+            if term.is_zero() || term.top() < -(precision() as i64) - 2 {
+                break;
+            }
+            sum = &sum + &term;
+        }
+        (0..halvings).fold(sum, |power, _| &power * &power)
+    });
+    Ok(power.times_power_of_two(k))
+}
+
+// ln x for x > 0, from x = 2^t y with y within a factor of sqrt 2 of 1, and ln y = 2 arctanh((y - 1) / (y + 1))
+fn ln(x: &BigFloat) -> BigFloat {
+    guarded(0, || {
+        let mut t = x.top();
+        let mut y = x.times_power_of_two(-t);
+        if y.to_f64() < std::f64::consts::FRAC_1_SQRT_2 {
+            y = y.times_power_of_two(1);
+            t -= 1;
+        }
+        let one = BigFloat::from(1);
+        let z = &(&y - &one) / &(&y + &one);
+        let ln_y = match z.is_zero() {
+            true => BigFloat::zero(),
+            false => odd_series(&z, false).times_power_of_two(1),
+        };
+        &(&ln_2() * &BigFloat::from(t)) + &ln_y
+    })
+}
+
+// a quarter turn in angle_mode's unit, None for radians where it isn't exact
+fn quarter_turn(angle_mode: AngleMode) -> Option<i64> {
+    match angle_mode {
+        AngleMode::Radians => None,
+        AngleMode::Degrees => Some(90),
+        AngleMode::Gradians => Some(100),
+    }
+}
+
+fn quadrant(n: &BigInt) -> u8 {
+    n.rem_euclid(&BigInt::from(4)).to_u8().unwrap()
+}
+
+// x as n quarter turns and r radians with |r| about pi / 4 at most, so sin and cos only need r near 0,
+// giving n mod 4 and r, or None if x is too large to reduce. In radians, x within a few of the ulps
+// it was rounded to of a multiple of pi / 2 is taken to be that multiple, so sin(pi) is 0
+fn quarter_turns(x: &BigFloat, angle_mode: AngleMode, rounded_to: u64) -> Option<(u8, BigFloat)> {
+    if x.top() > LARGEST_ANGLE {
+        return None;
+    }
+    let working = precision();
+    let magnitude = x.top().max(0) as u64;
+    if let Some(quarter) = quarter_turn(angle_mode) {
+        // exact, so multiples of 90° come out exact
+        let (n, r) = with_precision(working + magnitude + GUARD, || {
+            let quarter = BigFloat::from(quarter);
+            let n = (x / &quarter).round_to_integer();
+            let r = x - &(&quarter * &BigFloat::exact(n.clone(), 0));
+            (n, &(&r * &right_angle()) / &quarter)
+        });
+        return Some((quadrant(&n), r.round()));
+    }
+    let mut extra = GUARD + magnitude;
+    loop {
+        let (n, r) = with_precision(working + extra, || {
+            let right_angle = right_angle();
+            let n = (x / &right_angle).round_to_integer();
+            let r = x - &(&right_angle * &BigFloat::exact(n.clone(), 0));
+            (n, r)
+        });
+        if n.is_zero() {
+            return Some((0, x.clone()));
+        }
+        if r.is_zero() || r.top() < x.top() - rounded_to as i64 + 4 {
+            return Some((quadrant(&n), BigFloat::zero()));
+        }
+        // the bits lost to cancellation, which have to be worked out again with more if there were too many
+        let needed = GUARD + magnitude + (x.top() - r.top()).max(0) as u64;
+        if extra >= needed {
+            return Some((quadrant(&n), r.round()));
+        }
+        extra = needed;
+    }
+}
+
+// sin r and cos r from their Taylor series
+fn sin_cos(r: &BigFloat) -> (BigFloat, BigFloat) {
+    let square = -(r * r);
+    let series = |first: BigFloat, start: i64| {
+        let mut term = first.clone();
+        let mut sum = first;
+        for k in (start..).step_by(2) {
+            term = &(&term * &square) / &BigFloat::from((k + 1) * (k + 2));
+            if term.is_zero() || term.top() < sum.top() - precision() as i64 - 2 {
+                break;
+            }
+            sum = &sum + &term;
+        }
+        sum
+    };
+    (series(r.clone(), 1), series(BigFloat::from(1), 0))
+}
+
+// the circular functions of x in angle_mode's unit, undefined where they divide by a sin or cos of 0
+fn circular(
+    function: &Function,
+    x: &BigFloat,
+    angle_mode: AngleMode,
+    rounded_to: u64,
+) -> Result<BigFloat, EvalError> {
+    let domain = || EvalError::domain(function.name(), x.to_f64());
+    let (quadrant, r) = quarter_turns(x, angle_mode, rounded_to).ok_or_else(domain)?;
+    let (sin, cos) = sin_cos(&r);
+    let (sin, cos) = match quadrant {
+        0 => (sin, cos),
+        1 => (cos, -sin),
+        2 => (-sin, -cos),
+        _ => (-cos, sin),
+    };
+    let quotient = |numerator: &BigFloat, denominator: &BigFloat| match denominator.is_zero() {
+        true => Err(domain()),
+        false => Ok(numerator / denominator),
+    };
+    let one = BigFloat::from(1);
+    match function {
+        Function::Sin(_) => Ok(sin),
+        Function::Cos(_) => Ok(cos),
+        Function::Tan(_) => quotient(&sin, &cos),
+        Function::Csc(_) => quotient(&one, &sin),
+        Function::Sec(_) => quotient(&one, &cos),
+        _ => quotient(&cos, &sin),
+    }
+}
+
+// arctan x, halving the angle with arctan x = 2 arctan(x / (1 + sqrt(1 + x^2))) so the series converges quickly
+fn arctan(x: &BigFloat) -> BigFloat {
+    let one = BigFloat::from(1);
+    if x.is_zero() {
+        return BigFloat::zero();
+    }
+    if x.abs() > one {
+        let right_angle = if x.is_negative() {
+            -right_angle()
+        } else {
+            right_angle()
+        };
+        return &right_angle - &arctan(&(&one / x));
+    }
+    let mut y = x.clone();
+    for _ in 0..HALVINGS {
+        y = &y / &(&one + &(&one + &(&y * &y)).square_root());
+    }
+    odd_series(&y, true).times_power_of_two(HALVINGS)
+}
+
+// arcsin x = arctan(x / sqrt(1 - x^2)) for |x| <= 1
+fn arcsin(x: &BigFloat) -> BigFloat {
+    let one = BigFloat::from(1);
+    if x.abs() == one {
+        return if x.is_negative() {
+            -right_angle()
+        } else {
+            right_angle()
+        };
+    }
+    arctan(&(x / &(&(&one - x) * &(&one + x)).square_root()))
+}
+
+// arccos x = 2 arctan(sqrt((1 - x) / (1 + x))) for |x| <= 1, which doesn't cancel near 1 like pi / 2 - arcsin x
+fn arccos(x: &BigFloat) -> BigFloat {
+    let one = BigFloat::from(1);
+    if *x == -&one {
+        return pi();
+    }
+    arctan(&(&(&one - x) / &(&one + x)).square_root()).times_power_of_two(1)
+}
+
+fn from_radians(angle: BigFloat, angle_mode: AngleMode) -> BigFloat {
+    match quarter_turn(angle_mode) {
+        Some(quarter) => &(&angle * &BigFloat::from(quarter)) / &right_angle(),
+        None => angle,
+    }
+}
+
+// the inverse circular functions of x, inside their domains, in angle_mode's unit
+fn inverse_circular(function: &Function, x: &BigFloat, angle_mode: AngleMode) -> BigFloat {
+    let one = BigFloat::from(1);
+    let angle = match function {
+        Function::Arcsin(_) => arcsin(x),
+        Function::Arccos(_) => arccos(x),
+        Function::Arctan(_) => arctan(x),
+        Function::Arccsc(_) => arcsin(&(&one / x)),
+        Function::Arcsec(_) => arccos(&(&one / x)),
+        // arccot(0) = pi / 2, like eval
+        _ if x.is_zero() => right_angle(),
+        _ => arctan(&(&one / x)),
+    };
+    from_radians(angle, angle_mode)
+}
+
+// the hyperbolic functions from m = e^-|x|, worked out with the extra bits 1 - m^2 loses when x is small
+fn hyperbolic(function: &Function, x: &BigFloat) -> Result<BigFloat, EvalError> {
+    let one = BigFloat::from(1);
+    if tiny(x) {
+        return Ok(match function {
+            Function::Sinh(_) | Function::Tanh(_) => x.clone(),
+            Function::Cosh(_) | Function::Sech(_) => one,
+            _ => &one / x,
+        });
+    }
+    let extra = (1 - x.top()).max(0) as u64;
+    let result = with_precision(precision() + extra, || {
+        let m = exp(&-x.abs())?;
+        // e^|x| is past the largest value
+        if m.is_zero() && matches!(function, Function::Sinh(_) | Function::Cosh(_)) {
+            return Err(EvalError::Overflow { path: Vec::new() });
+        }
+        let square = &m * &m;
+        let (sum, difference) = (&one + &square, &one - &square);
+        let twice_m = m.times_power_of_two(1);
+        let magnitude = match function {
+            Function::Sinh(_) => &difference / &twice_m,
+            Function::Cosh(_) => &sum / &twice_m,
+            Function::Tanh(_) => &difference / &sum,
+            Function::Csch(_) => &twice_m / &difference,
+            Function::Sech(_) => &twice_m / &sum,
+            _ => &sum / &difference,
+        };
+        let odd = !matches!(function, Function::Cosh(_) | Function::Sech(_));
+        Ok(if odd && x.is_negative() {
+            -magnitude
+        } else {
+            magnitude
+        })
+    })?;
+    Ok(result.round())
+}
+
+// so small that the series' first terms are the values to the precision
+fn tiny(x: &BigFloat) -> bool {
+    x.top() < -(precision() as i64)
+}
+
+// f worked out with as many more bits as a small difference loses to cancellation
+fn cancelling(difference: &BigFloat, f: impl FnOnce() -> BigFloat) -> BigFloat {
+    with_precision(precision() + (-difference.top()).max(0) as u64, f).round()
+}
+
+// arcsinh x = ln(x + sqrt(x^2 + 1)), by symmetry for negative x
+fn arcsinh(x: &BigFloat) -> BigFloat {
+    if tiny(x) {
+        return x.clone();
+    }
+    cancelling(x, || {
+        let magnitude = x.abs();
+        let value =
+            ln(&(&magnitude + &(&(&magnitude * &magnitude) + &BigFloat::from(1)).square_root()));
+        if x.is_negative() {
+            -value
+        } else {
+            value
+        }
+    })
+}
+
+// arccosh x = ln(x + sqrt((x - 1)(x + 1))) for x >= 1, close to sqrt(2 (x - 1)) near 1
+fn arccosh(x: &BigFloat) -> BigFloat {
+    let one = BigFloat::from(1);
+    let above = x - &one;
+    if above.is_zero() {
+        return BigFloat::zero();
+    }
+    cancelling(&above, || ln(&(x + &(&above * &(x + &one)).square_root())))
+}
+
+// arctanh x = ln((1 + x) / (1 - x)) / 2 for |x| < 1
+fn arctanh(x: &BigFloat) -> BigFloat {
+    if tiny(x) {
+        return x.clone();
+    }
+    let one = BigFloat::from(1);
+    cancelling(x, || {
+        ln(&(&(&one + x) / &(&one - x))).times_power_of_two(-1)
+    })
+}
+
+// the inverse hyperbolic functions of x, inside their domains
+fn inverse_hyperbolic(function: &Function, x: &BigFloat) -> BigFloat {
+    let one = BigFloat::from(1);
+    match function {
+        Function::Arcsinh(_) => arcsinh(x),
+        Function::Arccosh(_) => arccosh(x),
+        Function::Arctanh(_) => arctanh(x),
+        Function::Arccsch(_) => arcsinh(&(&one / x)),
+        Function::Arcsech(_) => arccosh(&(&one / x)),
+        _ => arctanh(&(&one / x)),
+    }
+}
+
+impl Numeric for BigFloat {
+    // numbers in expressions are f64s, so each stands for the shortest decimal that rounds to it, which
+    // is what was written, except pi and e which stand for the constants
+    fn from_f64(value: f64) -> Self {
+        if !value.is_finite() {
+            return BigFloat::infinity(value < 0.0);
+        }
+        if value == PI {
+            return pi();
+        }
+        if value == E {
+            return guarded(0, || exp(&BigFloat::from(1)).unwrap());
+        }
+        value.to_string().parse().unwrap()
+    }
+
+    fn is_zero(&self) -> bool {
+        BigFloat::is_zero(self)
+    }
+
+    // exact by repeated squaring for whole exponents, e^(y ln x) otherwise
+    fn powf(self, exponent: Self) -> Result<Self, EvalError> {
+        if self.is_zero() {
+            return match exponent.cmp(&BigFloat::zero()) {
+                Ordering::Less => Err(EvalError::division_by_zero()),
+                Ordering::Equal => Ok(BigFloat::from(1)),
+                Ordering::Greater => Ok(BigFloat::zero()),
+            };
+        }
+        // how many bits the result's exponent has, which past the largest is an overflow or zero
+        let size = exponent.to_f64() * self.log2_estimate();
+        if size.abs() >= LARGEST_EXPONENT as f64 {
+            return match size > 0.0 {
+                true => Err(EvalError::Overflow { path: Vec::new() }),
+                false => Ok(BigFloat::zero()),
+            };
+        }
+        if exponent.is_integer() && exponent.top() < 64 {
+            let power = exponent.round_to_integer().to_i64().unwrap();
+            return Ok(guarded(64, || {
+                let magnitude = integer_power(&self, power.unsigned_abs());
+                match power {
+                    0.. => magnitude,
+                    _ => &BigFloat::from(1) / &magnitude,
+                }
+            }));
+        }
+        // an odd mantissa with no power of two is an odd integer
+        let negative = match (self.is_negative(), exponent.is_integer()) {
+            (false, _) => false,
+            (true, true) => exponent.exponent == 0,
+            (true, false) => return Err(EvalError::NaN { path: Vec::new() }),
+        };
+        let magnitude = with_precision(precision() + GUARD + 64, || {
+            exp(&(&exponent * &ln(&self.abs())))
+        })?
+        .round();
+        Ok(if negative { -magnitude } else { magnitude })
+    }
+
+    fn sqrt(self) -> Result<Self, EvalError> {
+        if self.is_negative() {
+            return Err(EvalError::domain("sqrt", self.to_f64()));
+        }
+        Ok(self.square_root())
+    }
+
+    // the same domains as eval, with exp, ln and the series worked out past the precision and rounded to it
+    fn function(
+        function: &Function,
+        value: Self,
+        angle_mode: AngleMode,
+    ) -> Result<Self, EvalError> {
+        let one = BigFloat::from(1);
+        let outside = match function {
+            Function::Arcsin(_) | Function::Arccos(_) => value.abs() > one,
+            Function::Arccsc(_) | Function::Arcsec(_) => value.abs() < one,
+            Function::Csch(_) | Function::Coth(_) | Function::Arccsch(_) => value.is_zero(),
+            Function::Arcsech(_) => value <= BigFloat::zero() || value > one,
+            Function::Arccosh(_) => value < one,
+            Function::Arctanh(_) => value.abs() >= one,
+            Function::Arccoth(_) => value.abs() <= one,
+            _ => false,
+        };
+        if outside {
+            return Err(EvalError::domain(function.name(), value.to_f64()));
+        }
+        let rounded_to = precision();
+        let result = with_precision(rounded_to + GUARD, || match function {
+            Function::Sin(_)
+            | Function::Cos(_)
+            | Function::Tan(_)
+            | Function::Csc(_)
+            | Function::Sec(_)
+            | Function::Cot(_) => circular(function, &value, angle_mode, rounded_to),
+            Function::Arcsin(_)
+            | Function::Arccos(_)
+            | Function::Arctan(_)
+            | Function::Arccsc(_)
+            | Function::Arcsec(_)
+            | Function::Arccot(_) => Ok(inverse_circular(function, &value, angle_mode)),
+            Function::Sinh(_)
+            | Function::Cosh(_)
+            | Function::Tanh(_)
+            | Function::Csch(_)
+            | Function::Sech(_)
+            | Function::Coth(_) => hyperbolic(function, &value),
+            Function::Arcsinh(_)
+            | Function::Arccosh(_)
+            | Function::Arctanh(_)
+            | Function::Arccsch(_)
+            | Function::Arcsech(_)
+            | Function::Arccoth(_) => Ok(inverse_hyperbolic(function, &value)),
+            // 30° is 30 degrees whatever unit the other angles are in
+            Function::Degrees(_) => Ok(match quarter_turn(angle_mode) {
+                Some(quarter) => &(&value * &BigFloat::from(quarter)) / &BigFloat::from(90),
+                None => &(&value * &right_angle()) / &BigFloat::from(90),
+            }),
+        })?;
+        Ok(result.round())
+    }
+
+    fn check(self) -> Result<Self, EvalError> {
+        if self.top() > LARGEST_EXPONENT {
+            return Err(EvalError::Overflow { path: Vec::new() });
+        }
+        Ok(self)
+    }
+}
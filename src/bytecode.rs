@@ -0,0 +1,230 @@
+use crate::{
+    angle::AngleMode,
+    complex::IMAGINARY_UNIT,
+    eval_error::EvalError,
+    expression::{eval_in, evaluate, Expression, Function},
+    numeric::Numeric,
+    substitution::free_variables,
+};
+
+// one step of a compiled expression, working on a stack of values
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    Constant(f64),
+    // the value of the variable in this slot
+    Load(usize),
+    // the sum or product of the top n values, folded in order from 0 or 1 like eval does
+    Add(usize),
+    Multiply(usize),
+    Divide,
+    Negate,
+    Power,
+    Sqrt,
+    // the function, with its argument left out since that's on the stack
+    Function(Function),
+    // a part of the expression eval fails on whatever the variables are, like i or an unknown variable
+    Fail,
+}
+
+// an expression compiled for evaluating many times, giving the same results as eval
+#[derive(Debug, Clone)]
+pub struct Program {
+    instructions: Vec<Instruction>,
+    variables: Vec<String>,
+    angle_mode: AngleMode,
+    stack_size: usize,
+    // evaluated again on failure, for the error and where it happened
+    expression: Expression,
+}
+
+// compiles `expr` with `variables` as its slots, in order, parts without variables folded to their values
+pub fn compile(expr: &Expression, variables: &[&str], angle_mode: AngleMode) -> Program {
+    let variables: Vec<String> = variables
+        .iter()
+        .map(|variable| variable.to_string())
+        .collect();
+    let mut instructions = Vec::new();
+    emit(expr, &variables, angle_mode, &mut instructions);
+    Program {
+        stack_size: stack_size(&instructions),
+        instructions,
+        variables,
+        angle_mode,
+        expression: expr.clone(),
+    }
+}
+
+fn emit(
+    expr: &Expression,
+    variables: &[String],
+    angle_mode: AngleMode,
+    instructions: &mut Vec<Instruction>,
+) {
+    if free_variables(expr).is_empty() {
+        instructions.push(match eval_in(expr, angle_mode) {
+            Ok(value) => Instruction::Constant(value),
+            Err(_) => Instruction::Fail,
+        });
+        return;
+    }
+    let mut emit = |expr: &Expression| emit(expr, variables, angle_mode, instructions);
+    let instruction = match expr {
+        Expression::Number(_) => unreachable!("numbers have no variables"),
+        Expression::Variable(name) if name == IMAGINARY_UNIT => Instruction::Fail,
+        Expression::Variable(name) => {
+            match variables.iter().position(|variable| variable == name) {
+                Some(slot) => Instruction::Load(slot),
+                None => Instruction::Fail,
+            }
+        }
+        Expression::Addition(addition) => {
+            addition.terms.iter().for_each(|term| emit(term));
+            Instruction::Add(addition.terms.len())
+        }
+        Expression::Multiplication(multiplication) => {
+            multiplication.terms.iter().for_each(|term| emit(term));
+            Instruction::Multiply(multiplication.terms.len())
+        }
+        Expression::Division(division) => {
+            emit(&division.numerator);
+            emit(&division.denominator);
+            Instruction::Divide
+        }
+        Expression::Negation(negation) => {
+            emit(&negation.term);
+            Instruction::Negate
+        }
+        Expression::Exponentiation(exponentiation) => {
+            emit(&exponentiation.base);
+            emit(&exponentiation.exponent);
+            Instruction::Power
+        }
+        Expression::Sqrt(sqrt) => {
+            emit(&sqrt.arg);
+            Instruction::Sqrt
+        }
+        Expression::Function(function) => {
+            emit(function.arg());
+            Instruction::Function(
+                Function::from_name(function.name(), Box::new(Expression::Number(0.0))).unwrap(),
+            )
+        }
+    };
+    instructions.push(instruction);
+}
+
+// the most values on the stack at once
+fn stack_size(instructions: &[Instruction]) -> usize {
+    let mut size: usize = 0;
+    let mut largest = 0;
+    for instruction in instructions {
+        size = match instruction {
+            Instruction::Constant(_) | Instruction::Load(_) | Instruction::Fail => size + 1,
+            Instruction::Add(count) | Instruction::Multiply(count) => size + 1 - count,
+            Instruction::Divide | Instruction::Power => size - 1,
+            Instruction::Negate | Instruction::Sqrt | Instruction::Function(_) => size,
+        };
+        largest = largest.max(size);
+    }
+    largest
+}
+
+impl Program {
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    // the value with the variables given `values` in slot order, the same as eval_with
+    pub fn eval(&self, values: &[f64]) -> Result<f64, EvalError> {
+        let mut stack = Vec::with_capacity(self.stack_size);
+        match self.run(values, &mut stack) {
+            Some(value) => Ok(value),
+            None => self.error(values),
+        }
+    }
+
+    // evaluates at each point of `points`, the variables' values one point after another, into `output`,
+    // NaN where eval fails, which eval itself can say why
+    pub fn eval_batch(&self, points: &[f64], output: &mut [f64]) {
+        let width = self.variables.len();
+        assert_eq!(
+            points.len(),
+            output.len() * width,
+            "{} values aren't {} points of {} variables",
+            points.len(),
+            output.len(),
+            width
+        );
+        let mut stack = Vec::with_capacity(self.stack_size);
+        for (i, value) in output.iter_mut().enumerate() {
+            *value = self
+                .run(&points[i * width..(i + 1) * width], &mut stack)
+                .unwrap_or(f64::NAN);
+        }
+    }
+
+    // the value, or None wherever eval gives an error
+    fn run(&self, values: &[f64], stack: &mut Vec<f64>) -> Option<f64> {
+        stack.clear();
+        for instruction in &self.instructions {
+            let value = match instruction {
+                Instruction::Constant(value) => {
+                    stack.push(*value);
+                    continue;
+                }
+                // variables aren't checked, like in eval
+                Instruction::Load(slot) => {
+                    stack.push(*values.get(*slot)?);
+                    continue;
+                }
+                Instruction::Add(count) => {
+                    let start = stack.len() - count;
+                    stack.drain(start..).fold(0.0, |acc, term| acc + term)
+                }
+                Instruction::Multiply(count) => {
+                    let start = stack.len() - count;
+                    stack.drain(start..).fold(1.0, |acc, term| acc * term)
+                }
+                Instruction::Divide => {
+                    let denominator = stack.pop()?;
+                    Numeric::divide(stack.pop()?, denominator).ok()?
+                }
+                Instruction::Negate => -stack.pop()?,
+                Instruction::Power => {
+                    let exponent = stack.pop()?;
+                    Numeric::powf(stack.pop()?, exponent).ok()?
+                }
+                Instruction::Sqrt => Numeric::sqrt(stack.pop()?).ok()?,
+                Instruction::Function(function) => {
+                    Numeric::function(function, stack.pop()?, self.angle_mode).ok()?
+                }
+                Instruction::Fail => return None,
+            };
+            if !value.is_finite() {
+                return None;
+            }
+            stack.push(value);
+        }
+        stack.pop()
+    }
+
+    // eval's error at values the program failed at
+    fn error(&self, values: &[f64]) -> Result<f64, EvalError> {
+        evaluate(&self.expression, self.angle_mode, &|name| match self
+            .variables
+            .iter()
+            .position(|variable| variable == name)
+            .and_then(|slot| values.get(slot))
+        {
+            Some(value) => Ok(*value),
+            None => Err(EvalError::UnboundVariable {
+                name: name.to_string(),
+                path: Vec::new(),
+            }),
+        })
+    }
+}
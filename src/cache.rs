@@ -0,0 +1,117 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use crate::expression::Expression;
+
+// how many simplified subtrees a cache keeps by default
+pub const DEFAULT_CAPACITY: usize = 10_000;
+
+// remembers what subtrees simplified to so identical subtrees aren't simplified again,
+// within one simplification and across calls that share it
+// the least recently used entry is dropped once it holds `capacity` entries
+#[derive(Debug)]
+pub struct SimplifyCache {
+    capacity: usize,
+    // the key, the expression that was simplified and what it simplified to if that's different,
+    // with when it was last used
+    entries: HashMap<u64, (Expression, Option<Expression>, u64)>,
+    // when each entry was last used, oldest first, to find the one to drop
+    uses: BTreeMap<u64, u64>,
+    clock: u64,
+    hits: u64,
+    // the entries that had to be worked out, counted as they're inserted as a lookup that finds
+    // nothing isn't always followed by the work
+    misses: u64,
+}
+
+// how well the cache is doing, for picking its capacity
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+// a cache that can be shared between simplifications, see SimplifyOptions::cache
+pub type SharedCache = Arc<Mutex<SimplifyCache>>;
+
+impl SimplifyCache {
+    pub fn new(capacity: usize) -> SimplifyCache {
+        SimplifyCache {
+            capacity,
+            entries: HashMap::new(),
+            uses: BTreeMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn shared(capacity: usize) -> SharedCache {
+        Arc::new(Mutex::new(SimplifyCache::new(capacity)))
+    }
+
+    // what `input` simplified to, the input is compared as well as the key in case two expressions
+    // have the same hash
+    pub fn get(&mut self, key: u64, input: &Expression) -> Option<Expression> {
+        self.clock += 1;
+        match self.entries.get_mut(&key) {
+            Some((expression, output, used)) if expression == input => {
+                self.uses.remove(used);
+                *used = self.clock;
+                self.uses.insert(self.clock, key);
+                self.hits += 1;
+                Some(output.as_ref().unwrap_or(expression).clone())
+            }
+            _ => None,
+        }
+    }
+
+    // remembers what `input` was worked out to simplify to, a miss
+    pub fn insert(&mut self, key: u64, input: Expression, output: Expression) {
+        self.misses += 1;
+        self.store(key, input, output);
+    }
+
+    // insert without counting a miss, for entries that come for free with another's work
+    pub(crate) fn store(&mut self, key: u64, input: Expression, output: Expression) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.clock += 1;
+        let output = (output != input).then_some(output);
+        if let Some((_, _, used)) = self.entries.insert(key, (input, output, self.clock)) {
+            self.uses.remove(&used);
+        }
+        self.uses.insert(self.clock, key);
+        while self.entries.len() > self.capacity {
+            let Some((_, oldest)) = self.uses.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.uses.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl Default for SimplifyCache {
+    fn default() -> Self {
+        SimplifyCache::new(DEFAULT_CAPACITY)
+    }
+}
@@ -0,0 +1,300 @@
+use std::{
+    f64::consts::{FRAC_PI_2, PI},
+    fmt,
+    str::FromStr,
+};
+
+use num_complex::Complex64;
+
+use crate::{
+    angle::{circular, AngleMode},
+    environment::Environment,
+    eval_error::EvalError,
+    expression::{apply, eval_as, eval_as_with, Expression, Function},
+    numeric::Numeric,
+};
+
+pub type Complex = Complex64;
+
+// the variable that is the imaginary unit rather than an unknown, 2i parses as 2 * i
+pub const IMAGINARY_UNIT: &str = "i";
+
+// how complex results are written
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ComplexForm {
+    // a + bi
+    #[default]
+    Rectangular,
+    // r ∠ θ, with θ in the angle mode's unit
+    Polar,
+}
+
+impl FromStr for ComplexForm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "rectangular" | "rect" => Ok(ComplexForm::Rectangular),
+            "polar" => Ok(ComplexForm::Polar),
+            s => Err(format!(
+                "Unknown complex form \"{}\", expected rectangular or polar",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ComplexForm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ComplexForm::Rectangular => write!(f, "rectangular"),
+            ComplexForm::Polar => write!(f, "polar"),
+        }
+    }
+}
+
+pub fn eval_complex(expr: &Expression, angle_mode: AngleMode) -> Result<Complex, EvalError> {
+    eval_as(expr, angle_mode)
+}
+
+pub fn eval_complex_with(
+    expr: &Expression,
+    environment: &Environment,
+) -> Result<Complex, EvalError> {
+    eval_as_with(expr, environment)
+}
+
+// evaluation over the complex numbers, so sqrt(-1) is i and every function takes its principal branch
+// real arguments give the same results as f64 does, exact values included
+impl Numeric for Complex {
+    fn from_f64(value: f64) -> Self {
+        Complex::from(value)
+    }
+
+    fn imaginary_unit() -> Result<Self, EvalError> {
+        Ok(Complex::i())
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Complex::from(0.0)
+    }
+
+    fn powf(self, exponent: Self) -> Result<Self, EvalError> {
+        power(self, exponent)
+    }
+
+    fn sqrt(self) -> Result<Self, EvalError> {
+        Ok(match self.im {
+            // sqrt(-4) is exactly 2i
+            0.0 if self.re < 0.0 => Complex::new(0.0, (-self.re).sqrt()),
+            0.0 => Complex::from(self.re.sqrt()),
+            _ => Complex64::sqrt(self),
+        })
+    }
+
+    fn function(
+        function: &Function,
+        value: Self,
+        angle_mode: AngleMode,
+    ) -> Result<Self, EvalError> {
+        let result = apply_complex(function, value, angle_mode)?;
+        if !result.is_finite() {
+            return Err(EvalError::complex_domain(function.name(), value));
+        }
+        Ok(result)
+    }
+
+    fn check(self) -> Result<Self, EvalError> {
+        if self.is_nan() {
+            Err(EvalError::NaN { path: Vec::new() })
+        } else if self.is_infinite() {
+            Err(EvalError::Overflow { path: Vec::new() })
+        } else {
+            // -0 would put -1 at an angle of -pi rather than pi
+            Ok(Complex::new(self.re + 0.0, self.im + 0.0))
+        }
+    }
+}
+
+fn power(base: Complex, exponent: Complex) -> Result<Complex, EvalError> {
+    if base == Complex::from(0.0) {
+        return match exponent {
+            _ if exponent == Complex::from(0.0) => Ok(Complex::from(1.0)),
+            _ if exponent.re > 0.0 => Ok(Complex::from(0.0)),
+            _ => Err(EvalError::division_by_zero()),
+        };
+    }
+    if base.im == 0.0 && exponent.im == 0.0 {
+        // the same as evaluate when the result is real, so 2^3 is exactly 8
+        let real = base.re.powf(exponent.re);
+        if !real.is_nan() {
+            return Ok(Complex::from(real));
+        }
+    }
+    if exponent.im == 0.0 && exponent.re.fract() == 0.0 && exponent.re.abs() <= i32::MAX as f64 {
+        // i^2 is exactly -1 by multiplying rather than exp(2 ln i)
+        return Ok(base.powi(exponent.re as i32));
+    }
+    if base.im == 0.0 {
+        // with x = |x| e^(i phase), x^(a + bi) = |x|^a e^(-b phase) (cos t + i sin t) where t = b ln|x| + a phase,
+        // so e^(i * pi) is exactly -1 and (-1)^(1/2) exactly i
+        let phase = if base.re < 0.0 { PI } else { 0.0 };
+        let magnitude = base.re.abs().powf(exponent.re) * (-exponent.im * phase).exp();
+        let angle = exponent.im * base.re.abs().ln() + exponent.re * phase;
+        return Ok(Complex::new(cos(angle), sin(angle)) * magnitude);
+    }
+    Ok(base.powc(exponent))
+}
+
+// sin and cos of real angles in radians, exact at multiples of 30° and 45° so sin(pi) is 0
+fn sin(x: f64) -> f64 {
+    circular("sin", x, AngleMode::Radians).unwrap()
+}
+
+fn cos(x: f64) -> f64 {
+    circular("cos", x, AngleMode::Radians).unwrap()
+}
+
+// sin, cos, sinh and cosh from their real and imaginary parts, which are exact where the real
+// functions are, so sinh(i * pi) is 0 and csch(i * pi) undefined rather than 8e15
+fn complex_sin(z: Complex) -> Complex {
+    Complex::new(sin(z.re) * z.im.cosh(), cos(z.re) * z.im.sinh())
+}
+
+fn complex_cos(z: Complex) -> Complex {
+    Complex::new(cos(z.re) * z.im.cosh(), -sin(z.re) * z.im.sinh())
+}
+
+fn complex_sinh(z: Complex) -> Complex {
+    Complex::new(z.re.sinh() * cos(z.im), z.re.cosh() * sin(z.im))
+}
+
+fn complex_cosh(z: Complex) -> Complex {
+    Complex::new(z.re.cosh() * cos(z.im), z.re.sinh() * sin(z.im))
+}
+
+// tan(x + iy) = (sin 2x + i sinh 2y) / (cos 2x + cosh 2y), which stays finite where sin / cos
+// overflows to inf / inf, and far from the real line, where cosh 2y would overflow as well,
+// is ±i with a real part of about 2 sin 2x e^(-2|y|)
+fn complex_tan(z: Complex) -> Complex {
+    let (x, y) = (2.0 * z.re, 2.0 * z.im);
+    if y.abs() > FAR_FROM_REAL_LINE {
+        return Complex::new(2.0 * sin(x) * (-y.abs()).exp(), y.signum());
+    }
+    Complex::new(sin(x), y.sinh()) / (cos(x) + y.cosh())
+}
+
+// cot(x + iy) = (sin 2x - i sinh 2y) / (cosh 2y - cos 2x), ∓i far from the real line like tan
+fn complex_cot(z: Complex) -> Complex {
+    let (x, y) = (2.0 * z.re, 2.0 * z.im);
+    if y.abs() > FAR_FROM_REAL_LINE {
+        return Complex::new(2.0 * sin(x) * (-y.abs()).exp(), -y.signum());
+    }
+    Complex::new(sin(x), -y.sinh()) / (y.cosh() - cos(x))
+}
+
+// how far 2y has to be from 0 for 1 - tanh |2y| to be below an f64's precision
+const FAR_FROM_REAL_LINE: f64 = 40.0;
+
+// real x past 1 comes out with an imaginary part of +pi / 2 like it does from above the branch cut,
+// where the -0 of 1 - x would otherwise put it below
+fn atanh(z: Complex) -> Complex {
+    match z.im {
+        0.0 => Complex::from((1.0 + z.re) / (1.0 - z.re)).ln() / 2.0,
+        _ => z.atanh(),
+    }
+}
+
+// a function of a complex value, on its principal branch
+fn apply_complex(
+    function: &Function,
+    z: Complex,
+    angle_mode: AngleMode,
+) -> Result<Complex, EvalError> {
+    if z.im == 0.0 {
+        match apply(function, z.re, angle_mode) {
+            Ok(value) if !value.is_nan() => return Ok(Complex::from(value)),
+            // the inverse functions go on past the ends of their real domains, the others fail at poles
+            Err(err) if !extends_off_real_line(function) => return Err(err),
+            _ => {}
+        }
+    }
+    // trig functions take their argument and inverse trig functions give their result in angle_mode's unit
+    let w = z * angle_mode.to_radians(1.0);
+    let angle = |radians: Complex| radians * angle_mode.from_radians(1.0);
+    let one = Complex::from(1.0);
+    Ok(match function {
+        Function::Sin(_) => complex_sin(w),
+        Function::Cos(_) => complex_cos(w),
+        Function::Tan(_) => complex_tan(w),
+        Function::Csc(_) => one / complex_sin(w),
+        Function::Sec(_) => one / complex_cos(w),
+        Function::Cot(_) => complex_cot(w),
+        Function::Arcsin(_) => angle(z.asin()),
+        Function::Arccos(_) => angle(z.acos()),
+        Function::Arctan(_) => angle(z.atan()),
+        Function::Arccsc(_) => angle((one / z).asin()),
+        Function::Arcsec(_) => angle((one / z).acos()),
+        Function::Arccot(_) if z == Complex::from(0.0) => angle(Complex::from(FRAC_PI_2)),
+        Function::Arccot(_) => angle((one / z).atan()),
+        Function::Sinh(_) => complex_sinh(z),
+        Function::Cosh(_) => complex_cosh(z),
+        // tanh z = -i tan(iz) and coth z = i cot(iz)
+        Function::Tanh(_) => complex_tan(z * Complex::i()) * -Complex::i(),
+        Function::Csch(_) => one / complex_sinh(z),
+        Function::Sech(_) => one / complex_cosh(z),
+        Function::Coth(_) => complex_cot(z * Complex::i()) * Complex::i(),
+        Function::Arcsinh(_) => z.asinh(),
+        Function::Arccosh(_) => z.acosh(),
+        Function::Arctanh(_) => atanh(z),
+        Function::Arccsch(_) => (one / z).asinh(),
+        Function::Arcsech(_) => (one / z).acosh(),
+        Function::Arccoth(_) => atanh(one / z),
+        Function::Degrees(_) => z * angle_mode.from_degrees(1.0),
+    })
+}
+
+// functions whose real domain stops short of where they are defined over the complex numbers, like
+// arcsin(2), rather than at a pole
+fn extends_off_real_line(function: &Function) -> bool {
+    matches!(
+        function,
+        Function::Arcsin(_)
+            | Function::Arccos(_)
+            | Function::Arccsc(_)
+            | Function::Arcsec(_)
+            | Function::Arccosh(_)
+            | Function::Arctanh(_)
+            | Function::Arcsech(_)
+            | Function::Arccoth(_)
+    )
+}
+
+// 3 + 4i or 5 ∠ 0.9272952180016122, with the angle in angle_mode's unit
+pub fn format_complex(z: Complex, form: ComplexForm, angle_mode: AngleMode) -> String {
+    match form {
+        ComplexForm::Rectangular => match (z.re, z.im) {
+            (re, 0.0) => format!("{}", re),
+            (0.0, im) => format!("{}i", imaginary_part(im)),
+            (re, im) if im < 0.0 => format!("{} - {}i", re, imaginary_part(-im)),
+            (re, im) => format!("{} + {}i", re, imaginary_part(im)),
+        },
+        ComplexForm::Polar => {
+            let (r, theta) = z.to_polar();
+            match angle_mode {
+                AngleMode::Radians => format!("{} ∠ {}", r, theta),
+                AngleMode::Degrees => format!("{} ∠ {}°", r, angle_mode.from_radians(theta)),
+                AngleMode::Gradians => format!("{} ∠ {}g", r, angle_mode.from_radians(theta)),
+            }
+        }
+    }
+}
+
+// i rather than 1i
+fn imaginary_part(im: f64) -> String {
+    match im {
+        1.0 => String::new(),
+        -1.0 => String::from("-"),
+        im => format!("{}", im),
+    }
+}
@@ -0,0 +1,536 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::{
+    angle::{circular, AngleMode},
+    environment::Environment,
+    eval_error::EvalError,
+    expression::{evaluate, Expression, Function},
+    numeric::Numeric,
+};
+
+// a value with its partial derivatives with respect to the variables being differentiated by, in order,
+// any missing from the end being 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dual {
+    pub value: f64,
+    pub derivatives: Vec<f64>,
+}
+
+// a value with its first and second partial derivatives, hessian[i][j] being the derivative with
+// respect to the ith and jth variables, any missing being 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyperDual {
+    pub value: f64,
+    pub gradient: Vec<f64>,
+    pub hessian: Vec<Vec<f64>>,
+}
+
+// the value of `expr` at the values `environment` gives its variables, with its partial derivatives with
+// respect to `variables`, all in one pass
+pub fn gradient(
+    expr: &Expression,
+    variables: &[&str],
+    environment: &Environment,
+) -> Result<Dual, EvalError> {
+    let mut dual: Dual = differentiate(expr, variables, environment)?;
+    dual.derivatives.resize(variables.len(), 0.0);
+    Ok(dual)
+}
+
+// gradient, with the second partial derivatives too
+pub fn hessian(
+    expr: &Expression,
+    variables: &[&str],
+    environment: &Environment,
+) -> Result<HyperDual, EvalError> {
+    let mut hyper_dual: HyperDual = differentiate(expr, variables, environment)?;
+    hyper_dual.gradient.resize(variables.len(), 0.0);
+    hyper_dual.hessian.resize(variables.len(), Vec::new());
+    for row in &mut hyper_dual.hessian {
+        row.resize(variables.len(), 0.0);
+    }
+    Ok(hyper_dual)
+}
+
+fn differentiate<T: Jet>(
+    expr: &Expression,
+    variables: &[&str],
+    environment: &Environment,
+) -> Result<T, EvalError> {
+    // the variables being differentiated by start out with a derivative of 1 with respect to themselves
+    let seeded = |name: &str| {
+        let index = variables.iter().position(|variable| *variable == name)?;
+        Some(
+            environment
+                .value(name)
+                .map(|value| T::variable(value, index, variables.len())),
+        )
+    };
+    evaluate(expr, environment.angle_mode, &|name| {
+        environment.value_seeded(name, &seeded)
+    })
+}
+
+impl Dual {
+    pub fn derivative(&self, index: usize) -> f64 {
+        self.derivatives.get(index).copied().unwrap_or(0.0)
+    }
+}
+
+impl HyperDual {
+    pub fn derivative(&self, index: usize) -> f64 {
+        self.gradient.get(index).copied().unwrap_or(0.0)
+    }
+
+    pub fn second_derivative(&self, i: usize, j: usize) -> f64 {
+        entry(&self.hessian, i, j)
+    }
+}
+
+// what Dual and HyperDual have in common, so powers, square roots and functions are differentiated once
+trait Jet: Numeric {
+    fn variable(value: f64, index: usize, count: usize) -> Self;
+
+    fn value(&self) -> f64;
+
+    // the same derivatives with another value, for quotients and powers that come out closer to eval's
+    // computed directly
+    fn with_value(self, value: f64) -> Self;
+
+    // whether every derivative is 0, so it's a constant as far as the variables are concerned
+    fn is_constant(&self) -> bool;
+
+    fn derivatives_finite(&self) -> bool;
+
+    // f(self), given f's value and first and second derivatives at self's value
+    fn chain(self, value: f64, first: f64, second: f64) -> Self;
+}
+
+impl Jet for Dual {
+    fn variable(value: f64, index: usize, count: usize) -> Self {
+        Dual {
+            value,
+            derivatives: (0..count)
+                .map(|i| if i == index { 1.0 } else { 0.0 })
+                .collect(),
+        }
+    }
+
+    fn value(&self) -> f64 {
+        self.value
+    }
+
+    fn with_value(self, value: f64) -> Self {
+        Dual { value, ..self }
+    }
+
+    fn is_constant(&self) -> bool {
+        self.derivatives.iter().all(|derivative| *derivative == 0.0)
+    }
+
+    fn derivatives_finite(&self) -> bool {
+        self.derivatives
+            .iter()
+            .all(|derivative| derivative.is_finite())
+    }
+
+    fn chain(self, value: f64, first: f64, _second: f64) -> Self {
+        Dual {
+            value,
+            derivatives: combine(&self.derivatives, first, &[], 0.0),
+        }
+    }
+}
+
+impl Jet for HyperDual {
+    fn variable(value: f64, index: usize, count: usize) -> Self {
+        HyperDual {
+            value,
+            gradient: (0..count)
+                .map(|i| if i == index { 1.0 } else { 0.0 })
+                .collect(),
+            hessian: vec![vec![0.0; count]; count],
+        }
+    }
+
+    fn value(&self) -> f64 {
+        self.value
+    }
+
+    fn with_value(self, value: f64) -> Self {
+        HyperDual { value, ..self }
+    }
+
+    fn is_constant(&self) -> bool {
+        self.gradient
+            .iter()
+            .chain(self.hessian.iter().flatten())
+            .all(|derivative| *derivative == 0.0)
+    }
+
+    fn derivatives_finite(&self) -> bool {
+        self.gradient
+            .iter()
+            .chain(self.hessian.iter().flatten())
+            .all(|derivative| derivative.is_finite())
+    }
+
+    // the second derivatives of f(u) are f'(u) u_ij + f''(u) u_i u_j
+    fn chain(self, value: f64, first: f64, second: f64) -> Self {
+        let hessian = matrix(self.gradient.len(), |i, j| {
+            first * entry(&self.hessian, i, j) + second * self.gradient[i] * self.gradient[j]
+        });
+        HyperDual {
+            value,
+            gradient: combine(&self.gradient, first, &[], 0.0),
+            hessian,
+        }
+    }
+}
+
+impl Add for Dual {
+    type Output = Dual;
+
+    fn add(self, other: Dual) -> Dual {
+        Dual {
+            value: self.value + other.value,
+            derivatives: combine(&self.derivatives, 1.0, &other.derivatives, 1.0),
+        }
+    }
+}
+
+impl Sub for Dual {
+    type Output = Dual;
+
+    fn sub(self, other: Dual) -> Dual {
+        self + -other
+    }
+}
+
+impl Mul for Dual {
+    type Output = Dual;
+
+    fn mul(self, other: Dual) -> Dual {
+        Dual {
+            value: self.value * other.value,
+            derivatives: combine(
+                &self.derivatives,
+                other.value,
+                &other.derivatives,
+                self.value,
+            ),
+        }
+    }
+}
+
+impl Div for Dual {
+    type Output = Dual;
+
+    fn div(self, divisor: Dual) -> Dual {
+        quotient(self, divisor)
+    }
+}
+
+impl Neg for Dual {
+    type Output = Dual;
+
+    fn neg(self) -> Dual {
+        Dual {
+            value: -self.value,
+            derivatives: combine(&self.derivatives, -1.0, &[], 0.0),
+        }
+    }
+}
+
+impl Add for HyperDual {
+    type Output = HyperDual;
+
+    fn add(self, other: HyperDual) -> HyperDual {
+        let size = self.gradient.len().max(other.gradient.len());
+        HyperDual {
+            value: self.value + other.value,
+            gradient: combine(&self.gradient, 1.0, &other.gradient, 1.0),
+            hessian: matrix(size, |i, j| {
+                entry(&self.hessian, i, j) + entry(&other.hessian, i, j)
+            }),
+        }
+    }
+}
+
+impl Sub for HyperDual {
+    type Output = HyperDual;
+
+    fn sub(self, other: HyperDual) -> HyperDual {
+        self + -other
+    }
+}
+
+impl Mul for HyperDual {
+    type Output = HyperDual;
+
+    fn mul(self, other: HyperDual) -> HyperDual {
+        HyperDual {
+            value: self.value * other.value,
+            gradient: combine(&self.gradient, other.value, &other.gradient, self.value),
+            hessian: product_hessian(&self, &other),
+        }
+    }
+}
+
+impl Div for HyperDual {
+    type Output = HyperDual;
+
+    fn div(self, divisor: HyperDual) -> HyperDual {
+        quotient(self, divisor)
+    }
+}
+
+impl Neg for HyperDual {
+    type Output = HyperDual;
+
+    fn neg(self) -> HyperDual {
+        let hessian = matrix(self.gradient.len(), |i, j| -entry(&self.hessian, i, j));
+        HyperDual {
+            value: -self.value,
+            gradient: combine(&self.gradient, -1.0, &[], 0.0),
+            hessian,
+        }
+    }
+}
+
+impl Numeric for Dual {
+    fn from_f64(value: f64) -> Self {
+        Dual {
+            value,
+            derivatives: Vec::new(),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value == 0.0
+    }
+
+    fn powf(self, exponent: Self) -> Result<Self, EvalError> {
+        power(self, exponent)
+    }
+
+    fn sqrt(self) -> Result<Self, EvalError> {
+        root(self)
+    }
+
+    fn function(
+        function: &Function,
+        value: Self,
+        angle_mode: AngleMode,
+    ) -> Result<Self, EvalError> {
+        apply_jet(function, value, angle_mode)
+    }
+
+    fn check(self) -> Result<Self, EvalError> {
+        check(self)
+    }
+}
+
+impl Numeric for HyperDual {
+    fn from_f64(value: f64) -> Self {
+        HyperDual {
+            value,
+            gradient: Vec::new(),
+            hessian: Vec::new(),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value == 0.0
+    }
+
+    fn powf(self, exponent: Self) -> Result<Self, EvalError> {
+        power(self, exponent)
+    }
+
+    fn sqrt(self) -> Result<Self, EvalError> {
+        root(self)
+    }
+
+    fn function(
+        function: &Function,
+        value: Self,
+        angle_mode: AngleMode,
+    ) -> Result<Self, EvalError> {
+        apply_jet(function, value, angle_mode)
+    }
+
+    fn check(self) -> Result<Self, EvalError> {
+        check(self)
+    }
+}
+
+// u / v as u * (1 / v), with eval's quotient as the value
+fn quotient<T: Jet>(dividend: T, divisor: T) -> T {
+    let (u, v) = (dividend.value(), divisor.value());
+    let reciprocal = divisor.chain(1.0 / v, -1.0 / (v * v), 2.0 / (v * v * v));
+    (dividend * reciprocal).with_value(u / v)
+}
+
+fn power<T: Jet>(base: T, exponent: T) -> Result<T, EvalError> {
+    let (x, y) = (base.value(), exponent.value());
+    let value = Numeric::check(Numeric::powf(x, y)?)?;
+    if exponent.is_constant() {
+        // x^y has derivatives y x^(y - 1) and y (y - 1) x^(y - 2), written so x^1 and x^0 have them at 0 too
+        let first = if y == 0.0 { 0.0 } else { y * x.powf(y - 1.0) };
+        let second = if y == 0.0 || y == 1.0 {
+            0.0
+        } else {
+            y * (y - 1.0) * x.powf(y - 2.0)
+        };
+        if !(first.is_finite() && second.is_finite()) {
+            return Err(EvalError::not_differentiable("power", x));
+        }
+        return Ok(base.chain(value, first, second));
+    }
+    if x <= 0.0 {
+        return Err(EvalError::not_differentiable("power", x));
+    }
+    // x^y is e^(y ln x), and e^u is its own derivative
+    let exponent = exponent * base.chain(x.ln(), 1.0 / x, -1.0 / (x * x));
+    Ok(exponent.chain(value, value, value))
+}
+
+fn root<T: Jet>(x: T) -> Result<T, EvalError> {
+    let value = Numeric::sqrt(x.value())?;
+    if value == 0.0 {
+        return Err(EvalError::not_differentiable("sqrt", x.value()));
+    }
+    Ok(x.chain(value, 0.5 / value, -0.25 / (value * value * value)))
+}
+
+fn apply_jet<T: Jet>(function: &Function, x: T, angle_mode: AngleMode) -> Result<T, EvalError> {
+    let (value, first, second) = derivatives(function, x.value(), angle_mode)?;
+    Ok(x.chain(value, first, second))
+}
+
+fn check<T: Jet>(x: T) -> Result<T, EvalError> {
+    Numeric::check(x.value())?;
+    if !x.derivatives_finite() {
+        return Err(EvalError::Overflow { path: Vec::new() });
+    }
+    Ok(x)
+}
+
+// a function's value at x, the same as eval's, and its first and second derivatives there
+fn derivatives(
+    function: &Function,
+    x: f64,
+    angle_mode: AngleMode,
+) -> Result<(f64, f64, f64), EvalError> {
+    let value = <f64 as Numeric>::function(function, x, angle_mode)?;
+    // trig functions take their argument in angle_mode's unit, so they are f(kx) for radians f,
+    // and inverse trig functions give their result in it
+    let k = angle_mode.to_radians(1.0);
+    let m = angle_mode.from_radians(1.0);
+    let sin = || circular("sin", x, angle_mode).unwrap();
+    let cos = || circular("cos", x, angle_mode).unwrap();
+    let (first, second) = match function {
+        Function::Sin(_) => (k * cos(), -k * k * sin()),
+        Function::Cos(_) => (-k * sin(), -k * k * cos()),
+        Function::Tan(_) => (
+            k * (1.0 + value * value),
+            k * k * 2.0 * value * (1.0 + value * value),
+        ),
+        Function::Csc(_) => (
+            -k * cos() / sin().powi(2),
+            k * k * (cos().powi(2) + 1.0) / sin().powi(3),
+        ),
+        Function::Sec(_) => (
+            k * sin() / cos().powi(2),
+            k * k * (sin().powi(2) + 1.0) / cos().powi(3),
+        ),
+        Function::Cot(_) => (-k / sin().powi(2), k * k * 2.0 * cos() / sin().powi(3)),
+        Function::Arcsin(_) => (m / (1.0 - x * x).sqrt(), m * x / (1.0 - x * x).powf(1.5)),
+        Function::Arccos(_) => (-m / (1.0 - x * x).sqrt(), -m * x / (1.0 - x * x).powf(1.5)),
+        Function::Arctan(_) => (m / (1.0 + x * x), -m * 2.0 * x / (1.0 + x * x).powi(2)),
+        // with g = |x| sqrt(x^2 - 1), arccsc has derivatives -1 / g and x (2x^2 - 1) / g^3
+        Function::Arccsc(_) | Function::Arcsec(_) => {
+            let g = (x.powi(4) - x * x).sqrt();
+            let sign = if let Function::Arccsc(_) = function {
+                -1.0
+            } else {
+                1.0
+            };
+            (
+                sign * m / g,
+                -sign * m * x * (2.0 * x * x - 1.0) / g.powi(3),
+            )
+        }
+        // arccot jumps from -pi / 2 to pi / 2 at 0
+        Function::Arccot(_) if x == 0.0 => {
+            return Err(EvalError::not_differentiable(function.name(), x))
+        }
+        Function::Arccot(_) => (-m / (1.0 + x * x), m * 2.0 * x / (1.0 + x * x).powi(2)),
+        Function::Sinh(_) => (x.cosh(), x.sinh()),
+        Function::Cosh(_) => (x.sinh(), x.cosh()),
+        Function::Tanh(_) => (1.0 - value * value, -2.0 * value * (1.0 - value * value)),
+        Function::Csch(_) => (
+            -x.cosh() / x.sinh().powi(2),
+            (x.cosh().powi(2) + 1.0) / x.sinh().powi(3),
+        ),
+        Function::Sech(_) => (
+            -x.sinh() / x.cosh().powi(2),
+            (x.sinh().powi(2) - 1.0) / x.cosh().powi(3),
+        ),
+        Function::Coth(_) => (-1.0 / x.sinh().powi(2), 2.0 * x.cosh() / x.sinh().powi(3)),
+        Function::Arcsinh(_) => (1.0 / (x * x + 1.0).sqrt(), -x / (x * x + 1.0).powf(1.5)),
+        Function::Arccosh(_) => (1.0 / (x * x - 1.0).sqrt(), -x / (x * x - 1.0).powf(1.5)),
+        Function::Arctanh(_) | Function::Arccoth(_) => {
+            (1.0 / (1.0 - x * x), 2.0 * x / (1.0 - x * x).powi(2))
+        }
+        // with g = |x| sqrt(x^2 + 1), -1 / g and x (2x^2 + 1) / g^3
+        Function::Arccsch(_) => {
+            let g = (x.powi(4) + x * x).sqrt();
+            (-1.0 / g, x * (2.0 * x * x + 1.0) / g.powi(3))
+        }
+        // with g = x sqrt(1 - x^2), -1 / g and x (1 - 2x^2) / g^3
+        Function::Arcsech(_) => {
+            let g = (x * x - x.powi(4)).sqrt();
+            (-1.0 / g, x * (1.0 - 2.0 * x * x) / g.powi(3))
+        }
+        Function::Degrees(_) => (angle_mode.from_degrees(1.0), 0.0),
+    };
+    // like arcsin at 1, where the slope is vertical
+    if !(first.is_finite() && second.is_finite()) {
+        return Err(EvalError::not_differentiable(function.name(), x));
+    }
+    Ok((value, first, second))
+}
+
+// (uv)_ij = u v_ij + v u_ij + u_i v_j + u_j v_i
+fn product_hessian(u: &HyperDual, v: &HyperDual) -> Vec<Vec<f64>> {
+    let first = |gradient: &[f64], i: usize| gradient.get(i).copied().unwrap_or(0.0);
+    matrix(u.gradient.len().max(v.gradient.len()), |i, j| {
+        u.value * entry(&v.hessian, i, j)
+            + v.value * entry(&u.hessian, i, j)
+            + first(&u.gradient, i) * first(&v.gradient, j)
+            + first(&u.gradient, j) * first(&v.gradient, i)
+    })
+}
+
+// a * x + b * y, with the entries missing from the shorter of a and b taken as 0
+fn combine(a: &[f64], x: f64, b: &[f64], y: f64) -> Vec<f64> {
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).copied().unwrap_or(0.0) * x + b.get(i).copied().unwrap_or(0.0) * y)
+        .collect()
+}
+
+fn entry(matrix: &[Vec<f64>], i: usize, j: usize) -> f64 {
+    matrix
+        .get(i)
+        .and_then(|row| row.get(j))
+        .copied()
+        .unwrap_or(0.0)
+}
+
+fn matrix(size: usize, entry: impl Fn(usize, usize) -> f64) -> Vec<Vec<f64>> {
+    (0..size)
+        .map(|i| (0..size).map(|j| entry(i, j)).collect())
+        .collect()
+}
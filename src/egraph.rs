@@ -0,0 +1,801 @@
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
+
+use crate::{
+    expression::{
+        Addition, Division, Exponentiation, Expression, Function, Multiplication, Negation, Sqrt,
+    },
+    passes::like_terms::{compare_factors, compare_terms},
+    rules::{Rule, RuleSet},
+    simplifier::{simplify_bounded, Backend, LimitReached, SimplifyOptions},
+};
+
+// rules for the e-graph only, see rules/algebra.rules
+const ALGEBRA: &str = include_str!("../rules/algebra.rules");
+// the trig_reduce passes as rules, see rules/trig.rules
+const TRIG: &str = include_str!("../rules/trig.rules");
+
+// the most matches of one rule applied in one iteration, rules like distributing can match
+// a number of ways that grows with the graph and would otherwise crowd out every other rule
+const MATCH_LIMIT: usize = 1000;
+// the same for rules that multiply a sum out or factor one back in, each of their matches
+// adds a whole new sum or product to the graph, so only a few are taken in each iteration
+const SPREAD_LIMIT: usize = 20;
+// iterations in a row the cheapest form can go without getting any cheaper before the search stops,
+// distributing and factoring always find new ways to write the same thing so the graph hardly ever
+// saturates on its own, and every iteration makes it bigger
+const PATIENCE: usize = 2;
+
+// the index of an e-class, a set of expressions that are known to be equal
+type Id = usize;
+
+// one operator whose children are e-classes instead of expressions
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Node {
+    // the bits of the f64 so nodes can be hashed
+    Number(u64),
+    Variable(String),
+    // the children of sums and products are kept sorted so the order of terms doesn't matter
+    Add(Vec<Id>),
+    Mul(Vec<Id>),
+    Div(Id, Id),
+    Neg(Id),
+    Pow(Id, Id),
+    Sqrt(Id),
+    Function(&'static str, Id),
+}
+
+// what a pattern variable matched, either a whole e-class
+// or the leftover terms of a sum (true) or product (false)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Binding {
+    Class(Id),
+    Terms(bool, Vec<Id>),
+}
+
+type Bindings = Vec<(String, Binding)>;
+
+// a rule ready to be matched, with its pattern and replacement already extended to
+// match part of a longer sum or product where that applies
+struct Rewrite<'a> {
+    rule: &'a Rule,
+    pattern: Expression,
+    replacement: Expression,
+    // the most new matches taken in one iteration
+    limit: usize,
+}
+
+#[derive(Debug, Default)]
+struct EGraph {
+    // union find over e-class ids, in cells so find can shorten paths while the graph is borrowed
+    parents: Vec<Cell<Id>>,
+    // the nodes in each e-class, only kept up to date for the root of each class
+    classes: Vec<Vec<Node>>,
+    // every node once its children are canonical, and the e-class it belongs to
+    memo: HashMap<Node, Id>,
+    // when the time limit runs out, a search stops as soon as it has
+    deadline: Option<Instant>,
+}
+
+// simplifies by equality saturation: the expression goes into an e-graph, every rule is applied
+// everywhere it matches without throwing away the form it matched, and once nothing new is found,
+// the cheapest form stops getting cheaper or the limits are hit, that form is taken back out
+// rules are used in both directions here, so alongside the rules in the options it uses the
+// algebra and trig rules, which the pass simplifier can't since a rule and its reverse would undo each other
+pub fn simplify(
+    expression: Expression,
+    options: &SimplifyOptions,
+) -> (Expression, Option<LimitReached>) {
+    let algebra = RuleSet::parse("algebra", ALGEBRA).expect("the built in rules should parse");
+    let trig = RuleSet::parse("trig", TRIG).expect("the built in rules should parse");
+    let rewrites: Vec<Rewrite> = options
+        .rules
+        .iter()
+        .chain([&algebra, &trig])
+        .flat_map(|rule_set| &rule_set.rules)
+        .flat_map(rewrites)
+        .collect();
+
+    let mut egraph = EGraph {
+        deadline: Instant::now().checked_add(options.limits.max_time),
+        ..EGraph::default()
+    };
+    let root = egraph.add_expression(&expression);
+    // what the passes make of it goes in as well, so the search starts from a form at least that cheap
+    // and only has to keep going for as long as it finds something cheaper still
+    let passes = simplify_bounded(
+        expression.clone(),
+        &SimplifyOptions {
+            backend: Backend::Passes,
+            ..options.clone()
+        },
+    );
+    let seeded = egraph.add_expression(&passes.expression);
+    egraph.union(root, seeded);
+    egraph.rebuild();
+
+    // every match already applied, so a rule that only takes a few matches an iteration
+    // moves on to new ones instead of finding the same few every time
+    let mut applied = HashSet::new();
+    let mut forms = egraph.cheapest(options.cost);
+    let mut limit = None;
+    let mut iterations = 0;
+    let mut best_cost = usize::MAX;
+    let mut stalled = 0;
+    loop {
+        if iterations >= options.limits.max_iterations {
+            limit = Some(LimitReached::Iterations);
+            break;
+        }
+        iterations += 1;
+
+        // find every match first so the graph doesn't change while it is being searched
+        let mut matches = Vec::new();
+        for (index, rewrite) in rewrites.iter().enumerate() {
+            let mut found = HashSet::new();
+            for id in egraph.roots() {
+                let done = egraph.search(&rewrite.pattern, id, &mut Vec::new(), &mut |bindings| {
+                    if egraph.conditions_hold(rewrite.rule, bindings, &forms, options)
+                        && !applied.contains(&(id, index, bindings.clone()))
+                    {
+                        found.insert((id, bindings.clone()));
+                    }
+                    found.len() >= rewrite.limit
+                });
+                // a pattern with a few sums in it can take a long time to search a big graph
+                if done || egraph.out_of_time() {
+                    break;
+                }
+            }
+            matches.extend(
+                found
+                    .into_iter()
+                    .map(|(id, bindings)| (id, index, bindings)),
+            );
+            if egraph.out_of_time() {
+                limit = Some(LimitReached::Time);
+                break;
+            }
+        }
+
+        let mut changed = false;
+        for (id, index, bindings) in matches {
+            let rewritten = egraph.instantiate(&rewrites[index].replacement, &bindings);
+            changed |= egraph.union(id, rewritten);
+            applied.insert((id, index, bindings));
+            if egraph.memo.len() > options.limits.max_nodes {
+                limit = Some(LimitReached::Nodes);
+                break;
+            }
+            if egraph.out_of_time() {
+                limit = Some(LimitReached::Time);
+                break;
+            }
+        }
+        egraph.rebuild();
+
+        if limit.is_some() || !changed {
+            break;
+        }
+        if egraph.out_of_time() {
+            limit = Some(LimitReached::Time);
+            break;
+        }
+        // a number is as simple as it gets
+        if egraph.constant(root).is_some() {
+            break;
+        }
+        forms = egraph.cheapest(options.cost);
+        let cost = forms[egraph.find(root)]
+            .as_ref()
+            .map_or(usize::MAX, options.cost);
+        if cost < best_cost {
+            best_cost = cost;
+            stalled = 0;
+        } else {
+            stalled += 1;
+            if stalled >= PATIENCE {
+                break;
+            }
+        }
+    }
+
+    let extracted = egraph.extract(root, options.cost).unwrap_or(expression);
+    // the passes' form was in the graph all along, but extraction picks the cheapest form of each class
+    // on its own, which is only the cheapest whole when the cost adds up over the parts of an expression
+    if (options.cost)(&passes.expression) < (options.cost)(&extracted) {
+        return (passes.expression, limit.or(passes.limit));
+    }
+    (extracted, limit.or(passes.limit))
+}
+
+// a sum or product pattern also matches some of the terms of a longer sum or product,
+// the same as the pass simplifier does with rules
+fn rewrites(rule: &Rule) -> Vec<Rewrite<'_>> {
+    let limit = if spreads(&rule.pattern) || spreads(&rule.replacement) {
+        SPREAD_LIMIT
+    } else {
+        MATCH_LIMIT
+    };
+    let mut rewrites = vec![Rewrite {
+        rule,
+        pattern: rule.pattern.clone(),
+        replacement: rule.replacement.clone(),
+        limit,
+    }];
+    if let Some((pattern, replacement)) = rule.with_rest() {
+        rewrites.push(Rewrite {
+            rule,
+            pattern,
+            replacement,
+            limit,
+        });
+    }
+    rewrites
+}
+
+// whether a sum is multiplied, negated or raised to a power somewhere in the expression,
+// as in the a * (b + c) that distributing starts from and factoring ends at
+fn spreads(expression: &Expression) -> bool {
+    let is_sum = |term: &Expression| matches!(term, Expression::Addition(_));
+    match expression {
+        Expression::Number(_) | Expression::Variable(_) => false,
+        Expression::Addition(addition) => addition.terms.iter().any(|term| spreads(term)),
+        Expression::Multiplication(multiplication) => multiplication
+            .terms
+            .iter()
+            .any(|term| is_sum(term) || spreads(term)),
+        Expression::Negation(negation) => is_sum(&negation.term) || spreads(&negation.term),
+        Expression::Exponentiation(exponentiation) => {
+            is_sum(&exponentiation.base) || spreads(&exponentiation.base)
+        }
+        Expression::Division(division) => {
+            spreads(&division.numerator) || spreads(&division.denominator)
+        }
+        Expression::Sqrt(sqrt) => spreads(&sqrt.arg),
+        Expression::Function(function) => spreads(function.arg()),
+    }
+}
+
+impl EGraph {
+    fn out_of_time(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() > deadline)
+    }
+
+    fn find(&self, id: Id) -> Id {
+        let mut root = id;
+        while self.parents[root].get() != root {
+            root = self.parents[root].get();
+        }
+        // every class on the way points straight at the root, so the next find is one step
+        let mut id = id;
+        while id != root {
+            id = self.parents[id].replace(root);
+        }
+        root
+    }
+
+    fn roots(&self) -> Vec<Id> {
+        (0..self.parents.len())
+            .filter(|id| self.parents[*id].get() == *id)
+            .collect()
+    }
+
+    fn canonical(&self, node: &Node) -> Node {
+        match node {
+            Node::Number(_) | Node::Variable(_) => node.clone(),
+            Node::Add(children) => {
+                let mut children: Vec<Id> =
+                    children.iter().map(|child| self.find(*child)).collect();
+                children.sort();
+                Node::Add(children)
+            }
+            Node::Mul(children) => {
+                let mut children: Vec<Id> =
+                    children.iter().map(|child| self.find(*child)).collect();
+                children.sort();
+                Node::Mul(children)
+            }
+            Node::Div(a, b) => Node::Div(self.find(*a), self.find(*b)),
+            Node::Neg(a) => Node::Neg(self.find(*a)),
+            Node::Pow(a, b) => Node::Pow(self.find(*a), self.find(*b)),
+            Node::Sqrt(a) => Node::Sqrt(self.find(*a)),
+            Node::Function(name, a) => Node::Function(name, self.find(*a)),
+        }
+    }
+
+    fn add(&mut self, node: Node) -> Id {
+        let node = self.canonical(&node);
+        if let Some(id) = self.memo.get(&node) {
+            return self.find(*id);
+        }
+        // constant folding, 2 + 3 is just 5
+        if let Some(value) = self.fold(&node) {
+            return self.add(Node::Number(value.to_bits()));
+        }
+        let id = self.parents.len();
+        self.parents.push(Cell::new(id));
+        self.classes.push(vec![node.clone()]);
+        self.memo.insert(node, id);
+        id
+    }
+
+    fn add_expression(&mut self, expression: &Expression) -> Id {
+        let node = match expression {
+            Expression::Number(n) => Node::Number(n.to_bits()),
+            Expression::Variable(v) => Node::Variable(v.clone()),
+            Expression::Addition(addition) => Node::Add(self.add_terms(&addition.terms, true)),
+            Expression::Multiplication(multiplication) => {
+                Node::Mul(self.add_terms(&multiplication.terms, false))
+            }
+            Expression::Division(division) => Node::Div(
+                self.add_expression(&division.numerator),
+                self.add_expression(&division.denominator),
+            ),
+            Expression::Negation(negation) => Node::Neg(self.add_expression(&negation.term)),
+            Expression::Exponentiation(exponentiation) => Node::Pow(
+                self.add_expression(&exponentiation.base),
+                self.add_expression(&exponentiation.exponent),
+            ),
+            Expression::Sqrt(sqrt) => Node::Sqrt(self.add_expression(&sqrt.arg)),
+            Expression::Function(function) => {
+                Node::Function(function.name(), self.add_expression(function.arg()))
+            }
+        };
+        self.add(node)
+    }
+
+    // adds the terms of a sum or product, flattening nested sums into sums and products into products
+    #[allow(clippy::vec_box)]
+    fn add_terms(&mut self, terms: &Vec<Box<Expression>>, sum: bool) -> Vec<Id> {
+        let mut ids = Vec::new();
+        for term in terms {
+            match &**term {
+                Expression::Addition(addition) if sum => {
+                    ids.extend(self.add_terms(&addition.terms, sum))
+                }
+                Expression::Multiplication(multiplication) if !sum => {
+                    ids.extend(self.add_terms(&multiplication.terms, sum))
+                }
+                term => ids.push(self.add_expression(term)),
+            }
+        }
+        ids
+    }
+
+    fn union(&mut self, a: Id, b: Id) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        self.parents[b].set(a);
+        let nodes = std::mem::take(&mut self.classes[b]);
+        self.classes[a].extend(nodes);
+        true
+    }
+
+    // after a union two nodes that were different can become the same, f(a) and f(b) once a = b,
+    // this merges their e-classes until every node is in exactly one
+    fn rebuild(&mut self) {
+        loop {
+            let mut merges = Vec::new();
+            self.memo.clear();
+            for id in self.roots() {
+                let mut seen = HashSet::new();
+                let mut nodes: Vec<Node> = self.classes[id]
+                    .iter()
+                    .map(|node| self.canonical(node))
+                    .collect();
+                nodes.retain(|node| seen.insert(node.clone()));
+                // a class with a known value is just that number, otherwise the rules keep finding
+                // new ways to write it like 1 + 1 and 2 * 1 and the graph fills up with arithmetic
+                if self.constant(id).is_some() {
+                    nodes.retain(|node| matches!(node, Node::Number(_)));
+                }
+                for node in &nodes {
+                    match self.memo.get(node) {
+                        Some(other) if self.find(*other) != id => merges.push((*other, id)),
+                        Some(_) => {}
+                        None => {
+                            self.memo.insert(node.clone(), id);
+                        }
+                    }
+                }
+                self.classes[id] = nodes;
+            }
+            if merges.is_empty() {
+                break;
+            }
+            for (a, b) in merges {
+                self.union(a, b);
+            }
+        }
+    }
+
+    fn constant(&self, id: Id) -> Option<f64> {
+        self.classes[self.find(id)]
+            .iter()
+            .find_map(|node| match node {
+                Node::Number(bits) => Some(f64::from_bits(*bits)),
+                _ => None,
+            })
+    }
+
+    fn fold(&self, node: &Node) -> Option<f64> {
+        let value = match node {
+            Node::Add(children) => children
+                .iter()
+                .map(|child| self.constant(*child))
+                .sum::<Option<f64>>()?,
+            Node::Mul(children) => children
+                .iter()
+                .map(|child| self.constant(*child))
+                .product::<Option<f64>>()?,
+            Node::Neg(a) => -self.constant(*a)?,
+            // only kept when exact, so 1/3 stays a fraction
+            Node::Div(a, b) => {
+                let (a, b) = (self.constant(*a)?, self.constant(*b)?);
+                if b == 0.0 || (a / b).fract() != 0.0 {
+                    return None;
+                }
+                a / b
+            }
+            Node::Pow(a, b) => {
+                let (a, b) = (self.constant(*a)?, self.constant(*b)?);
+                let value = a.powf(b);
+                if value.fract() != 0.0 || value.abs() >= 9007199254740992.0 {
+                    return None;
+                }
+                value
+            }
+            _ => return None,
+        };
+        value.is_finite().then_some(value)
+    }
+
+    // the conditions are checked against the cheapest form of what each variable matched,
+    // which is a number or a variable whenever the class has one in it
+    fn conditions_hold(
+        &self,
+        rule: &Rule,
+        bindings: &Bindings,
+        forms: &[Option<Expression>],
+        options: &SimplifyOptions,
+    ) -> bool {
+        rule.conditions.iter().all(|condition| {
+            match bindings
+                .iter()
+                .find(|(variable, _)| *variable == condition.variable)
+            {
+                Some((_, Binding::Class(id))) => {
+                    forms[self.find(*id)].as_ref().is_some_and(|value| {
+                        condition.holds(value, &options.assumptions, options.angle_mode)
+                    })
+                }
+                _ => false,
+            }
+        })
+    }
+
+    // calls `found` with the bindings of every way the pattern matches something in the e-class,
+    // stopping early once `found` returns true
+    fn search(
+        &self,
+        pattern: &Expression,
+        id: Id,
+        bindings: &mut Bindings,
+        found: &mut dyn FnMut(&mut Bindings) -> bool,
+    ) -> bool {
+        if self.out_of_time() {
+            return true;
+        }
+        let id = self.find(id);
+        if let Expression::Variable(variable) = pattern {
+            return match bindings.iter().find(|(bound, _)| bound == variable) {
+                Some((_, Binding::Class(bound))) => self.find(*bound) == id && found(bindings),
+                Some(_) => false,
+                None => {
+                    bindings.push((variable.clone(), Binding::Class(id)));
+                    let done = found(bindings);
+                    bindings.pop();
+                    done
+                }
+            };
+        }
+        if let Expression::Number(n) = pattern {
+            return self.constant(id) == Some(*n) && found(bindings);
+        }
+
+        for node in &self.classes[id] {
+            let done = match (pattern, node) {
+                (Expression::Addition(pattern), Node::Add(children)) => {
+                    let patterns: Vec<&Expression> =
+                        pattern.terms.iter().map(|term| &**term).collect();
+                    self.flattenings(id, children, true)
+                        .into_iter()
+                        .any(|terms| self.search_terms(&patterns, terms, true, bindings, found))
+                }
+                (Expression::Multiplication(pattern), Node::Mul(children)) => {
+                    let patterns: Vec<&Expression> =
+                        pattern.terms.iter().map(|term| &**term).collect();
+                    self.flattenings(id, children, false)
+                        .into_iter()
+                        .any(|terms| self.search_terms(&patterns, terms, false, bindings, found))
+                }
+                (Expression::Division(pattern), Node::Div(a, b)) => {
+                    self.search(&pattern.numerator, *a, bindings, &mut |bindings| {
+                        self.search(&pattern.denominator, *b, bindings, found)
+                    })
+                }
+                (Expression::Exponentiation(pattern), Node::Pow(a, b)) => {
+                    self.search(&pattern.base, *a, bindings, &mut |bindings| {
+                        self.search(&pattern.exponent, *b, bindings, found)
+                    })
+                }
+                (Expression::Negation(pattern), Node::Neg(a)) => {
+                    self.search(&pattern.term, *a, bindings, found)
+                }
+                (Expression::Sqrt(pattern), Node::Sqrt(a)) => {
+                    self.search(&pattern.arg, *a, bindings, found)
+                }
+                (Expression::Function(pattern), Node::Function(name, a))
+                    if pattern.name() == *name =>
+                {
+                    self.search(pattern.arg(), *a, bindings, found)
+                }
+                _ => false,
+            };
+            if done {
+                return true;
+            }
+        }
+        false
+    }
+
+    // the terms of a sum as they are and with each term that is itself a sum spliced in, the same
+    // for products, so a rule sees the -x in x * (x + 1) - x next to the x it cancels once the product
+    // is distributed, the graph only ever holds the flat sums that rules build from these
+    fn flattenings(&self, id: Id, terms: &[Id], sum: bool) -> Vec<Vec<Id>> {
+        let mut flattenings = vec![terms.to_vec()];
+        for (i, term) in terms.iter().enumerate() {
+            let term = self.find(*term);
+            for node in &self.classes[term] {
+                let inner = match (node, sum) {
+                    (Node::Add(inner), true) | (Node::Mul(inner), false) => inner,
+                    _ => continue,
+                };
+                // a class among its own terms, like x = x + 0, would splice in forever
+                if term == id || inner.iter().any(|inner| self.find(*inner) == id) {
+                    continue;
+                }
+                let mut flattened = terms.to_vec();
+                flattened.remove(i);
+                flattened.extend(inner);
+                flattened.sort();
+                flattenings.push(flattened);
+            }
+        }
+        flattenings
+    }
+
+    // matches the terms of a sum or product in any order, a last pattern term that is a variable
+    // takes all of the leftover terms
+    fn search_terms(
+        &self,
+        patterns: &[&Expression],
+        terms: Vec<Id>,
+        sum: bool,
+        bindings: &mut Bindings,
+        found: &mut dyn FnMut(&mut Bindings) -> bool,
+    ) -> bool {
+        if patterns.is_empty() {
+            return terms.is_empty() && found(bindings);
+        }
+        if patterns.len() > terms.len() {
+            return false;
+        }
+        if let [Expression::Variable(variable)] = patterns {
+            if terms.len() > 1 {
+                let mut sorted: Vec<Id> = terms.iter().map(|term| self.find(*term)).collect();
+                sorted.sort();
+                return match bindings.iter().find(|(bound, _)| bound == variable) {
+                    Some((_, bound)) => *bound == Binding::Terms(sum, sorted) && found(bindings),
+                    None => {
+                        bindings.push((variable.clone(), Binding::Terms(sum, sorted)));
+                        let done = found(bindings);
+                        bindings.pop();
+                        done
+                    }
+                };
+            }
+        }
+
+        let index = patterns
+            .iter()
+            .position(|pattern| !matches!(pattern, Expression::Variable(_)))
+            .unwrap_or(0);
+        let mut others = patterns.to_vec();
+        let pattern = others.remove(index);
+        for i in 0..terms.len() {
+            // the same e-class twice in a row would only find the same matches again
+            if i > 0 && terms[i] == terms[i - 1] {
+                continue;
+            }
+            let mut rest = terms.clone();
+            let term = rest.remove(i);
+            let done = self.search(pattern, term, bindings, &mut |bindings| {
+                self.search_terms(&others, rest.clone(), sum, bindings, found)
+            });
+            if done {
+                return true;
+            }
+        }
+        false
+    }
+
+    // adds the replacement with every pattern variable swapped for what it matched
+    fn instantiate(&mut self, replacement: &Expression, bindings: &Bindings) -> Id {
+        let node = match replacement {
+            Expression::Variable(variable) => {
+                match bindings.iter().find(|(bound, _)| bound == variable) {
+                    Some((_, Binding::Class(id))) => return self.find(*id),
+                    Some((_, Binding::Terms(true, ids))) => Node::Add(ids.clone()),
+                    Some((_, Binding::Terms(false, ids))) => Node::Mul(ids.clone()),
+                    None => Node::Variable(variable.clone()),
+                }
+            }
+            Expression::Number(n) => Node::Number(n.to_bits()),
+            Expression::Addition(addition) => {
+                Node::Add(self.instantiate_terms(&addition.terms, true, bindings))
+            }
+            Expression::Multiplication(multiplication) => {
+                Node::Mul(self.instantiate_terms(&multiplication.terms, false, bindings))
+            }
+            Expression::Division(division) => Node::Div(
+                self.instantiate(&division.numerator, bindings),
+                self.instantiate(&division.denominator, bindings),
+            ),
+            Expression::Negation(negation) => Node::Neg(self.instantiate(&negation.term, bindings)),
+            Expression::Exponentiation(exponentiation) => Node::Pow(
+                self.instantiate(&exponentiation.base, bindings),
+                self.instantiate(&exponentiation.exponent, bindings),
+            ),
+            Expression::Sqrt(sqrt) => Node::Sqrt(self.instantiate(&sqrt.arg, bindings)),
+            Expression::Function(function) => {
+                Node::Function(function.name(), self.instantiate(function.arg(), bindings))
+            }
+        };
+        self.add(node)
+    }
+
+    // the terms of a sum or product, with nested sums and leftover terms spliced in so sums stay flat
+    #[allow(clippy::vec_box)]
+    fn instantiate_terms(
+        &mut self,
+        terms: &Vec<Box<Expression>>,
+        sum: bool,
+        bindings: &Bindings,
+    ) -> Vec<Id> {
+        let mut ids = Vec::new();
+        for term in terms {
+            match &**term {
+                Expression::Addition(addition) if sum => {
+                    ids.extend(self.instantiate_terms(&addition.terms, sum, bindings))
+                }
+                Expression::Multiplication(multiplication) if !sum => {
+                    ids.extend(self.instantiate_terms(&multiplication.terms, sum, bindings))
+                }
+                Expression::Variable(variable) => {
+                    match bindings.iter().find(|(bound, _)| bound == variable) {
+                        Some((_, Binding::Terms(kind, terms))) if *kind == sum => ids.extend(terms),
+                        _ => ids.push(self.instantiate(term, bindings)),
+                    }
+                }
+                term => ids.push(self.instantiate(term, bindings)),
+            }
+        }
+        ids
+    }
+
+    // the cheapest expression in the e-class
+    fn extract(&self, root: Id, cost: fn(&Expression) -> usize) -> Option<Expression> {
+        self.cheapest(cost).swap_remove(self.find(root))
+    }
+
+    // the cheapest expression in every e-class, found by repeatedly building the cheapest expression
+    // for every node out of the cheapest expressions of its children until nothing gets cheaper
+    // forms that cost the same go to the one that writes the variables fewer times, x^2 over x * x
+    fn cheapest(&self, cost: fn(&Expression) -> usize) -> Vec<Option<Expression>> {
+        let mut best: Vec<Option<((usize, usize), Expression)>> = vec![None; self.parents.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for id in self.roots() {
+                for node in &self.classes[id] {
+                    let Some(expression) = self.build(node, &best) else {
+                        continue;
+                    };
+                    let node_cost = (cost(&expression), occurrences(&expression));
+                    if best[id]
+                        .as_ref()
+                        .map_or(true, |(best_cost, _)| node_cost < *best_cost)
+                    {
+                        best[id] = Some((node_cost, expression));
+                        changed = true;
+                    }
+                }
+            }
+        }
+        best.into_iter()
+            .map(|best| best.map(|(_, expression)| expression))
+            .collect()
+    }
+
+    fn build(
+        &self,
+        node: &Node,
+        best: &[Option<((usize, usize), Expression)>],
+    ) -> Option<Expression> {
+        let child = |id: &Id| {
+            best[self.find(*id)]
+                .as_ref()
+                .map(|(_, expression)| Box::new(expression.clone()))
+        };
+        Some(match node {
+            Node::Number(bits) => Expression::Number(f64::from_bits(*bits)),
+            Node::Variable(v) => Expression::Variable(v.clone()),
+            // children are in e-class order, so they are put in the order the pass simplifier uses,
+            // highest powers first for sums and numbers first for products
+            Node::Add(children) => {
+                let mut terms: Vec<Box<Expression>> =
+                    children.iter().map(child).collect::<Option<_>>()?;
+                terms.sort_by(|a, b| compare_terms(a, b));
+                Expression::Addition(Addition { terms })
+            }
+            Node::Mul(children) => {
+                let mut terms: Vec<Box<Expression>> =
+                    children.iter().map(child).collect::<Option<_>>()?;
+                terms.sort_by(|a, b| compare_factors(a, b));
+                Expression::Multiplication(Multiplication { terms })
+            }
+            Node::Div(a, b) => Expression::Division(Division {
+                numerator: child(a)?,
+                denominator: child(b)?,
+            }),
+            Node::Neg(a) => Expression::Negation(Negation { term: child(a)? }),
+            Node::Pow(a, b) => Expression::Exponentiation(Exponentiation {
+                base: child(a)?,
+                exponent: child(b)?,
+            }),
+            Node::Sqrt(a) => Expression::Sqrt(Sqrt { arg: child(a)? }),
+            Node::Function(name, a) => Expression::Function(Function::from_name(name, child(a)?)?),
+        })
+    }
+}
+
+// how many times variables are written in the expression
+fn occurrences(expression: &Expression) -> usize {
+    match expression {
+        Expression::Number(_) => 0,
+        Expression::Variable(_) => 1,
+        Expression::Addition(addition) => addition.terms.iter().map(|term| occurrences(term)).sum(),
+        Expression::Multiplication(multiplication) => multiplication
+            .terms
+            .iter()
+            .map(|term| occurrences(term))
+            .sum(),
+        Expression::Division(division) => {
+            occurrences(&division.numerator) + occurrences(&division.denominator)
+        }
+        Expression::Negation(negation) => occurrences(&negation.term),
+        Expression::Exponentiation(exponentiation) => {
+            occurrences(&exponentiation.base) + occurrences(&exponentiation.exponent)
+        }
+        Expression::Sqrt(sqrt) => occurrences(&sqrt.arg),
+        Expression::Function(function) => occurrences(function.arg()),
+    }
+}
@@ -0,0 +1,189 @@
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    angle::AngleMode,
+    complex::IMAGINARY_UNIT,
+    eval_error::EvalError,
+    expression::{evaluate, Expression},
+    latex::to_latex,
+    numeric::Numeric,
+    substitution::{free_variables, substitute_all},
+};
+
+// what a variable is bound to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Binding {
+    Value(f64),
+    // evaluated whenever the variable is, so it follows later changes to the variables it uses
+    Expression(Expression),
+    // a value that can't be reassigned, like g = 9.81
+    Constant(f64),
+}
+
+// the values of variables for eval_with, with no bindings it evaluates like eval_in
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Environment {
+    bindings: BTreeMap<String, Binding>,
+    pub angle_mode: AngleMode,
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        Environment::default()
+    }
+
+    pub fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        self.bind(name, Binding::Value(value))
+    }
+
+    // binds `name` to an expression that is only evaluated when `name` is
+    pub fn set_expression(&mut self, name: &str, expression: Expression) -> Result<(), String> {
+        if free_variables(&expression).contains(name) {
+            return Err(format!("{} can't be defined in terms of itself", name));
+        }
+        self.bind(name, Binding::Expression(expression))
+    }
+
+    pub fn define_constant(&mut self, name: &str, value: f64) -> Result<(), String> {
+        self.bind(name, Binding::Constant(value))
+    }
+
+    // x = 3 binds a value, y = x^2 binds the expression so y follows x and x = x + 1 uses the old x
+    pub fn assign(&mut self, name: &str, expression: Expression) -> Result<(), String> {
+        let variables = free_variables(&expression);
+        if variables.is_empty() || variables.contains(name) {
+            let value = evaluate(&expression, self.angle_mode, &|variable| {
+                self.value(variable)
+            })
+            .map_err(|err| err.to_string())?;
+            self.set(name, value)
+        } else {
+            self.set_expression(name, expression)
+        }
+    }
+
+    fn bind(&mut self, name: &str, binding: Binding) -> Result<(), String> {
+        if name == IMAGINARY_UNIT {
+            return Err(format!(
+                "{} is the imaginary unit and can't be assigned",
+                name
+            ));
+        }
+        if let Some(Binding::Constant(_)) = self.bindings.get(name) {
+            return Err(format!("{} is a constant and can't be reassigned", name));
+        }
+        self.bindings.insert(name.to_string(), binding);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Binding> {
+        self.bindings.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Binding> {
+        self.bindings.remove(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    // the expression with every bound variable replaced by what it's bound to, following expressions
+    // down to the variables that are unbound, the ones in a cycle like x = y, y = x are left as they are
+    pub fn substitute_bindings(&self, expression: &Expression) -> Expression {
+        let mut expression = expression.clone();
+        // each round replaces one more level of bindings, a chain can't be longer than there are bindings
+        for _ in 0..=self.bindings.len() {
+            let substitutions: Vec<(String, Expression)> = free_variables(&expression)
+                .into_iter()
+                .filter_map(|name| {
+                    let replacement = match self.bindings.get(&name)? {
+                        Binding::Value(value) | Binding::Constant(value) => {
+                            Expression::Number(*value)
+                        }
+                        Binding::Expression(bound) => bound.clone(),
+                    };
+                    Some((name, replacement))
+                })
+                .collect();
+            if substitutions.is_empty() {
+                break;
+            }
+            expression = substitute_all(&expression, &substitutions);
+        }
+        expression
+    }
+
+    // the value of a variable, evaluating the expression it's bound to if it is
+    pub fn value(&self, name: &str) -> Result<f64, EvalError> {
+        self.value_as(name)
+    }
+
+    // value, for evaluation over another Numeric type
+    pub fn value_as<T: Numeric>(&self, name: &str) -> Result<T, EvalError> {
+        self.resolve(name, &[], &|_| None)
+    }
+
+    // value_as, with the variables `seeded` gives values taking them over their bindings, so differentiating
+    // with respect to x follows x through y = x^2
+    pub fn value_seeded<T: Numeric>(
+        &self,
+        name: &str,
+        seeded: &dyn Fn(&str) -> Option<Result<T, EvalError>>,
+    ) -> Result<T, EvalError> {
+        self.resolve(name, &[], seeded)
+    }
+
+    // `visiting` are the variables whose expressions are being evaluated, to catch x = y, y = x
+    fn resolve<T: Numeric>(
+        &self,
+        name: &str,
+        visiting: &[&str],
+        seeded: &dyn Fn(&str) -> Option<Result<T, EvalError>>,
+    ) -> Result<T, EvalError> {
+        if visiting.contains(&name) {
+            return Err(EvalError::CircularDefinition {
+                name: name.to_string(),
+                path: Vec::new(),
+            });
+        }
+        if let Some(value) = seeded(name) {
+            return value;
+        }
+        match self.bindings.get(name) {
+            Some(Binding::Value(value)) | Some(Binding::Constant(value)) => Ok(T::from_f64(*value)),
+            Some(Binding::Expression(expression)) => {
+                let mut visiting = visiting.to_vec();
+                visiting.push(name);
+                evaluate(expression, self.angle_mode, &|variable| {
+                    self.resolve(variable, &visiting, seeded)
+                })
+            }
+            None => Err(EvalError::UnboundVariable {
+                name: name.to_string(),
+                path: Vec::new(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Binding::Value(value) => write!(f, "{}", value),
+            Binding::Expression(expression) => write!(f, "{}", to_latex(expression)),
+            Binding::Constant(value) => write!(f, "{} (constant)", value),
+        }
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bindings: Vec<String> = self
+            .bindings
+            .iter()
+            .map(|(name, binding)| format!("{} = {}", name, binding))
+            .collect();
+        write!(f, "{}", bindings.join(", "))
+    }
+}
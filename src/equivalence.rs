@@ -0,0 +1,220 @@
+use std::fmt;
+
+use crate::{
+    eval_error::EvalError,
+    expression::{eval_in, Addition, Expression, Negation},
+    simplifier::{simplify_with_options, SimplifyOptions},
+    substitution::{free_variables, substitute_all},
+};
+
+// how many points the numeric check tries to agree at
+const SAMPLES: usize = 100;
+// how many points it draws before giving up, most of the others being outside the domain
+const ATTEMPTS: usize = 1000;
+// how far apart two values can be and still count as equal, relative to their size
+const TOLERANCE: f64 = 1e-9;
+
+// whether two expressions are equal for every value of their variables
+#[derive(Debug, Clone, PartialEq)]
+pub enum Equivalence {
+    // they simplify to the same expression
+    Proven,
+    // they couldn't be simplified to the same expression but agreed at every point that was tried
+    Probable { samples: usize },
+    // a point where they differ
+    Disproven(Counterexample),
+    // no point could be tried, with the error from the last point where either side was undefined,
+    // or None when the assumptions ruled out every point
+    Undetermined(Option<EvalError>),
+}
+
+// values of the variables where two expressions differ
+#[derive(Debug, Clone, PartialEq)]
+pub struct Counterexample {
+    pub values: Vec<(String, f64)>,
+    pub left: f64,
+    pub right: f64,
+}
+
+// checks whether a = b for every value of their variables
+pub fn equivalent(a: &Expression, b: &Expression) -> Equivalence {
+    equivalent_with_options(a, b, &SimplifyOptions::default())
+}
+
+// same as equivalent, with the simplifier's options and only trying values the assumptions allow
+pub fn equivalent_with_options(
+    a: &Expression,
+    b: &Expression,
+    options: &SimplifyOptions,
+) -> Equivalence {
+    // the simplifier puts equal expressions into the same form often enough that this is tried first,
+    // either they simplify to the same thing or their difference simplifies to 0
+    let simplified_a = simplify_with_options(a.clone(), options);
+    let simplified_b = simplify_with_options(b.clone(), options);
+    if simplified_a == simplified_b {
+        return Equivalence::Proven;
+    }
+    let difference = Expression::Addition(Addition {
+        terms: vec![
+            Box::new(a.clone()),
+            Box::new(Expression::Negation(Negation {
+                term: Box::new(b.clone()),
+            })),
+        ],
+    });
+    if simplify_with_options(difference, options) == Expression::Number(0.0) {
+        return Equivalence::Proven;
+    }
+
+    // otherwise they are compared at random points, skipping points where either side is undefined
+    let mut variables = free_variables(a);
+    variables.extend(free_variables(b));
+    let variables: Vec<String> = variables.into_iter().collect();
+    let mut random = Random::new();
+    let mut samples = 0;
+    let mut error = None;
+    for _ in 0..ATTEMPTS {
+        if samples == SAMPLES {
+            break;
+        }
+        let values: Vec<(String, f64)> = variables
+            .iter()
+            .map(|variable| (variable.clone(), random.sample()))
+            .collect();
+        if !values
+            .iter()
+            .all(|(variable, value)| options.assumptions.allows(variable, *value))
+        {
+            continue;
+        }
+        let (left, right) = match (
+            evaluate_at(a, &values, options),
+            evaluate_at(b, &values, options),
+        ) {
+            (Ok(left), Ok(right)) => (left, right),
+            (Err(err), _) | (_, Err(err)) => {
+                error = Some(err);
+                continue;
+            }
+        };
+        if !close(left, right) {
+            return Equivalence::Disproven(round(
+                a,
+                b,
+                Counterexample {
+                    values,
+                    left,
+                    right,
+                },
+                options,
+            ));
+        }
+        samples += 1;
+        // without variables every point is the same point
+        if variables.is_empty() {
+            break;
+        }
+    }
+    if samples == 0 {
+        return Equivalence::Undetermined(error);
+    }
+    Equivalence::Probable { samples }
+}
+
+// rounds the values of a counterexample to whole numbers where they still show the difference,
+// x = 2 is easier to check by hand than x = 1.7316...
+fn round(
+    a: &Expression,
+    b: &Expression,
+    mut counterexample: Counterexample,
+    options: &SimplifyOptions,
+) -> Counterexample {
+    for i in 0..counterexample.values.len() {
+        let value = counterexample.values[i].1;
+        // adding 0 turns -0 into 0
+        for rounded in [value.round() + 0.0, value.floor() + 0.0, value.ceil() + 0.0] {
+            let mut values = counterexample.values.clone();
+            values[i].1 = rounded;
+            if !options.assumptions.allows(&values[i].0, rounded) {
+                continue;
+            }
+            if let (Ok(left), Ok(right)) = (
+                evaluate_at(a, &values, options),
+                evaluate_at(b, &values, options),
+            ) {
+                if !close(left, right) {
+                    counterexample = Counterexample {
+                        values,
+                        left,
+                        right,
+                    };
+                    break;
+                }
+            }
+        }
+    }
+    counterexample
+}
+
+// the value of the expression with each variable replaced by its value, or why it has none there
+fn evaluate_at(
+    expression: &Expression,
+    values: &[(String, f64)],
+    options: &SimplifyOptions,
+) -> Result<f64, EvalError> {
+    match eval_in(&with_values(expression, values), options.angle_mode)? {
+        value if value.is_nan() => Err(EvalError::NaN { path: Vec::new() }),
+        value if value.is_infinite() => Err(EvalError::Overflow { path: Vec::new() }),
+        value => Ok(value + 0.0),
+    }
+}
+
+fn with_values(expression: &Expression, values: &[(String, f64)]) -> Expression {
+    let values: Vec<(String, Expression)> = values
+        .iter()
+        .map(|(name, value)| (name.clone(), Expression::Number(*value)))
+        .collect();
+    substitute_all(expression, &values)
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= TOLERANCE * a.abs().max(b.abs()).max(1.0)
+}
+
+// a small xorshift generator with a fixed seed, so the same check always tries the same points
+struct Random(u64);
+
+impl Random {
+    fn new() -> Random {
+        Random(0x2545f4914f6cdd1d)
+    }
+
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // a value between -10 and 10, rounded half the time so integer assumptions can be met
+    fn sample(&mut self) -> f64 {
+        let value = self.next() * 20.0 - 10.0;
+        if self.next() < 0.5 {
+            value.round() + 0.0
+        } else {
+            value
+        }
+    }
+}
+
+impl fmt::Display for Counterexample {
+    // the values of the variables, like x = 1, y = 2
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let values: Vec<String> = self
+            .values
+            .iter()
+            .map(|(variable, value)| format!("{} = {}", variable, value))
+            .collect();
+        write!(f, "{}", values.join(", "))
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+
+use crate::{
+    angle::AngleMode,
+    complex::{format_complex, Complex, ComplexForm},
+};
+
+// why an expression couldn't be evaluated, `path` leads from the root to the node that failed the same
+// way a step's does, so the part of the expression at fault can be pointed out
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind")]
+pub enum EvalError {
+    DivisionByZero {
+        path: Vec<usize>,
+    },
+    // a function given a value outside its domain or at a pole, like sqrt(-1) or tan(pi / 2)
+    // `imaginary` is the imaginary part of the value in complex evaluation
+    DomainError {
+        function: String,
+        value: f64,
+        imaginary: f64,
+        path: Vec<usize>,
+    },
+    UnboundVariable {
+        name: String,
+        path: Vec<usize>,
+    },
+    // a variable whose binding ends up depending on itself
+    CircularDefinition {
+        name: String,
+        path: Vec<usize>,
+    },
+    // finite inputs giving a result too large for an f64
+    Overflow {
+        path: Vec<usize>,
+    },
+    NaN {
+        path: Vec<usize>,
+    },
+    // the imaginary unit outside of complex evaluation
+    NotReal {
+        path: Vec<usize>,
+    },
+    // a function with a value but no derivative there, like sqrt at 0, when differentiating
+    NotDifferentiable {
+        function: String,
+        value: f64,
+        path: Vec<usize>,
+    },
+}
+
+impl EvalError {
+    pub fn division_by_zero() -> EvalError {
+        EvalError::DivisionByZero { path: Vec::new() }
+    }
+
+    pub fn domain(function: &str, value: f64) -> EvalError {
+        EvalError::DomainError {
+            function: function.to_string(),
+            value,
+            imaginary: 0.0,
+            path: Vec::new(),
+        }
+    }
+
+    pub fn complex_domain(function: &str, value: Complex) -> EvalError {
+        EvalError::DomainError {
+            function: function.to_string(),
+            value: value.re,
+            imaginary: value.im,
+            path: Vec::new(),
+        }
+    }
+
+    pub fn not_differentiable(function: &str, value: f64) -> EvalError {
+        EvalError::NotDifferentiable {
+            function: function.to_string(),
+            value,
+            path: Vec::new(),
+        }
+    }
+
+    pub fn path(&self) -> &[usize] {
+        match self {
+            EvalError::DivisionByZero { path }
+            | EvalError::DomainError { path, .. }
+            | EvalError::UnboundVariable { path, .. }
+            | EvalError::CircularDefinition { path, .. }
+            | EvalError::Overflow { path }
+            | EvalError::NaN { path }
+            | EvalError::NotReal { path }
+            | EvalError::NotDifferentiable { path, .. } => path,
+        }
+    }
+
+    fn path_mut(&mut self) -> &mut Vec<usize> {
+        match self {
+            EvalError::DivisionByZero { path }
+            | EvalError::DomainError { path, .. }
+            | EvalError::UnboundVariable { path, .. }
+            | EvalError::CircularDefinition { path, .. }
+            | EvalError::Overflow { path }
+            | EvalError::NaN { path }
+            | EvalError::NotReal { path }
+            | EvalError::NotDifferentiable { path, .. } => path,
+        }
+    }
+
+    // the error of a node's `index`th child, as seen from the node
+    pub fn within(mut self, index: usize) -> EvalError {
+        self.path_mut().insert(0, index);
+        self
+    }
+
+    // the same error pointing at `path` instead, a variable's binding failing is the variable's fault
+    pub fn at(mut self, path: Vec<usize>) -> EvalError {
+        *self.path_mut() = path;
+        self
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::DivisionByZero { .. } => write!(f, "Division by zero"),
+            EvalError::DomainError {
+                function,
+                value,
+                imaginary: 0.0,
+                ..
+            } => {
+                write!(f, "{} is undefined for {}", function, value)
+            }
+            EvalError::DomainError {
+                function,
+                value,
+                imaginary,
+                ..
+            } => write!(
+                f,
+                "{} is undefined for {}",
+                function,
+                format_complex(
+                    Complex::new(*value, *imaginary),
+                    ComplexForm::Rectangular,
+                    AngleMode::Radians
+                )
+            ),
+            EvalError::UnboundVariable { name, .. } => {
+                write!(f, "Cannot evaluate variable {}", name)
+            }
+            EvalError::CircularDefinition { name, .. } => {
+                write!(f, "{} is defined in terms of itself", name)
+            }
+            EvalError::Overflow { .. } => write!(f, "Result is too large"),
+            EvalError::NaN { .. } => write!(f, "Result is not a number"),
+            EvalError::NotReal { .. } => write!(f, "i is only a number in complex mode"),
+            EvalError::NotDifferentiable {
+                function, value, ..
+            } => {
+                write!(f, "{} is not differentiable at {}", function, value)
+            }
+        }
+    }
+}
@@ -1,42 +1,55 @@
-use crate::parser::{ASTNode, BinaryOp, UnaryOp};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    mem::discriminant,
+};
 
-#[derive(Debug, Clone)]
+use crate::{
+    angle::{circular, inverse_circular, AngleMode},
+    complex::IMAGINARY_UNIT,
+    environment::Environment,
+    eval_error::EvalError,
+    numeric::Numeric,
+    parser::{ASTNode, BinaryOp, UnaryOp},
+};
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Multiplication {
     //TODO: don't have box here?
     #[allow(clippy::vec_box)]
     pub terms: Vec<Box<Expression>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Division {
     pub numerator: Box<Expression>,
     pub denominator: Box<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Addition {
     //TODO: don't have box here?
     #[allow(clippy::vec_box)]
     pub terms: Vec<Box<Expression>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Negation {
     pub term: Box<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Exponentiation {
     pub base: Box<Expression>,
     pub exponent: Box<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Sqrt {
     pub arg: Box<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Function {
     Sin(Box<Expression>),
     Cos(Box<Expression>),
@@ -50,9 +63,147 @@ pub enum Function {
     Arccsc(Box<Expression>),
     Arcsec(Box<Expression>),
     Arccot(Box<Expression>),
+    Sinh(Box<Expression>),
+    Cosh(Box<Expression>),
+    Tanh(Box<Expression>),
+    Csch(Box<Expression>),
+    Sech(Box<Expression>),
+    Coth(Box<Expression>),
+    Arcsinh(Box<Expression>),
+    Arccosh(Box<Expression>),
+    Arctanh(Box<Expression>),
+    Arccsch(Box<Expression>),
+    Arcsech(Box<Expression>),
+    Arccoth(Box<Expression>),
+    Degrees(Box<Expression>),
+}
+
+impl Function {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Function::Sin(_) => "sin",
+            Function::Cos(_) => "cos",
+            Function::Tan(_) => "tan",
+            Function::Csc(_) => "csc",
+            Function::Sec(_) => "sec",
+            Function::Cot(_) => "cot",
+            Function::Arcsin(_) => "arcsin",
+            Function::Arccos(_) => "arccos",
+            Function::Arctan(_) => "arctan",
+            Function::Arccsc(_) => "arccsc",
+            Function::Arcsec(_) => "arcsec",
+            Function::Arccot(_) => "arccot",
+            Function::Sinh(_) => "sinh",
+            Function::Cosh(_) => "cosh",
+            Function::Tanh(_) => "tanh",
+            Function::Csch(_) => "csch",
+            Function::Sech(_) => "sech",
+            Function::Coth(_) => "coth",
+            Function::Arcsinh(_) => "arcsinh",
+            Function::Arccosh(_) => "arccosh",
+            Function::Arctanh(_) => "arctanh",
+            Function::Arccsch(_) => "arccsch",
+            Function::Arcsech(_) => "arcsech",
+            Function::Arccoth(_) => "arccoth",
+            Function::Degrees(_) => "degrees",
+        }
+    }
+
+    pub fn arg(&self) -> &Expression {
+        match self {
+            Function::Sin(arg)
+            | Function::Cos(arg)
+            | Function::Tan(arg)
+            | Function::Csc(arg)
+            | Function::Sec(arg)
+            | Function::Cot(arg)
+            | Function::Arcsin(arg)
+            | Function::Arccos(arg)
+            | Function::Arctan(arg)
+            | Function::Arccsc(arg)
+            | Function::Arcsec(arg)
+            | Function::Arccot(arg)
+            | Function::Sinh(arg)
+            | Function::Cosh(arg)
+            | Function::Tanh(arg)
+            | Function::Csch(arg)
+            | Function::Sech(arg)
+            | Function::Coth(arg)
+            | Function::Arcsinh(arg)
+            | Function::Arccosh(arg)
+            | Function::Arctanh(arg)
+            | Function::Arccsch(arg)
+            | Function::Arcsech(arg)
+            | Function::Arccoth(arg)
+            | Function::Degrees(arg) => arg,
+        }
+    }
+
+    // the argument, taking the function apart, Function::from_name puts it back together
+    pub fn into_arg(self) -> Box<Expression> {
+        match self {
+            Function::Sin(arg)
+            | Function::Cos(arg)
+            | Function::Tan(arg)
+            | Function::Csc(arg)
+            | Function::Sec(arg)
+            | Function::Cot(arg)
+            | Function::Arcsin(arg)
+            | Function::Arccos(arg)
+            | Function::Arctan(arg)
+            | Function::Arccsc(arg)
+            | Function::Arcsec(arg)
+            | Function::Arccot(arg)
+            | Function::Sinh(arg)
+            | Function::Cosh(arg)
+            | Function::Tanh(arg)
+            | Function::Csch(arg)
+            | Function::Sech(arg)
+            | Function::Coth(arg)
+            | Function::Arcsinh(arg)
+            | Function::Arccosh(arg)
+            | Function::Arctanh(arg)
+            | Function::Arccsch(arg)
+            | Function::Arcsech(arg)
+            | Function::Arccoth(arg)
+            | Function::Degrees(arg) => arg,
+        }
+    }
+
+    // the function called `name` applied to `arg`, the reverse of Function::name
+    pub fn from_name(name: &str, arg: Box<Expression>) -> Option<Function> {
+        match name {
+            "sin" => Some(Function::Sin(arg)),
+            "cos" => Some(Function::Cos(arg)),
+            "tan" => Some(Function::Tan(arg)),
+            "csc" => Some(Function::Csc(arg)),
+            "sec" => Some(Function::Sec(arg)),
+            "cot" => Some(Function::Cot(arg)),
+            "arcsin" => Some(Function::Arcsin(arg)),
+            "arccos" => Some(Function::Arccos(arg)),
+            "arctan" => Some(Function::Arctan(arg)),
+            "arccsc" => Some(Function::Arccsc(arg)),
+            "arcsec" => Some(Function::Arcsec(arg)),
+            "arccot" => Some(Function::Arccot(arg)),
+            "sinh" => Some(Function::Sinh(arg)),
+            "cosh" => Some(Function::Cosh(arg)),
+            "tanh" => Some(Function::Tanh(arg)),
+            "csch" => Some(Function::Csch(arg)),
+            "sech" => Some(Function::Sech(arg)),
+            "coth" => Some(Function::Coth(arg)),
+            "arcsinh" => Some(Function::Arcsinh(arg)),
+            "arccosh" => Some(Function::Arccosh(arg)),
+            "arctanh" => Some(Function::Arctanh(arg)),
+            "arccsch" => Some(Function::Arccsch(arg)),
+            "arcsech" => Some(Function::Arcsech(arg)),
+            "arccoth" => Some(Function::Arccoth(arg)),
+            "degrees" => Some(Function::Degrees(arg)),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     Number(f64),
     Variable(String),
@@ -65,10 +216,38 @@ pub enum Expression {
     Function(Function),
 }
 
+impl Expression {
+    // the subtrees directly below this one, in the order of their paths' indices
+    pub(crate) fn children(&self) -> Vec<&Expression> {
+        match self {
+            Expression::Number(_) | Expression::Variable(_) => Vec::new(),
+            Expression::Multiplication(multiplication) => {
+                multiplication.terms.iter().map(|term| &**term).collect()
+            }
+            Expression::Addition(addition) => addition.terms.iter().map(|term| &**term).collect(),
+            Expression::Division(division) => vec![&division.numerator, &division.denominator],
+            Expression::Negation(negation) => vec![&negation.term],
+            Expression::Exponentiation(exponentiation) => {
+                vec![&exponentiation.base, &exponentiation.exponent]
+            }
+            Expression::Sqrt(sqrt) => vec![&sqrt.arg],
+            Expression::Function(function) => vec![function.arg()],
+        }
+    }
+}
+
 pub fn convert_to_expression(ast: &ASTNode) -> Expression {
     match ast {
         ASTNode::Number(n) => Expression::Number(*n),
         ASTNode::PI => Expression::Number(std::f64::consts::PI),
+        ASTNode::E => Expression::Number(std::f64::consts::E),
+        ASTNode::Imaginary(1.0) => Expression::Variable(IMAGINARY_UNIT.to_string()),
+        ASTNode::Imaginary(n) => Expression::Multiplication(Multiplication {
+            terms: vec![
+                Box::new(Expression::Number(*n)),
+                Box::new(Expression::Variable(IMAGINARY_UNIT.to_string())),
+            ],
+        }),
         ASTNode::Variable(v) => Expression::Variable(v.clone()),
         ASTNode::BinaryOp(lhs, rhs, op) => match op {
             BinaryOp::Add => {
@@ -130,8 +309,23 @@ pub fn convert_to_expression(ast: &ASTNode) -> Expression {
                 UnaryOp::Arccsc => Expression::Function(Function::Arccsc(Box::new(arg))),
                 UnaryOp::Arcsec => Expression::Function(Function::Arcsec(Box::new(arg))),
                 UnaryOp::Arccot => Expression::Function(Function::Arccot(Box::new(arg))),
+                UnaryOp::Sinh => Expression::Function(Function::Sinh(Box::new(arg))),
+                UnaryOp::Cosh => Expression::Function(Function::Cosh(Box::new(arg))),
+                UnaryOp::Tanh => Expression::Function(Function::Tanh(Box::new(arg))),
+                UnaryOp::Csch => Expression::Function(Function::Csch(Box::new(arg))),
+                UnaryOp::Sech => Expression::Function(Function::Sech(Box::new(arg))),
+                UnaryOp::Coth => Expression::Function(Function::Coth(Box::new(arg))),
+                UnaryOp::Arcsinh => Expression::Function(Function::Arcsinh(Box::new(arg))),
+                UnaryOp::Arccosh => Expression::Function(Function::Arccosh(Box::new(arg))),
+                UnaryOp::Arctanh => Expression::Function(Function::Arctanh(Box::new(arg))),
+                UnaryOp::Arccsch => Expression::Function(Function::Arccsch(Box::new(arg))),
+                UnaryOp::Arcsech => Expression::Function(Function::Arcsech(Box::new(arg))),
+                UnaryOp::Arccoth => Expression::Function(Function::Arccoth(Box::new(arg))),
+                UnaryOp::Degrees => Expression::Function(Function::Degrees(Box::new(arg))),
                 UnaryOp::Sqrt => Expression::Sqrt(Sqrt { arg: Box::new(arg) }),
-                UnaryOp::Negate => Expression::Negation(Negation { term: Box::new(arg) }),
+                UnaryOp::Negate => Expression::Negation(Negation {
+                    term: Box::new(arg),
+                }),
             }
         }
     }
@@ -196,97 +390,183 @@ pub fn deep_copy(expression: &Expression) -> Expression {
             Function::Arccot(arg) => {
                 Expression::Function(Function::Arccot(Box::new(deep_copy(arg))))
             }
+            Function::Sinh(arg) => Expression::Function(Function::Sinh(Box::new(deep_copy(arg)))),
+            Function::Cosh(arg) => Expression::Function(Function::Cosh(Box::new(deep_copy(arg)))),
+            Function::Tanh(arg) => Expression::Function(Function::Tanh(Box::new(deep_copy(arg)))),
+            Function::Csch(arg) => Expression::Function(Function::Csch(Box::new(deep_copy(arg)))),
+            Function::Sech(arg) => Expression::Function(Function::Sech(Box::new(deep_copy(arg)))),
+            Function::Coth(arg) => Expression::Function(Function::Coth(Box::new(deep_copy(arg)))),
+            Function::Arcsinh(arg) => {
+                Expression::Function(Function::Arcsinh(Box::new(deep_copy(arg))))
+            }
+            Function::Arccosh(arg) => {
+                Expression::Function(Function::Arccosh(Box::new(deep_copy(arg))))
+            }
+            Function::Arctanh(arg) => {
+                Expression::Function(Function::Arctanh(Box::new(deep_copy(arg))))
+            }
+            Function::Arccsch(arg) => {
+                Expression::Function(Function::Arccsch(Box::new(deep_copy(arg))))
+            }
+            Function::Arcsech(arg) => {
+                Expression::Function(Function::Arcsech(Box::new(deep_copy(arg))))
+            }
+            Function::Arccoth(arg) => {
+                Expression::Function(Function::Arccoth(Box::new(deep_copy(arg))))
+            }
+            Function::Degrees(arg) => {
+                Expression::Function(Function::Degrees(Box::new(deep_copy(arg))))
+            }
         },
     }
 }
 
-pub fn eval(expr: &Expression) -> Result<f64, String> {
-    match expr {
-        Expression::Number(n) => Ok(*n),
-        Expression::Variable(_) => Err("Cannot evaluate variable".to_string()),
+// A hash of the shape and values of an expression, structurally equal expressions always hash the same.
+// It is worked out from the children's hashes, so every subtree's can be found in one walk, see node_hash.
+pub fn structural_hash(expression: &Expression) -> u64 {
+    let children: Vec<u64> = expression
+        .children()
+        .into_iter()
+        .map(structural_hash)
+        .collect();
+    node_hash(expression, &children)
+}
+
+// the structural hash of `expression` from the structural hashes of its children
+pub(crate) fn node_hash(expression: &Expression, children: &[u64]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    discriminant(expression).hash(&mut hasher);
+    match expression {
+        Expression::Number(n) => n.to_bits().hash(&mut hasher),
+        Expression::Variable(v) => v.hash(&mut hasher),
+        Expression::Function(function) => discriminant(function).hash(&mut hasher),
+        _ => {}
+    }
+    children.hash(&mut hasher);
+    hasher.finish()
+}
+
+pub fn eval(expr: &Expression) -> Result<f64, EvalError> {
+    eval_in(expr, AngleMode::Radians)
+}
+
+// evaluates with trig functions taking their arguments in `angle_mode`'s unit and inverse trig
+// functions giving their results in it
+pub fn eval_in(expr: &Expression, angle_mode: AngleMode) -> Result<f64, EvalError> {
+    eval_as(expr, angle_mode)
+}
+
+// evaluates with the variables given values by `environment`
+pub fn eval_with(expr: &Expression, environment: &Environment) -> Result<f64, EvalError> {
+    eval_as_with(expr, environment)
+}
+
+// eval_in over any Numeric type, like eval_as::<f32>
+pub fn eval_as<T: Numeric>(expr: &Expression, angle_mode: AngleMode) -> Result<T, EvalError> {
+    evaluate(expr, angle_mode, &|name| {
+        Err(EvalError::UnboundVariable {
+            name: name.to_string(),
+            path: Vec::new(),
+        })
+    })
+}
+
+// eval_with over any Numeric type
+pub fn eval_as_with<T: Numeric>(
+    expr: &Expression,
+    environment: &Environment,
+) -> Result<T, EvalError> {
+    evaluate(expr, environment.angle_mode, &|name| {
+        environment.value_as(name)
+    })
+}
+
+// the evaluator behind all the others, `variable` gives each variable's value
+pub fn evaluate<T: Numeric>(
+    expr: &Expression,
+    angle_mode: AngleMode,
+    variable: &dyn Fn(&str) -> Result<T, EvalError>,
+) -> Result<T, EvalError> {
+    let child = |index: usize, expr: &Expression| {
+        evaluate(expr, angle_mode, variable).map_err(|error| error.within(index))
+    };
+    let value = match expr {
+        Expression::Number(n) => T::from_f64(*n),
+        Expression::Variable(name) if name == IMAGINARY_UNIT => return T::imaginary_unit(),
+        Expression::Variable(name) => return variable(name).map_err(|error| error.at(Vec::new())),
         Expression::Multiplication(multiplication) => multiplication
             .terms
             .iter()
-            .map(|term| eval(term))
-            .try_fold(1.0, |acc, res| res.map(|val| acc * val)),
+            .enumerate()
+            .try_fold(T::from_f64(1.0), |acc, (i, term)| {
+                child(i, term).map(|val| acc * val)
+            })?,
         Expression::Division(division) => {
-            let numerator = eval(&division.numerator)?;
-            let denominator = eval(&division.denominator)?;
-            if denominator == 0.0 {
-                Err("Division by zero".to_string())
-            } else {
-                Ok(numerator / denominator)
-            }
+            child(0, &division.numerator)?.divide(child(1, &division.denominator)?)?
         }
         Expression::Addition(addition) => addition
             .terms
             .iter()
-            .map(|term| eval(term))
-            .try_fold(0.0, |acc, res| res.map(|val| acc + val)),
-        Expression::Negation(negation) => eval(&negation.term).map(|val| -val),
+            .enumerate()
+            .try_fold(T::from_f64(0.0), |acc, (i, term)| {
+                child(i, term).map(|val| acc + val)
+            })?,
+        Expression::Negation(negation) => -child(0, &negation.term)?,
         Expression::Exponentiation(exponentiation) => {
-            let base = eval(&exponentiation.base)?;
-            let exponent = eval(&exponentiation.exponent)?;
-            Ok(base.powf(exponent))
+            child(0, &exponentiation.base)?.powf(child(1, &exponentiation.exponent)?)?
         }
-        Expression::Sqrt(sqrt) => {
-            let arg = eval(&sqrt.arg)?;
-            if arg < 0.0 {
-                Err("Square root of a negative number".to_string())
-            } else {
-                Ok(arg.sqrt())
-            }
+        Expression::Sqrt(sqrt) => child(0, &sqrt.arg)?.sqrt()?,
+        Expression::Function(function) => {
+            T::function(function, child(0, function.arg())?, angle_mode)?
         }
-        Expression::Function(function) => match function {
-            Function::Sin(arg) => eval(arg).map(|val| val.sin()),
-            Function::Cos(arg) => eval(arg).map(|val| val.cos()),
-            Function::Tan(arg) => eval(arg).map(|val| val.tan()),
-            Function::Csc(arg) => eval(arg).and_then(|val| {
-                if val.sin() == 0.0 {
-                    Err("Cosecant undefined for this input".to_string())
-                } else {
-                    Ok(1.0 / val.sin())
-                }
-            }),
-            Function::Sec(arg) => eval(arg).and_then(|val| {
-                if val.cos() == 0.0 {
-                    Err("Secant undefined for this input".to_string())
-                } else {
-                    Ok(1.0 / val.cos())
-                }
-            }),
-            Function::Cot(arg) => eval(arg).and_then(|val| {
-                if val.tan() == 0.0 {
-                    Err("Cotangent undefined for this input".to_string())
-                } else {
-                    Ok(1.0 / val.tan())
-                }
-            }),
-            Function::Arcsin(arg) => eval(arg).map(|val| val.asin()),
-            Function::Arccos(arg) => eval(arg).map(|val| val.acos()),
-            Function::Arctan(arg) => eval(arg).map(|val| val.atan()),
-            Function::Arccsc(arg) => eval(arg).and_then(|val| {
-                if val == 0.0 {
-                    Err("Arccosecant undefined for zero".to_string())
-                } else {
-                    Ok(1.0 / val.asin())
-                }
-            }),
-            Function::Arcsec(arg) => eval(arg).and_then(|val| {
-                if val == 0.0 {
-                    Err("Arcsecant undefined for zero".to_string())
-                } else {
-                    Ok(1.0 / val.acos())
-                }
-            }),
-            Function::Arccot(arg) => eval(arg).and_then(|val| {
-                if val == 0.0 {
-                    Err("Arccotangent undefined for zero".to_string())
-                } else {
-                    Ok(1.0 / val.atan())
-                }
-            }),
-        },
+    };
+    // the children are usable, so anything else started here
+    value.check()
+}
+
+// a function of the value of its argument
+pub fn apply(function: &Function, val: f64, angle_mode: AngleMode) -> Result<f64, EvalError> {
+    let undefined_at_zero = |value: f64| {
+        if val == 0.0 {
+            Err(EvalError::domain(function.name(), val))
+        } else {
+            Ok(value)
+        }
+    };
+    match function {
+        // exact at multiples of 30° and 45°, so sin(30) is 0.5 in degrees and tan(pi / 2) is undefined
+        Function::Sin(_)
+        | Function::Cos(_)
+        | Function::Tan(_)
+        | Function::Csc(_)
+        | Function::Sec(_)
+        | Function::Cot(_) => circular(function.name(), val, angle_mode)
+            .ok_or_else(|| EvalError::domain(function.name(), val)),
+        // exact too, so arcsin(0.5) is 30 in degrees rather than 30.000000000000004
+        Function::Arcsin(_)
+        | Function::Arccos(_)
+        | Function::Arctan(_)
+        | Function::Arccsc(_)
+        | Function::Arcsec(_)
+        | Function::Arccot(_) => inverse_circular(function.name(), val, angle_mode)
+            .ok_or_else(|| EvalError::domain(function.name(), val)),
+        Function::Sinh(_) => Ok(val.sinh()),
+        Function::Cosh(_) => Ok(val.cosh()),
+        Function::Tanh(_) => Ok(val.tanh()),
+        Function::Csch(_) => undefined_at_zero(1.0 / val.sinh()),
+        Function::Sech(_) => Ok(1.0 / val.cosh()),
+        Function::Coth(_) => undefined_at_zero(1.0 / val.tanh()),
+        Function::Arcsinh(_) => Ok(val.asinh()),
+        Function::Arccosh(_) => Ok(val.acosh()),
+        // infinite rather than NaN at the poles
+        Function::Arctanh(_) if val.abs() >= 1.0 => Err(EvalError::domain(function.name(), val)),
+        Function::Arctanh(_) => Ok(val.atanh()),
+        Function::Arccsch(_) => undefined_at_zero((1.0 / val).asinh()),
+        Function::Arcsech(_) => undefined_at_zero((1.0 / val).acosh()),
+        Function::Arccoth(_) if val.abs() <= 1.0 => Err(EvalError::domain(function.name(), val)),
+        Function::Arccoth(_) => Ok((1.0 / val).atanh()),
+        // 30° is 30 degrees whatever unit the other angles are in
+        Function::Degrees(_) => Ok(angle_mode.from_degrees(val)),
     }
 }
 
@@ -378,6 +658,58 @@ pub fn print_expression(expression: &Expression, indent: i32) {
                 println!("Arccot");
                 print_expression(arg, indent + 1);
             }
+            Function::Sinh(arg) => {
+                println!("Sinh");
+                print_expression(arg, indent + 1);
+            }
+            Function::Cosh(arg) => {
+                println!("Cosh");
+                print_expression(arg, indent + 1);
+            }
+            Function::Tanh(arg) => {
+                println!("Tanh");
+                print_expression(arg, indent + 1);
+            }
+            Function::Csch(arg) => {
+                println!("Csch");
+                print_expression(arg, indent + 1);
+            }
+            Function::Sech(arg) => {
+                println!("Sech");
+                print_expression(arg, indent + 1);
+            }
+            Function::Coth(arg) => {
+                println!("Coth");
+                print_expression(arg, indent + 1);
+            }
+            Function::Arcsinh(arg) => {
+                println!("Arcsinh");
+                print_expression(arg, indent + 1);
+            }
+            Function::Arccosh(arg) => {
+                println!("Arccosh");
+                print_expression(arg, indent + 1);
+            }
+            Function::Arctanh(arg) => {
+                println!("Arctanh");
+                print_expression(arg, indent + 1);
+            }
+            Function::Arccsch(arg) => {
+                println!("Arccsch");
+                print_expression(arg, indent + 1);
+            }
+            Function::Arcsech(arg) => {
+                println!("Arcsech");
+                print_expression(arg, indent + 1);
+            }
+            Function::Arccoth(arg) => {
+                println!("Arccoth");
+                print_expression(arg, indent + 1);
+            }
+            Function::Degrees(arg) => {
+                println!("Degrees");
+                print_expression(arg, indent + 1);
+            }
         },
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,670 @@
+use crate::{
+    expression::{Exponentiation, Expression, Multiplication},
+    polynomial::{monomial_to_expression, rational_to_expression, Polynomial},
+    rational::{gcd, integer_root, Rational},
+};
+
+// dense polynomials with integer coefficients, lowest degree first
+type IntPoly = Vec<i128>;
+
+// odd primes tried for the modular factorization, the first one that keeps f square-free is used
+const PRIMES: [i128; 24] = [
+    3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+// the Hensel lifting modulus has to stay below this so products of residues fit in an i128
+const MAX_MODULUS: i128 = 1 << 60;
+
+//exposed to api consumers, factors a polynomial over the rationals
+//x^2 - 5x + 6 -> (x - 3) * (x - 2)
+//the result is a multiplication of the content, the common monomial and every irreducible factor,
+//repeated factors are written as powers. Expressions that aren't polynomials, and polynomials whose
+//coefficients grow past an i128 along the way, are returned unchanged
+pub fn factor(expression: Expression) -> Expression {
+    let polynomial = match Polynomial::from_expression(&expression) {
+        Some(polynomial) if !polynomial.is_zero() => polynomial,
+        _ => return expression,
+    };
+    let Some((content, primitive)) = polynomial.content_and_primitive() else {
+        return expression;
+    };
+    let (monomial, rest) = primitive.split_monomial();
+    let constant = rest.is_constant() && monomial.iter().all(|e| *e == 0);
+    let Some(factors) = factor_primitive(rest) else {
+        return expression;
+    };
+
+    let mut terms = Vec::new();
+    if !content.is_one() || constant {
+        terms.push(Box::new(rational_to_expression(content)));
+    }
+    terms.extend(monomial_to_expression(&polynomial.variables, &monomial));
+    for (factor, multiplicity) in factors {
+        let factor = factor.to_expression();
+        terms.push(Box::new(if multiplicity == 1 {
+            factor
+        } else {
+            Expression::Exponentiation(Exponentiation {
+                base: Box::new(factor),
+                exponent: Box::new(Expression::Number(multiplicity as f64)),
+            })
+        }));
+    }
+
+    // an irreducible polynomial is its own only factor
+    if terms.len() == 1 {
+        return *terms.pop().unwrap();
+    }
+    Expression::Multiplication(Multiplication { terms })
+}
+
+// factors a primitive polynomial with no monomial factor into irreducibles with multiplicities,
+// None if the coefficients overflow
+fn factor_primitive(polynomial: Polynomial) -> Option<Vec<(Polynomial, u32)>> {
+    if polynomial.is_constant() {
+        return Some(Vec::new());
+    }
+
+    if let Some(index) = polynomial.univariate_index() {
+        let dense = to_integer_poly(&polynomial.to_dense(index))?;
+        let mut factors = Vec::new();
+        for (square_free, multiplicity) in square_free_decomposition(&dense) {
+            for irreducible in factor_square_free(square_free)? {
+                let coefficients: Vec<Rational> = irreducible
+                    .into_iter()
+                    .map(Rational::from_integer)
+                    .collect();
+                factors.push((
+                    Polynomial::from_dense(&polynomial.variables, index, &coefficients),
+                    multiplicity,
+                ));
+            }
+        }
+        return Some(factors);
+    }
+
+    // several variables, only sums and differences of squares and cubes are recognized
+    match factor_binomial(&polynomial) {
+        Some(parts) => {
+            let mut factors: Vec<(Polynomial, u32)> = Vec::new();
+            for part in parts {
+                for (factor, multiplicity) in factor_primitive(part)? {
+                    match factors.iter_mut().find(|(existing, _)| *existing == factor) {
+                        Some((_, existing_multiplicity)) => *existing_multiplicity += multiplicity,
+                        None => factors.push((factor, multiplicity)),
+                    }
+                }
+            }
+            Some(factors)
+        }
+        None => Some(vec![(polynomial, 1)]),
+    }
+}
+
+// a^2 - b^2 -> (a - b)(a + b) and a^3 + b^3 -> (a + b)(a^2 - ab + b^2)
+// where a and b are monomials, the factors are returned as primitive polynomials,
+// None if the polynomial isn't one of those or the factors' coefficients overflow
+fn factor_binomial(polynomial: &Polynomial) -> Option<Vec<Polynomial>> {
+    if polynomial.terms.len() != 2 {
+        return None;
+    }
+    let mut terms = polynomial.terms.iter();
+    let (first_exponents, first) = terms.next()?;
+    let (second_exponents, second) = terms.next()?;
+    let variables = &polynomial.variables;
+
+    let root_of = |exponents: &Vec<u32>, coefficient: &Rational, k: u32| -> Option<Polynomial> {
+        if exponents.iter().any(|e| e % k != 0) {
+            return None;
+        }
+        let root = coefficient.root(k)?;
+        let root_exponents = exponents.iter().map(|e| e / k).collect();
+        Some(Polynomial {
+            variables: variables.clone(),
+            terms: [(root_exponents, root)].into_iter().collect(),
+        })
+    };
+
+    if first.signum() != second.signum() {
+        // a^2 - b^2, with the sign of b folded into the square root of -b^2
+        if let (Some(a), Some(b)) = (
+            root_of(first_exponents, &first.abs(), 2),
+            root_of(second_exponents, &second.abs(), 2),
+        ) {
+            let (a, b) = if first.signum() > 0 { (a, b) } else { (b, a) };
+            let negative_b = b.scale(-Rational::one())?;
+            return Some(vec![
+                a.add(&negative_b)?.content_and_primitive()?.1,
+                a.add(&b)?.content_and_primitive()?.1,
+            ]);
+        }
+    }
+
+    // a^3 + b^3, differences are covered by taking the cube root of a negative coefficient
+    let a = root_of(first_exponents, first, 3)?;
+    let b = root_of(second_exponents, second, 3)?;
+    let quadratic = a
+        .mul(&a)?
+        .add(&a.mul(&b)?.scale(-Rational::one())?)?
+        .add(&b.mul(&b)?)?;
+    Some(vec![
+        a.add(&b)?.content_and_primitive()?.1,
+        quadratic.content_and_primitive()?.1,
+    ])
+}
+
+fn to_integer_poly(coefficients: &[Rational]) -> Option<IntPoly> {
+    let denominator = coefficients.iter().try_fold(1, |d: i128, c| {
+        (d / gcd(d, c.denominator())).checked_mul(c.denominator())
+    })?;
+    coefficients
+        .iter()
+        .map(|c| c.numerator().checked_mul(denominator / c.denominator()))
+        .collect()
+}
+
+fn primitive_part(mut f: IntPoly) -> IntPoly {
+    trim(&mut f);
+    let content = f.iter().fold(0, |g, c| gcd(g, *c));
+    if content == 0 {
+        return f;
+    }
+    let sign = f.last().unwrap().signum();
+    f.iter().map(|c| c / content * sign).collect()
+}
+
+fn trim<T: Default + PartialEq>(f: &mut Vec<T>) {
+    while f.len() > 1 && *f.last().unwrap() == T::default() {
+        f.pop();
+    }
+}
+
+fn degree(f: &[i128]) -> usize {
+    f.len().saturating_sub(1)
+}
+
+// Yun's algorithm, splits f into square-free polynomials a_i with f = a_1 * a_2^2 * a_3^3 ...
+// every quotient is exact over the integers by Gauss's lemma since the divisors are primitive
+fn square_free_decomposition(f: &[i128]) -> Vec<(IntPoly, u32)> {
+    yun(f).unwrap_or_else(|| vec![(f.to_vec(), 1)])
+}
+
+fn yun(f: &[i128]) -> Option<Vec<(IntPoly, u32)>> {
+    let derivative = derivative(f)?;
+    let b = integer_gcd(f, &derivative)?;
+    let mut c = exact_divide(f, &b)?;
+    let mut d = subtract(&exact_divide(&derivative, &b)?, &self::derivative(&c)?)?;
+    let mut result = Vec::new();
+    let mut multiplicity = 1;
+    while degree(&c) > 0 {
+        let a = integer_gcd(&c, &d)?;
+        c = exact_divide(&c, &a)?;
+        d = subtract(&exact_divide(&d, &a)?, &self::derivative(&c)?)?;
+        if degree(&a) > 0 {
+            result.push((a, multiplicity));
+        }
+        multiplicity += 1;
+    }
+    Some(result)
+}
+
+fn derivative(f: &[i128]) -> Option<IntPoly> {
+    let mut derivative = f
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, c)| c.checked_mul(i as i128))
+        .collect::<Option<IntPoly>>()?;
+    if derivative.is_empty() {
+        derivative.push(0);
+    }
+    Some(derivative)
+}
+
+fn subtract(a: &[i128], b: &[i128]) -> Option<IntPoly> {
+    let mut difference = (0..a.len().max(b.len()))
+        .map(|i| a.get(i).unwrap_or(&0).checked_sub(*b.get(i).unwrap_or(&0)))
+        .collect::<Option<IntPoly>>()?;
+    trim(&mut difference);
+    Some(difference)
+}
+
+fn is_zero(f: &[i128]) -> bool {
+    f.iter().all(|c| *c == 0)
+}
+
+// primitive greatest common divisor over the integers using a primitive remainder sequence,
+// None if the intermediate coefficients overflow
+fn integer_gcd(a: &[i128], b: &[i128]) -> Option<IntPoly> {
+    let (mut a, mut b) = (primitive_part(a.to_vec()), primitive_part(b.to_vec()));
+    if is_zero(&a) {
+        return Some(b);
+    }
+    while !is_zero(&b) {
+        let remainder = pseudo_remainder(&a, &b)?;
+        a = b;
+        b = remainder;
+    }
+    Some(a)
+}
+
+// the remainder of c * a divided by b for some constant c, kept primitive at every step
+fn pseudo_remainder(a: &[i128], b: &[i128]) -> Option<IntPoly> {
+    let mut remainder = a.to_vec();
+    let b_degree = degree(b);
+    let b_leading = b[b_degree];
+    while !is_zero(&remainder) && degree(&remainder) >= b_degree {
+        let shift = degree(&remainder) - b_degree;
+        let r_leading = *remainder.last().unwrap();
+        let mut next = remainder
+            .iter()
+            .map(|c| c.checked_mul(b_leading))
+            .collect::<Option<IntPoly>>()?;
+        for (i, c) in b.iter().enumerate() {
+            next[i + shift] = next[i + shift].checked_sub(r_leading.checked_mul(*c)?)?;
+        }
+        next.pop();
+        if next.is_empty() {
+            next.push(0);
+        }
+        remainder = primitive_part(next);
+    }
+    Some(remainder)
+}
+
+// factors a square-free primitive polynomial into irreducibles over the integers,
+// None if the coefficients overflow
+fn factor_square_free(f: IntPoly) -> Option<Vec<IntPoly>> {
+    match degree(&f) {
+        0 => Some(Vec::new()),
+        1 => Some(vec![f]),
+        2 => factor_quadratic(f),
+        _ => Some(zassenhaus(f)),
+    }
+}
+
+// ax^2 + bx + c splits over the rationals exactly when b^2 - 4ac is a perfect square
+fn factor_quadratic(f: IntPoly) -> Option<Vec<IntPoly>> {
+    let (c, b, a) = (f[0], f[1], f[2]);
+    let discriminant = b
+        .checked_mul(b)?
+        .checked_sub(a.checked_mul(c)?.checked_mul(4)?)?;
+    if discriminant < 0 {
+        return Some(vec![f]);
+    }
+    match integer_root(discriminant, 2) {
+        // (2ax + b - s)(2ax + b + s) = 4a(ax^2 + bx + c)
+        Some(s) => Some(vec![
+            primitive_part(vec![b.checked_sub(s)?, a.checked_mul(2)?]),
+            primitive_part(vec![b.checked_add(s)?, a.checked_mul(2)?]),
+        ]),
+        None => Some(vec![f]),
+    }
+}
+
+// Zassenhaus: factor modulo a small prime, Hensel lift the factors and recombine them over the integers
+fn zassenhaus(f: IntPoly) -> Vec<IntPoly> {
+    let n = degree(&f);
+    let leading = f[n];
+
+    let prime = PRIMES.iter().copied().find(|p| {
+        if leading % p == 0 {
+            return false;
+        }
+        let reduced = reduce(&f, *p);
+        degree(&gcd_mod(&reduced, &derivative_mod(&reduced, *p), *p)) == 0
+    });
+    let p = match prime {
+        Some(p) => p,
+        None => return vec![f],
+    };
+
+    let monic = make_monic(&reduce(&f, p), p);
+    let modular_factors = cantor_zassenhaus(&monic, p);
+    if modular_factors.len() == 1 {
+        return vec![f];
+    }
+
+    // Mignotte's bound on the coefficients of any factor, times the leading coefficient
+    let norm = f.iter().map(|c| c.abs()).max().unwrap_or(1) as f64;
+    let bound = (n as f64 + 1.0).sqrt() * 2f64.powi(n as i32) * norm * leading.abs() as f64;
+    let mut modulus = p;
+    let mut k = 1;
+    while (modulus as f64) <= 2.0 * bound {
+        if modulus > MAX_MODULUS / p {
+            // the coefficients are too large to lift safely, leave f unfactored
+            return vec![f];
+        }
+        modulus *= p;
+        k += 1;
+    }
+
+    let lifted = hensel_lift(&f, modular_factors, p, k);
+    recombine(f, lifted, modulus)
+}
+
+fn recombine(mut f: IntPoly, mut factors: Vec<IntPoly>, modulus: i128) -> Vec<IntPoly> {
+    let mut result = Vec::new();
+    let mut size = 1;
+    'outer: while 2 * size <= factors.len() {
+        for subset in combinations(factors.len(), size) {
+            let leading = *f.last().unwrap();
+            let candidate = subset.iter().fold(vec![leading], |product, i| {
+                mul_mod(&product, &factors[*i], modulus)
+            });
+            let candidate = primitive_part(symmetric(&candidate, modulus));
+            if let Some(quotient) = exact_divide(&f, &candidate) {
+                result.push(candidate);
+                f = primitive_part(quotient);
+                for i in subset.iter().rev() {
+                    factors.remove(*i);
+                }
+                continue 'outer;
+            }
+        }
+        size += 1;
+    }
+    if degree(&f) > 0 {
+        result.push(f);
+    }
+    result
+}
+
+// every ascending list of `size` indices below `n`
+fn combinations(n: usize, size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![Vec::new()];
+    }
+    if n < size {
+        return Vec::new();
+    }
+    let mut result = combinations(n - 1, size);
+    for mut subset in combinations(n - 1, size - 1) {
+        subset.push(n - 1);
+        result.push(subset);
+    }
+    result.sort();
+    result
+}
+
+// divides over the integers, None if b doesn't divide a exactly
+fn exact_divide(a: &[i128], b: &[i128]) -> Option<IntPoly> {
+    if is_zero(a) {
+        return Some(vec![0]);
+    }
+    let mut remainder = a.to_vec();
+    let b_degree = degree(b);
+    if degree(a) < b_degree {
+        return None;
+    }
+    let mut quotient = vec![0; degree(a) - b_degree + 1];
+    for shift in (0..quotient.len()).rev() {
+        let top = remainder[shift + b_degree];
+        if top % b[b_degree] != 0 {
+            return None;
+        }
+        let coefficient = top / b[b_degree];
+        quotient[shift] = coefficient;
+        for (i, c) in b.iter().enumerate() {
+            remainder[i + shift] =
+                remainder[i + shift].checked_sub(coefficient.checked_mul(*c)?)?;
+        }
+    }
+    if remainder.iter().all(|c| *c == 0) {
+        Some(quotient)
+    } else {
+        None
+    }
+}
+
+// lifts f = lc(f) * g_1 * ... * g_r mod p to the same factorization mod p^k, the g_i stay monic
+fn hensel_lift(f: &[i128], factors: Vec<IntPoly>, p: i128, k: u32) -> Vec<IntPoly> {
+    let modulus = p.pow(k);
+    let mut result = Vec::new();
+    let mut remaining = reduce(f, modulus);
+    let mut factors = factors.into_iter();
+    let mut current = factors.next().unwrap();
+    for next in factors {
+        // the cofactor of the current factor mod p, it keeps the leading coefficient of f
+        let rest = div_rem_mod(&reduce(&remaining, p), &current, p).0;
+        let (g, h) = hensel_step(&remaining, current, rest, p, k);
+        result.push(g);
+        remaining = h;
+        current = next;
+    }
+    let inverse = inverse_mod(*remaining.last().unwrap(), modulus);
+    result.push(scale_mod(&remaining, inverse, modulus));
+    result
+}
+
+// linear Hensel lifting of f = g * h mod p with g monic to mod p^k
+fn hensel_step(f: &[i128], mut g: IntPoly, mut h: IntPoly, p: i128, k: u32) -> (IntPoly, IntPoly) {
+    let (s, t) = extended_gcd_mod(&g, &h, p);
+    let mut power = p;
+    for _ in 1..k {
+        let next_power = power * p;
+        let product = mul_mod(&g, &h, next_power);
+        let difference = sub_mod(&reduce(f, next_power), &product, next_power);
+        let error: IntPoly = difference
+            .iter()
+            .map(|c| (c / power).rem_euclid(p))
+            .collect();
+        // sigma * h + tau * g = error mod p, with deg sigma < deg g
+        let (quotient, sigma) = div_rem_mod(&mul_mod(&t, &error, p), &g, p);
+        let tau = add_mod(&mul_mod(&s, &error, p), &mul_mod(&quotient, &h, p), p);
+        g = add_mod(&g, &scale_mod(&sigma, power, next_power), next_power);
+        h = add_mod(&h, &scale_mod(&tau, power, next_power), next_power);
+        power = next_power;
+    }
+    (g, h)
+}
+
+// factors a monic square-free polynomial modulo the odd prime p into monic irreducibles
+fn cantor_zassenhaus(f: &[i128], p: i128) -> Vec<IntPoly> {
+    let mut factors = Vec::new();
+    let mut seed = 0x2545F4914F6CDD1Du64;
+    for (product, d) in distinct_degree(f, p) {
+        factors.extend(equal_degree(product, d, p, &mut seed));
+    }
+    factors
+}
+
+// groups the factors of f by degree, returns (product of all degree d factors, d)
+fn distinct_degree(f: &[i128], p: i128) -> Vec<(IntPoly, usize)> {
+    let mut result = Vec::new();
+    let mut rest = f.to_vec();
+    let x = vec![0, 1];
+    let mut frobenius = x.clone();
+    let mut d = 1;
+    while degree(&rest) >= 2 * d {
+        frobenius = pow_mod(&frobenius, p as u128, &rest, p);
+        let g = gcd_mod(&rest, &sub_mod(&frobenius, &x, p), p);
+        if degree(&g) > 0 {
+            rest = div_rem_mod(&rest, &g, p).0;
+            frobenius = div_rem_mod(&frobenius, &rest, p).1;
+            result.push((g, d));
+        }
+        d += 1;
+    }
+    if degree(&rest) > 0 {
+        let d = degree(&rest);
+        result.push((rest, d));
+    }
+    result
+}
+
+fn equal_degree(f: IntPoly, d: usize, p: i128, seed: &mut u64) -> Vec<IntPoly> {
+    if degree(&f) == d {
+        return vec![f];
+    }
+    loop {
+        let a: IntPoly = (0..degree(&f))
+            .map(|_| {
+                *seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                ((*seed >> 33) as i128) % p
+            })
+            .collect();
+        let mut a = a;
+        trim(&mut a);
+        if degree(&a) == 0 {
+            continue;
+        }
+        // a^((p^d - 1) / 2) = (a * a^p * ... * a^(p^(d-1)))^((p - 1) / 2)
+        let mut term = a.clone();
+        let mut product = a;
+        for _ in 1..d {
+            term = pow_mod(&term, p as u128, &f, p);
+            product = div_rem_mod(&mul_mod(&product, &term, p), &f, p).1;
+        }
+        let b = pow_mod(&product, ((p - 1) / 2) as u128, &f, p);
+        let g = gcd_mod(&f, &sub_mod(&b, &[1], p), p);
+        if degree(&g) > 0 && degree(&g) < degree(&f) {
+            let other = div_rem_mod(&f, &g, p).0;
+            let mut factors = equal_degree(g, d, p, seed);
+            factors.extend(equal_degree(other, d, p, seed));
+            return factors;
+        }
+    }
+}
+
+fn reduce(f: &[i128], modulus: i128) -> IntPoly {
+    let mut result: IntPoly = f.iter().map(|c| c.rem_euclid(modulus)).collect();
+    trim(&mut result);
+    result
+}
+
+// representatives in (-m/2, m/2] so negative coefficients come back out
+fn symmetric(f: &[i128], modulus: i128) -> IntPoly {
+    f.iter()
+        .map(|c| if *c > modulus / 2 { c - modulus } else { *c })
+        .collect()
+}
+
+fn add_mod(a: &[i128], b: &[i128], modulus: i128) -> IntPoly {
+    let sum: IntPoly = (0..a.len().max(b.len()))
+        .map(|i| a.get(i).unwrap_or(&0) + b.get(i).unwrap_or(&0))
+        .collect();
+    reduce(&sum, modulus)
+}
+
+fn sub_mod(a: &[i128], b: &[i128], modulus: i128) -> IntPoly {
+    let difference: IntPoly = (0..a.len().max(b.len()))
+        .map(|i| a.get(i).unwrap_or(&0) - b.get(i).unwrap_or(&0))
+        .collect();
+    reduce(&difference, modulus)
+}
+
+fn scale_mod(f: &[i128], factor: i128, modulus: i128) -> IntPoly {
+    let scaled: IntPoly = f.iter().map(|c| (c * factor).rem_euclid(modulus)).collect();
+    reduce(&scaled, modulus)
+}
+
+fn mul_mod(a: &[i128], b: &[i128], modulus: i128) -> IntPoly {
+    let mut product = vec![0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] = (product[i + j] + x * y).rem_euclid(modulus);
+        }
+    }
+    reduce(&product, modulus)
+}
+
+// division by a polynomial whose leading coefficient is invertible mod `modulus`
+fn div_rem_mod(a: &[i128], b: &[i128], modulus: i128) -> (IntPoly, IntPoly) {
+    let mut remainder = reduce(a, modulus);
+    let b = reduce(b, modulus);
+    let b_degree = degree(&b);
+    let inverse = inverse_mod(b[b_degree], modulus);
+    if degree(&remainder) < b_degree || (remainder.len() == 1 && remainder[0] == 0) {
+        return (vec![0], remainder);
+    }
+    let mut quotient = vec![0; degree(&remainder) - b_degree + 1];
+    while remainder.len() > b_degree && !(remainder.len() == 1 && remainder[0] == 0) {
+        let shift = remainder.len() - 1 - b_degree;
+        let coefficient = (remainder.last().unwrap() * inverse).rem_euclid(modulus);
+        quotient[shift] = coefficient;
+        for (i, c) in b.iter().enumerate() {
+            remainder[i + shift] = (remainder[i + shift] - coefficient * c).rem_euclid(modulus);
+        }
+        remainder.pop();
+        trim(&mut remainder);
+    }
+    if remainder.is_empty() {
+        remainder.push(0);
+    }
+    (reduce(&quotient, modulus), remainder)
+}
+
+fn make_monic(f: &[i128], p: i128) -> IntPoly {
+    scale_mod(f, inverse_mod(*f.last().unwrap(), p), p)
+}
+
+fn gcd_mod(a: &[i128], b: &[i128], p: i128) -> IntPoly {
+    let (mut a, mut b) = (reduce(a, p), reduce(b, p));
+    while !(b.len() == 1 && b[0] == 0) {
+        let remainder = div_rem_mod(&a, &b, p).1;
+        a = b;
+        b = remainder;
+    }
+    if a.len() == 1 && a[0] == 0 {
+        return a;
+    }
+    make_monic(&a, p)
+}
+
+// s and t with s * a + t * b = 1 mod p, for coprime a and b
+fn extended_gcd_mod(a: &[i128], b: &[i128], p: i128) -> (IntPoly, IntPoly) {
+    let (mut r0, mut r1) = (reduce(a, p), reduce(b, p));
+    let (mut s0, mut s1) = (vec![1], vec![0]);
+    let (mut t0, mut t1) = (vec![0], vec![1]);
+    while !(r1.len() == 1 && r1[0] == 0) {
+        let (quotient, remainder) = div_rem_mod(&r0, &r1, p);
+        (r0, r1) = (r1, remainder);
+        let s = sub_mod(&s0, &mul_mod(&quotient, &s1, p), p);
+        (s0, s1) = (s1, s);
+        let t = sub_mod(&t0, &mul_mod(&quotient, &t1, p), p);
+        (t0, t1) = (t1, t);
+    }
+    // r0 is a nonzero constant, scale so the combination is exactly 1
+    let inverse = inverse_mod(r0[0], p);
+    (scale_mod(&s0, inverse, p), scale_mod(&t0, inverse, p))
+}
+
+fn derivative_mod(f: &[i128], p: i128) -> IntPoly {
+    if f.len() <= 1 {
+        return vec![0];
+    }
+    let derivative: IntPoly = f
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, c)| c * i as i128)
+        .collect();
+    reduce(&derivative, p)
+}
+
+fn pow_mod(base: &[i128], mut exponent: u128, modulus_poly: &[i128], p: i128) -> IntPoly {
+    let mut result = vec![1];
+    let mut base = div_rem_mod(base, modulus_poly, p).1;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = div_rem_mod(&mul_mod(&result, &base, p), modulus_poly, p).1;
+        }
+        base = div_rem_mod(&mul_mod(&base, &base, p), modulus_poly, p).1;
+        exponent >>= 1;
+    }
+    result
+}
+
+fn inverse_mod(a: i128, modulus: i128) -> i128 {
+    let (mut r0, mut r1) = (a.rem_euclid(modulus), modulus);
+    let (mut s0, mut s1) = (1i128, 0i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+    }
+    s0.rem_euclid(modulus)
+}
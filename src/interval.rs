@@ -0,0 +1,604 @@
+use std::{
+    f64::consts::PI,
+    fmt,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use crate::{
+    angle::AngleMode,
+    eval_error::EvalError,
+    expression::{evaluate, Expression, Function},
+    numeric::Numeric,
+};
+
+// how many ulps the standard library's sin, exp, powf and the like can be off by, their results are
+// widened this much
+const FUNCTION_ULPS: u32 = 4;
+
+// past this many multiples of pi, sin, cos, tan and cot are taken to cover their whole range
+const LARGEST_MULTIPLE: f64 = 1e15;
+
+// every real number from lo to hi, which can be -inf and inf for the extended intervals dividing by an
+// interval containing 0 gives
+// evaluating over intervals gives bounds that hold for every value of the variables in theirs, with the
+// numbers in the expression taken as the f64s they are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+// bounds on `expr` for every value of each variable in its interval, x^2 - 2 with x in [1, 2] is in [-1, 2]
+pub fn eval_interval(
+    expr: &Expression,
+    variables: &[(String, Interval)],
+    angle_mode: AngleMode,
+) -> Result<Interval, EvalError> {
+    evaluate(expr, angle_mode, &|name| {
+        variables
+            .iter()
+            .find(|(variable, _)| variable == name)
+            .map(|(_, interval)| *interval)
+            .ok_or_else(|| EvalError::UnboundVariable {
+                name: name.to_string(),
+                path: Vec::new(),
+            })
+    })
+}
+
+impl Interval {
+    // the interval between two bounds, in either order
+    pub fn new(a: f64, b: f64) -> Interval {
+        Interval {
+            lo: a.min(b),
+            hi: a.max(b),
+        }
+    }
+
+    pub fn point(x: f64) -> Interval {
+        Interval { lo: x, hi: x }
+    }
+
+    pub fn entire() -> Interval {
+        Interval {
+            lo: f64::NEG_INFINITY,
+            hi: f64::INFINITY,
+        }
+    }
+
+    pub fn contains(&self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    pub fn width(&self) -> f64 {
+        add(self.hi, -self.lo, Rounding::Up)
+    }
+
+    // the smallest interval containing both
+    pub fn hull(self, other: Interval) -> Interval {
+        Interval {
+            lo: self.lo.min(other.lo),
+            hi: self.hi.max(other.hi),
+        }
+    }
+
+    // every quotient of a value in self by a nonzero value in `divisor`, in order, so [1, 2] / [-1, 1] is
+    // [-inf, -1] and [1, inf], and only dividing by [0, 0] itself fails
+    pub fn extended_division(self, divisor: Interval) -> Result<Vec<Interval>, EvalError> {
+        let (a, b, c, d) = (self.lo, self.hi, divisor.lo, divisor.hi);
+        if divisor.is_zero() {
+            return Err(EvalError::division_by_zero());
+        }
+        if !divisor.contains(0.0) {
+            let quotients = [(a, c), (a, d), (b, c), (b, d)];
+            return Ok(vec![Interval {
+                lo: quotients
+                    .iter()
+                    .map(|&(x, y)| quotient(x, y, Rounding::Down))
+                    .fold(f64::INFINITY, f64::min),
+                hi: quotients
+                    .iter()
+                    .map(|&(x, y)| quotient(x, y, Rounding::Up))
+                    .fold(f64::NEG_INFINITY, f64::max),
+            }]);
+        }
+        if self.is_zero() {
+            return Ok(vec![self]);
+        }
+        if self.contains(0.0) {
+            return Ok(vec![Interval::entire()]);
+        }
+        // the quotients run off to infinity on each side of the divisor's 0
+        let (below, above) = if a > 0.0 {
+            (
+                (c < 0.0).then(|| quotient(a, c, Rounding::Up)),
+                (d > 0.0).then(|| quotient(a, d, Rounding::Down)),
+            )
+        } else {
+            (
+                (d > 0.0).then(|| quotient(b, d, Rounding::Up)),
+                (c < 0.0).then(|| quotient(b, c, Rounding::Down)),
+            )
+        };
+        let below = below.map(|hi| Interval {
+            lo: f64::NEG_INFINITY,
+            hi,
+        });
+        let above = above.map(|lo| Interval {
+            lo,
+            hi: f64::INFINITY,
+        });
+        Ok(below.into_iter().chain(above).collect())
+    }
+
+    // self^n for a whole number n, by squaring so powers like 2^10 stay exact
+    fn powi(self, n: f64) -> Result<Interval, EvalError> {
+        if n == 0.0 {
+            return Ok(Interval::point(1.0));
+        }
+        if n < 0.0 {
+            if self.is_zero() {
+                return Err(EvalError::division_by_zero());
+            }
+            return Ok(Interval::point(1.0) / self.powi(-n)?);
+        }
+        // every whole f64 from 2^53 on is even
+        let odd = n < 9007199254740992.0 && n as u64 % 2 == 1;
+        let power = |x: f64, rounding: Rounding| nonnegative_power(x, n, rounding);
+        Ok(if odd {
+            Interval {
+                lo: if self.lo < 0.0 {
+                    -power(-self.lo, Rounding::Up)
+                } else {
+                    power(self.lo, Rounding::Down)
+                },
+                hi: if self.hi < 0.0 {
+                    -power(-self.hi, Rounding::Down)
+                } else {
+                    power(self.hi, Rounding::Up)
+                },
+            }
+        } else {
+            let smallest = if self.contains(0.0) {
+                0.0
+            } else {
+                self.lo.abs().min(self.hi.abs())
+            };
+            Interval {
+                lo: power(smallest, Rounding::Down),
+                hi: power(self.lo.abs().max(self.hi.abs()), Rounding::Up),
+            }
+        })
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}]", self.lo, self.hi)
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, other: Interval) -> Interval {
+        Interval {
+            lo: add(self.lo, other.lo, Rounding::Down),
+            hi: add(self.hi, other.hi, Rounding::Up),
+        }
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, other: Interval) -> Interval {
+        self + -other
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+
+    fn mul(self, other: Interval) -> Interval {
+        let products = [
+            (self.lo, other.lo),
+            (self.lo, other.hi),
+            (self.hi, other.lo),
+            (self.hi, other.hi),
+        ];
+        Interval {
+            lo: products
+                .iter()
+                .map(|&(x, y)| product(x, y, Rounding::Down))
+                .fold(f64::INFINITY, f64::min),
+            hi: products
+                .iter()
+                .map(|&(x, y)| product(x, y, Rounding::Up))
+                .fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+impl Div for Interval {
+    type Output = Interval;
+
+    // the hull of extended_division, NaN for [0, 0] like 0.0 / 0.0
+    fn div(self, divisor: Interval) -> Interval {
+        self.divide(divisor)
+            .unwrap_or_else(|_| Interval::point(f64::NAN))
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+
+    fn neg(self) -> Interval {
+        Interval {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+}
+
+impl Numeric for Interval {
+    fn from_f64(value: f64) -> Self {
+        Interval::point(value)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.lo == 0.0 && self.hi == 0.0
+    }
+
+    fn divide(self, divisor: Self) -> Result<Self, EvalError> {
+        let pieces = self.extended_division(divisor)?;
+        Ok(pieces
+            .into_iter()
+            .reduce(Interval::hull)
+            .unwrap_or_else(Interval::entire))
+    }
+
+    fn powf(self, exponent: Self) -> Result<Self, EvalError> {
+        if exponent.lo == exponent.hi && exponent.lo.fract() == 0.0 {
+            return self.powi(exponent.lo);
+        }
+        if self.lo < 0.0 {
+            // like (-8)^(1/3), which is NaN for f64 too
+            return Err(EvalError::NaN { path: Vec::new() });
+        }
+        if self.is_zero() && exponent.hi < 0.0 {
+            return Err(EvalError::division_by_zero());
+        }
+        // for x >= 0, x^y only rises or only falls along each of x and y, so it's widest at the corners
+        let corners = [
+            (self.lo, exponent.lo),
+            (self.lo, exponent.hi),
+            (self.hi, exponent.lo),
+            (self.hi, exponent.hi),
+        ];
+        let power = |(x, y): (f64, f64), rounding: Rounding| {
+            let value = x.powf(y);
+            // 0^y, 1^y and x^0 are exact
+            if x == 0.0 || x == 1.0 || y == 0.0 {
+                value
+            } else {
+                rounding.widen(value)
+            }
+        };
+        Ok(Interval {
+            lo: corners
+                .iter()
+                .map(|&corner| power(corner, Rounding::Down))
+                .fold(f64::INFINITY, f64::min),
+            hi: corners
+                .iter()
+                .map(|&corner| power(corner, Rounding::Up))
+                .fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+
+    fn sqrt(self) -> Result<Self, EvalError> {
+        if self.lo < 0.0 {
+            return Err(EvalError::domain("sqrt", self.lo));
+        }
+        Ok(Interval {
+            lo: root(self.lo, Rounding::Down),
+            hi: root(self.hi, Rounding::Up),
+        })
+    }
+
+    // an interval reaching outside the function's domain is a DomainError at a value there, one containing
+    // a pole gives an extended interval like dividing by an interval containing 0 does
+    fn function(function: &Function, x: Self, angle_mode: AngleMode) -> Result<Self, EvalError> {
+        let name = function.name();
+        let outside = |value: f64| Err(EvalError::domain(name, value));
+        let reciprocal = |y: Interval| {
+            if y.is_zero() {
+                return outside(x.lo);
+            }
+            Ok(Interval::point(1.0) / y)
+        };
+        let radians = || to_radians(x, angle_mode);
+        let angle = |radians: Interval| from_radians(radians, angle_mode);
+        Ok(match function {
+            Function::Sin(_) => periodic(radians(), f64::sin, 0.5, 1.5),
+            Function::Cos(_) => periodic(radians(), f64::cos, 0.0, 1.0),
+            Function::Tan(_) => branch(radians(), f64::tan, 0.5, true),
+            Function::Csc(_) => reciprocal(periodic(radians(), f64::sin, 0.5, 1.5))?,
+            Function::Sec(_) => reciprocal(periodic(radians(), f64::cos, 0.0, 1.0))?,
+            Function::Cot(_) => branch(radians(), |x| 1.0 / x.tan(), 0.0, false),
+            Function::Arcsin(_) | Function::Arccos(_) if x.lo < -1.0 => return outside(x.lo),
+            Function::Arcsin(_) | Function::Arccos(_) if x.hi > 1.0 => return outside(x.hi),
+            Function::Arcsin(_) => angle(monotone(x, f64::asin, true)),
+            Function::Arccos(_) => angle(monotone(x, f64::acos, false)),
+            Function::Arctan(_) => angle(monotone(x, f64::atan, true)),
+            Function::Arccsc(_) | Function::Arcsec(_) if x.lo < 1.0 && x.hi > -1.0 => {
+                return outside(0.0f64.clamp(x.lo, x.hi))
+            }
+            Function::Arccsc(_) => angle(monotone(Interval::point(1.0) / x, f64::asin, true)),
+            Function::Arcsec(_) => angle(monotone(Interval::point(1.0) / x, f64::acos, false)),
+            // arccot(0) is pi / 2, between the -pi / 2 and pi / 2 it approaches from either side of 0
+            Function::Arccot(_) if x.contains(0.0) => angle(
+                monotone(Interval::point(1.0) / x, f64::atan, true)
+                    .hull(pi() * Interval::point(0.5)),
+            ),
+            Function::Arccot(_) => angle(monotone(Interval::point(1.0) / x, f64::atan, true)),
+            Function::Sinh(_) => monotone(x, f64::sinh, true),
+            Function::Cosh(_) => cosh(x),
+            Function::Tanh(_) => clamped(monotone(x, f64::tanh, true)),
+            Function::Csch(_) => reciprocal(monotone(x, f64::sinh, true))?,
+            Function::Sech(_) => reciprocal(cosh(x))?,
+            Function::Coth(_) => reciprocal(clamped(monotone(x, f64::tanh, true)))?,
+            Function::Arcsinh(_) => monotone(x, f64::asinh, true),
+            Function::Arccosh(_) if x.lo < 1.0 => return outside(x.lo),
+            Function::Arccosh(_) => monotone(x, f64::acosh, true),
+            Function::Arctanh(_) if x.lo <= -1.0 => return outside(x.lo),
+            Function::Arctanh(_) if x.hi >= 1.0 => return outside(x.hi),
+            Function::Arctanh(_) => monotone(x, f64::atanh, true),
+            Function::Arccsch(_) if x.is_zero() => return outside(0.0),
+            Function::Arccsch(_) => monotone(Interval::point(1.0) / x, f64::asinh, true),
+            Function::Arcsech(_) if x.lo <= 0.0 => return outside(x.lo),
+            Function::Arcsech(_) if x.hi > 1.0 => return outside(x.hi),
+            Function::Arcsech(_) => monotone(Interval::point(1.0) / x, f64::acosh, true),
+            Function::Arccoth(_) if x.lo <= 1.0 && x.hi >= -1.0 => {
+                return outside(0.0f64.clamp(x.lo, x.hi))
+            }
+            Function::Arccoth(_) => monotone(Interval::point(1.0) / x, f64::atanh, true),
+            // 30° is 30 degrees whatever unit the other angles are in
+            Function::Degrees(_) => match angle_mode {
+                AngleMode::Degrees => x,
+                _ => x * (turn(angle_mode) / Interval::point(360.0)),
+            },
+        })
+    }
+
+    fn check(self) -> Result<Self, EvalError> {
+        if self.lo.is_nan() || self.hi.is_nan() {
+            Err(EvalError::NaN { path: Vec::new() })
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+// π itself, which f64's PI is just below
+fn pi() -> Interval {
+    Interval {
+        lo: PI,
+        hi: next_up(PI),
+    }
+}
+
+fn turn(angle_mode: AngleMode) -> Interval {
+    match angle_mode {
+        AngleMode::Radians => pi() * Interval::point(2.0),
+        _ => Interval::point(angle_mode.turn()),
+    }
+}
+
+fn to_radians(x: Interval, angle_mode: AngleMode) -> Interval {
+    match angle_mode {
+        AngleMode::Radians => x,
+        _ => x * (turn(AngleMode::Radians) / turn(angle_mode)),
+    }
+}
+
+fn from_radians(x: Interval, angle_mode: AngleMode) -> Interval {
+    match angle_mode {
+        AngleMode::Radians => x,
+        _ => x * (turn(angle_mode) / turn(AngleMode::Radians)),
+    }
+}
+
+// f over x where it only rises or only falls
+fn monotone(x: Interval, f: fn(f64) -> f64, increasing: bool) -> Interval {
+    let (lo, hi) = if increasing {
+        (x.lo, x.hi)
+    } else {
+        (x.hi, x.lo)
+    };
+    Interval {
+        lo: bound(f, lo, Rounding::Down),
+        hi: bound(f, hi, Rounding::Up),
+    }
+}
+
+// sin or cos of x in radians, which are largest at max_at * pi and smallest at min_at * pi every 2 pi
+fn periodic(x: Interval, f: fn(f64) -> f64, max_at: f64, min_at: f64) -> Interval {
+    let multiples = x / pi();
+    if !(multiples.lo.abs() < LARGEST_MULTIPLE && multiples.hi.abs() < LARGEST_MULTIPLE)
+        || multiples.width() >= 2.0
+    {
+        return Interval { lo: -1.0, hi: 1.0 };
+    }
+    let ends =
+        monotone(Interval::point(x.lo), f, true).hull(monotone(Interval::point(x.hi), f, true));
+    clamped(Interval {
+        lo: if reaches(multiples, min_at, 2.0) {
+            -1.0
+        } else {
+            ends.lo
+        },
+        hi: if reaches(multiples, max_at, 2.0) {
+            1.0
+        } else {
+            ends.hi
+        },
+    })
+}
+
+// tan or cot of x in radians, which only rise or only fall between poles at pole_at * pi every pi
+fn branch(x: Interval, f: fn(f64) -> f64, pole_at: f64, increasing: bool) -> Interval {
+    let multiples = x / pi();
+    if !(multiples.lo.abs() < LARGEST_MULTIPLE && multiples.hi.abs() < LARGEST_MULTIPLE)
+        || multiples.width() >= 1.0
+        || reaches(multiples, pole_at, 1.0)
+    {
+        return Interval::entire();
+    }
+    monotone(x, f, increasing)
+}
+
+// whether `multiples` contains at + k * period for some whole k, all exact below LARGEST_MULTIPLE
+fn reaches(multiples: Interval, at: f64, period: f64) -> bool {
+    let k = ((multiples.lo - at) / period).ceil();
+    at + k * period <= multiples.hi
+}
+
+fn cosh(x: Interval) -> Interval {
+    if x.contains(0.0) {
+        Interval {
+            lo: 1.0,
+            hi: bound(f64::cosh, x.lo, Rounding::Up).max(bound(f64::cosh, x.hi, Rounding::Up)),
+        }
+    } else {
+        monotone(x, f64::cosh, x.lo > 0.0)
+    }
+}
+
+// within [-1, 1], where widening can take sin and tanh past their ranges
+fn clamped(x: Interval) -> Interval {
+    Interval {
+        lo: x.lo.max(-1.0),
+        hi: x.hi.min(1.0),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Rounding {
+    Down,
+    Up,
+}
+
+impl Rounding {
+    fn step(self, value: f64) -> f64 {
+        match self {
+            Rounding::Down => -next_up(-value),
+            Rounding::Up => next_up(value),
+        }
+    }
+
+    // `value` rounded to nearest, stepped in this direction if `error`, the exact result less it, says it
+    // went the wrong way, NaN meaning it overflowed
+    fn correct(self, value: f64, error: f64) -> f64 {
+        match self {
+            Rounding::Down if error < 0.0 || error.is_nan() => self.step(value),
+            Rounding::Up if error > 0.0 || error.is_nan() => self.step(value),
+            _ => value,
+        }
+    }
+
+    // a library function's result, stepped past anything it could have rounded away from
+    fn widen(self, value: f64) -> f64 {
+        (0..FUNCTION_ULPS).fold(value, |value, _| self.step(value))
+    }
+}
+
+// the next f64 towards inf, like f64::next_up
+fn next_up(x: f64) -> f64 {
+    if x.is_nan() || x == f64::INFINITY {
+        return x;
+    }
+    if x == 0.0 {
+        return f64::from_bits(1);
+    }
+    f64::from_bits(if x > 0.0 {
+        x.to_bits() + 1
+    } else {
+        x.to_bits() - 1
+    })
+}
+
+// f(x) widened, where f(0) being 0 or 1, like sin(0) and cosh(0), is exact
+fn bound(f: fn(f64) -> f64, x: f64, rounding: Rounding) -> f64 {
+    let value = f(x);
+    if x == 0.0 && (value == 0.0 || value == 1.0) {
+        value
+    } else {
+        rounding.widen(value)
+    }
+}
+
+// a + b rounded in one direction, from the exact error of the rounded sum
+fn add(a: f64, b: f64, rounding: Rounding) -> f64 {
+    let sum = a + b;
+    if !a.is_finite() || !b.is_finite() {
+        return sum;
+    }
+    let b_part = sum - a;
+    rounding.correct(sum, (a - (sum - b_part)) + (b - b_part))
+}
+
+// a * b rounded in one direction, with 0 * inf being 0 since the infinite bound is never reached
+fn product(a: f64, b: f64, rounding: Rounding) -> f64 {
+    if a == 0.0 || b == 0.0 {
+        return 0.0;
+    }
+    let product = a * b;
+    if !a.is_finite() || !b.is_finite() {
+        return product;
+    }
+    if product == 0.0 {
+        // underflowed, so the exact product is away from 0 on its side
+        return rounding.correct(product, a.signum() * b.signum());
+    }
+    rounding.correct(product, a.mul_add(b, -product))
+}
+
+// a / b for nonzero b rounded in one direction, with inf / inf anywhere from 0 to inf
+fn quotient(a: f64, b: f64, rounding: Rounding) -> f64 {
+    if a.is_infinite() && b.is_infinite() {
+        let sign = a.signum() * b.signum();
+        return match rounding {
+            Rounding::Down => (sign * f64::INFINITY).min(0.0),
+            Rounding::Up => (sign * f64::INFINITY).max(0.0),
+        };
+    }
+    let quotient = a / b;
+    if !a.is_finite() || !b.is_finite() {
+        return quotient;
+    }
+    // the remainder has the sign of the error times b's
+    rounding.correct(quotient, (-quotient).mul_add(b, a) * b.signum())
+}
+
+fn root(x: f64, rounding: Rounding) -> f64 {
+    let root = x.sqrt();
+    if !x.is_finite() {
+        return root;
+    }
+    rounding.correct(root, (-root).mul_add(root, x))
+}
+
+// x^n for x >= 0 and whole n > 0, every product rounded the same way
+fn nonnegative_power(x: f64, n: f64, rounding: Rounding) -> f64 {
+    let (mut result, mut square, mut n) = (1.0, x, n as u64);
+    while n > 0 {
+        if n % 2 == 1 {
+            result = product(result, square, rounding);
+        }
+        square = product(square, square, rounding);
+        n /= 2;
+    }
+    result
+}
@@ -1,11 +1,6 @@
-use crate::expression::Expression;
-use crate::expression::Function;
-use crate::expression::Multiplication;
-use crate::expression::Division;
-use crate::expression::Addition;
-use crate::expression::Negation;
-use crate::expression::Exponentiation;
-use crate::expression::Sqrt;
+use crate::angle::AngleMode;
+use crate::complex::{Complex, ComplexForm, IMAGINARY_UNIT};
+use crate::expression::{Addition, Expression, Function, Multiplication, Negation};
 
 #[derive(PartialEq, PartialOrd)]
 enum Precedence {
@@ -17,17 +12,34 @@ enum Precedence {
 }
 
 pub fn to_latex(expr: &Expression) -> String {
-    let (latex, _) = expr_to_latex(expr);
+    let (latex, _) = expr_to_latex(expr, None);
     latex
 }
 
-fn expr_to_latex(expr: &Expression) -> (String, Precedence) {
+// the latex with the node at `path` in red, like where an EvalError happened
+pub fn to_latex_highlighted(expr: &Expression, path: &[usize]) -> String {
+    let (latex, _) = expr_to_latex(expr, Some(path));
+    latex
+}
+
+// `highlight` is the path from expr to the node to highlight, None when it isn't under expr
+fn expr_to_latex(expr: &Expression, highlight: Option<&[usize]>) -> (String, Precedence) {
+    if highlight == Some(&[]) {
+        let (latex, prec) = expr_to_latex(expr, None);
+        return (format!("{{\\color{{red}}{}}}", latex), prec);
+    }
     match expr {
         Expression::Number(n) => {
             let s = if *n == std::f64::consts::PI {
                 String::from("\\pi")
             } else if *n == std::f64::consts::E {
                 String::from("e")
+            } else if let Some(multiple) = pi_multiple(*n) {
+                // 2 * pi is folded into 6.28... by the simplifier
+                match multiple {
+                    -1 => String::from("-\\pi"),
+                    multiple => format!("{}\\pi", multiple),
+                }
             } else {
                 if n.fract() == 0.0 {
                     format!("{}", *n as i64)
@@ -39,38 +51,63 @@ fn expr_to_latex(expr: &Expression) -> (String, Precedence) {
         }
         Expression::Variable(v) => (v.clone(), Precedence::Atom),
         Expression::Multiplication(m) => {
-            let terms: Vec<String> = m.terms.iter().map(|term| {
-                let (term_latex, term_prec) = expr_to_latex(term);
-                if term_prec < Precedence::Mul {
-                    format!("({})", term_latex)
-                } else {
-                    term_latex
+            // -1 * x is written as -x
+            let (sign, skipped, terms) = match m.terms.split_first() {
+                Some((first, rest))
+                    if matches!(**first, Expression::Number(n) if n == -1.0)
+                        && !rest.is_empty() =>
+                {
+                    ("-", 1, rest)
                 }
-            }).collect();
-            let latex = terms.join(" \\cdot ");
+                _ => ("", 0, &m.terms[..]),
+            };
+            let terms: Vec<(String, &Expression)> = terms
+                .iter()
+                .enumerate()
+                .map(|(i, term)| {
+                    let (term_latex, term_prec) =
+                        expr_to_latex(term, child(highlight, i + skipped));
+                    if term_prec < Precedence::Mul {
+                        (format!("({})", term_latex), &**term)
+                    } else {
+                        (term_latex, &**term)
+                    }
+                })
+                .collect();
+            let latex = format!("{}{}", sign, product(terms));
             (latex, Precedence::Mul)
         }
         Expression::Division(d) => {
-            let numerator = expr_to_latex(&d.numerator).0;
-            let denominator = expr_to_latex(&d.denominator).0;
+            let numerator = expr_to_latex(&d.numerator, child(highlight, 0)).0;
+            let denominator = expr_to_latex(&d.denominator, child(highlight, 1)).0;
             let latex = format!("\\frac{{{}}}{{{}}}", numerator, denominator);
             (latex, Precedence::Atom)
         }
         Expression::Addition(a) => {
-            let terms: Vec<String> = a.terms.iter().map(|term| {
-                let (term_latex, term_prec) = expr_to_latex(term);
-                if term_prec < Precedence::AddSub {
-                    format!("({})", term_latex)
-                } else {
-                    term_latex
+            // negated terms after the first are written as subtraction, a + -b -> a - b
+            let mut latex = String::new();
+            for (i, term) in a.terms.iter().enumerate() {
+                let highlight = child(highlight, i);
+                // a highlighted term keeps its sign so the whole term is highlighted
+                let (negative, term) = match (
+                    i,
+                    negated(term, highlight).filter(|_| highlight != Some(&[])),
+                ) {
+                    (0, _) | (_, None) => {
+                        (false, term_with_parens(term, Precedence::AddSub, highlight))
+                    }
+                    (_, Some(magnitude)) => (true, magnitude),
+                };
+                if i > 0 {
+                    latex.push_str(if negative { " - " } else { " + " });
                 }
-            }).collect();
-            let latex = terms.join(" + ");
+                latex.push_str(&term);
+            }
             (latex, Precedence::AddSub)
         }
         Expression::Negation(n) => {
-            let (inner_latex, inner_prec) = expr_to_latex(&n.term);
-            let latex = if inner_prec < Precedence::Unary {
+            let (inner_latex, inner_prec) = expr_to_latex(&n.term, child(highlight, 0));
+            let latex = if inner_prec < Precedence::Mul || inner_latex.starts_with('-') {
                 format!("-({})", inner_latex)
             } else {
                 format!("-{}", inner_latex)
@@ -78,26 +115,31 @@ fn expr_to_latex(expr: &Expression) -> (String, Precedence) {
             (latex, Precedence::Unary)
         }
         Expression::Exponentiation(e) => {
-            let (base_latex, base_prec) = expr_to_latex(&e.base);
-            let (exponent_latex, exponent_prec) = expr_to_latex(&e.exponent);
-            let base_str = if base_prec < Precedence::Exp {
+            let (base_latex, base_prec) = expr_to_latex(&e.base, child(highlight, 0));
+            // the exponent is already grouped by the braces so it never needs parentheses
+            let (exponent_latex, _) = expr_to_latex(&e.exponent, child(highlight, 1));
+            let base_str = if base_prec <= Precedence::Exp || base_latex.starts_with('-') {
                 format!("({})", base_latex)
             } else {
                 base_latex
             };
-            let exponent_str = if exponent_prec < Precedence::Atom {
-                format!("({})", exponent_latex)
-            } else {
-                exponent_latex
-            };
-            let latex = format!("{}^{{{}}}", base_str, exponent_str);
+            let latex = format!("{}^{{{}}}", base_str, exponent_latex);
             (latex, Precedence::Exp)
         }
         Expression::Sqrt(s) => {
-            let (arg_latex, _) = expr_to_latex(&s.arg);
+            let (arg_latex, _) = expr_to_latex(&s.arg, child(highlight, 0));
             let latex = format!("\\sqrt{{{}}}", arg_latex);
             (latex, Precedence::Atom)
         }
+        // 30^\circ
+        Expression::Function(Function::Degrees(a)) => {
+            let latex = format!(
+                "{}^\\circ",
+                term_with_parens(a, Precedence::Atom, child(highlight, 0))
+            );
+            // below Exp so (30^\circ)^{2} keeps its parentheses
+            (latex, Precedence::Unary)
+        }
         Expression::Function(f) => {
             let (name, arg) = match f {
                 Function::Sin(a) => ("\\sin", a),
@@ -112,10 +154,157 @@ fn expr_to_latex(expr: &Expression) -> (String, Precedence) {
                 Function::Arccsc(a) => ("\\arccsc", a),
                 Function::Arcsec(a) => ("\\arcsec", a),
                 Function::Arccot(a) => ("\\arccot", a),
+                Function::Sinh(a) => ("\\sinh", a),
+                Function::Cosh(a) => ("\\cosh", a),
+                Function::Tanh(a) => ("\\tanh", a),
+                Function::Csch(a) => ("\\operatorname{csch}", a),
+                Function::Sech(a) => ("\\operatorname{sech}", a),
+                Function::Coth(a) => ("\\coth", a),
+                Function::Arcsinh(a) => ("\\operatorname{arcsinh}", a),
+                Function::Arccosh(a) => ("\\operatorname{arccosh}", a),
+                Function::Arctanh(a) => ("\\operatorname{arctanh}", a),
+                Function::Arccsch(a) => ("\\operatorname{arccsch}", a),
+                Function::Arcsech(a) => ("\\operatorname{arcsech}", a),
+                Function::Arccoth(a) => ("\\operatorname{arccoth}", a),
+                Function::Degrees(_) => unreachable!(),
             };
-            let (arg_latex, _) = expr_to_latex(arg);
+            let (arg_latex, _) = expr_to_latex(arg, child(highlight, 0));
             let latex = format!("{}({})", name, arg_latex);
             (latex, Precedence::Atom)
         }
     }
-}
\ No newline at end of file
+}
+
+fn term_with_parens(
+    term: &Expression,
+    min_prec: Precedence,
+    highlight: Option<&[usize]>,
+) -> String {
+    let (latex, prec) = expr_to_latex(term, highlight);
+    if prec < min_prec {
+        format!("({})", latex)
+    } else {
+        latex
+    }
+}
+
+// the latex of -term if term is negative, so it can be written after a minus sign
+fn negated(term: &Expression, highlight: Option<&[usize]>) -> Option<String> {
+    match term {
+        Expression::Negation(n) => Some(term_with_parens(
+            &n.term,
+            Precedence::Mul,
+            child(highlight, 0),
+        )),
+        Expression::Number(n) if *n < 0.0 => Some(expr_to_latex(&Expression::Number(-n), None).0),
+        Expression::Multiplication(m) => match m.terms.split_first() {
+            Some((first, rest)) => match **first {
+                Expression::Number(n) if n < 0.0 => {
+                    let magnitude = Expression::Number(-n);
+                    let mut terms: Vec<(String, &Expression)> = rest
+                        .iter()
+                        .enumerate()
+                        .map(|(i, t)| {
+                            (
+                                term_with_parens(t, Precedence::Mul, child(highlight, i + 1)),
+                                &**t,
+                            )
+                        })
+                        .collect();
+                    if n != -1.0 || terms.is_empty() {
+                        terms.insert(
+                            0,
+                            (expr_to_latex(&magnitude, child(highlight, 0)).0, &magnitude),
+                        );
+                    }
+                    Some(product(terms))
+                }
+                _ => None,
+            },
+            None => None,
+        },
+        _ => None,
+    }
+}
+
+// the factors of a product joined by dots, except 4i which is written like a number
+fn product(factors: Vec<(String, &Expression)>) -> String {
+    let mut latex = String::new();
+    for (i, (factor, expression)) in factors.iter().enumerate() {
+        if i > 0 {
+            let imaginary = matches!(expression, Expression::Variable(v) if v == IMAGINARY_UNIT)
+                && matches!(factors[i - 1].1, Expression::Number(_));
+            if !imaginary {
+                latex.push_str(" \\cdot ");
+            }
+        }
+        latex.push_str(factor);
+    }
+    latex
+}
+
+// 3 + 4i, or 5 \\angle 0.927 in polar form with the angle in angle_mode's unit
+pub fn complex_to_latex(z: Complex, form: ComplexForm, angle_mode: AngleMode) -> String {
+    match form {
+        ComplexForm::Rectangular => {
+            let imaginary = Expression::Multiplication(Multiplication {
+                terms: vec![
+                    Box::new(Expression::Number(z.im)),
+                    Box::new(Expression::Variable(IMAGINARY_UNIT.to_string())),
+                ],
+            });
+            let imaginary = match z.im {
+                1.0 => Expression::Variable(IMAGINARY_UNIT.to_string()),
+                -1.0 => Expression::Negation(Negation {
+                    term: Box::new(Expression::Variable(IMAGINARY_UNIT.to_string())),
+                }),
+                _ => imaginary,
+            };
+            match (z.re, z.im) {
+                (re, 0.0) => to_latex(&Expression::Number(re)),
+                (0.0, _) => to_latex(&imaginary),
+                (re, _) => to_latex(&Expression::Addition(Addition {
+                    terms: vec![Box::new(Expression::Number(re)), Box::new(imaginary)],
+                })),
+            }
+        }
+        ComplexForm::Polar => {
+            let (r, theta) = z.to_polar();
+            let (r, theta) = (
+                to_latex(&Expression::Number(r)),
+                angle_mode.from_radians(theta),
+            );
+            match angle_mode {
+                AngleMode::Radians => {
+                    format!("{} \\angle {}", r, to_latex(&Expression::Number(theta)))
+                }
+                AngleMode::Degrees => format!(
+                    "{} \\angle {}^\\circ",
+                    r,
+                    to_latex(&Expression::Number(theta))
+                ),
+                AngleMode::Gradians => {
+                    format!("{} \\angle {}^g", r, to_latex(&Expression::Number(theta)))
+                }
+            }
+        }
+    }
+}
+
+// the path from the `index`th child to the highlighted node, if it's under that child
+fn child(highlight: Option<&[usize]>, index: usize) -> Option<&[usize]> {
+    match highlight {
+        Some([first, rest @ ..]) if *first == index => Some(rest),
+        _ => None,
+    }
+}
+
+// k when n is k * pi for a small whole number k other than 0 and 1
+fn pi_multiple(n: f64) -> Option<i64> {
+    let multiple = (n / std::f64::consts::PI).round();
+    (multiple != 0.0
+        && multiple != 1.0
+        && multiple.abs() <= 100.0
+        && multiple * std::f64::consts::PI == n)
+        .then_some(multiple as i64)
+}
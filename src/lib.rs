@@ -1,15 +1,67 @@
-
+mod angle;
+mod assumptions;
+mod bigfloat;
+mod bytecode;
+mod cache;
+mod complex;
+mod dual;
+mod egraph;
+mod environment;
+mod equivalence;
+mod eval_error;
 mod expression;
-mod factored;
+mod factor;
+mod interval;
+mod latex;
+mod numeric;
 mod parser;
-mod simplifier;
 mod passes;
-mod latex;
+mod polynomial;
+mod rational;
+mod rules;
+mod simplifier;
+mod steps;
+mod substitution;
 
-pub use parser::parse;
-pub use parser::tokenize;
+pub use angle::AngleMode;
+pub use assumptions::{Assumption, Assumptions, Predicate};
+pub use bigfloat::{
+    check_digits, digits_to_bits, eval_big, eval_big_with, pi, precision, with_precision, BigFloat,
+    MAX_DIGITS,
+};
+pub use bytecode::{compile, Instruction, Program};
+pub use cache::{CacheStats, SharedCache, SimplifyCache, DEFAULT_CAPACITY};
+pub use complex::{
+    eval_complex, eval_complex_with, format_complex, Complex, ComplexForm, IMAGINARY_UNIT,
+};
+pub use dual::{gradient, hessian, Dual, HyperDual};
+pub use environment::{Binding, Environment};
+pub use equivalence::{equivalent, equivalent_with_options, Counterexample, Equivalence};
+pub use eval_error::EvalError;
 pub use expression::eval;
+pub use expression::eval_in;
+pub use expression::eval_with;
+pub use expression::print_expression;
 pub use expression::Expression;
+pub use expression::Function;
+pub use expression::{eval_as, eval_as_with};
+pub use factor::factor;
+pub use interval::{eval_interval, Interval};
+pub use latex::{complex_to_latex, to_latex, to_latex_highlighted};
+pub use numeric::Numeric;
+pub use parser::parse;
+pub use parser::tokenize;
+pub use passes::exponents::{NegativeExponents, Radicals};
+pub use passes::trig::derivative;
+pub use rules::{Condition, Property, Rule, RuleSet};
+pub use simplifier::node_count;
+pub use simplifier::simplify_bounded;
 pub use simplifier::simplify_expression;
-pub use expression::print_expression;
-pub use latex::to_latex;
\ No newline at end of file
+pub use simplifier::simplify_with_options;
+pub use simplifier::simplify_with_steps;
+pub use simplifier::Backend;
+pub use simplifier::SimplifyOptions;
+pub use simplifier::Strategy;
+pub use simplifier::{LimitReached, Limits, Simplified};
+pub use steps::Step;
+pub use substitution::{free_variables, rename, substitute, substitute_all};
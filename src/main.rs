@@ -1,18 +1,138 @@
 //Clap is a command line argument parser made freely available by the MIT license at https://github.com/clap-rs/clap
 //Rustyline is a readline library made freely available by the MIT license at https://github.com/kkawakam/rustyline
-use rustic_math::{eval, parse, tokenize};
+use clap::{Arg, ArgAction, Command};
+use rustic_math::{
+    check_digits, equivalent_with_options, eval_big_with, eval_complex_with, eval_with,
+    format_complex, parse, simplify_bounded, simplify_with_steps, to_latex, tokenize, AngleMode,
+    Assumption, Backend, ComplexForm, Environment, Equivalence, Expression, RuleSet, Simplified,
+    SimplifyCache, SimplifyOptions, Strategy, DEFAULT_CAPACITY,
+};
 use rustyline::DefaultEditor;
 
 fn main() {
+    let matches = Command::new("cli")
+        .about("Evaluates and simplifies mathematical expressions")
+        .arg(
+            Arg::new("strategy")
+                .long("strategy")
+                .short('s')
+                .value_parser(|s: &str| s.parse::<Strategy>())
+                .help("expand, factor, collect(<variable>), trig_reduce, exponential or simplest"),
+        )
+        .arg(
+            Arg::new("backend")
+                .long("backend")
+                .short('b')
+                .value_parser(|s: &str| s.parse::<Backend>())
+                .help("passes or egraph, egraph tries every rewrite at once and keeps the cheapest result"),
+        )
+        .arg(
+            Arg::new("rules")
+                .long("rules")
+                .short('r')
+                .action(ArgAction::Append)
+                .value_parser(|s: &str| RuleSet::load(s))
+                .help("a file of rewrite rules to simplify with, one per line like a*(b+c) -> a*b + a*c"),
+        )
+        .arg(
+            Arg::new("assume")
+                .long("assume")
+                .short('a')
+                .action(ArgAction::Append)
+                .value_parser(|s: &str| s.parse::<Assumption>())
+                .help("something known about a variable, like \"x > 0\" or \"n is integer\""),
+        )
+        .arg(
+            Arg::new("angle")
+                .long("angle")
+                .value_parser(|s: &str| s.parse::<AngleMode>())
+                .help("radians, degrees or gradians, the unit of trig function arguments, defaults to radians"),
+        )
+        .arg(
+            Arg::new("complex")
+                .long("complex")
+                .value_parser(|s: &str| s.parse::<ComplexForm>())
+                .help("evaluate over the complex numbers and write results in rectangular or polar form"),
+        )
+        .arg(
+            Arg::new("parallel")
+                .long("parallel")
+                .short('p')
+                .value_parser(clap::value_parser!(usize))
+                .help("simplify the terms of sums and products with at least this many nodes on every core"),
+        )
+        .arg(Arg::new("expression").help("simplify this expression and exit instead of starting the prompt"))
+        .get_matches();
+
+    // one cache for the whole session so repeated expressions are only simplified once
+    let mut options = SimplifyOptions {
+        cache: Some(SimplifyCache::shared(DEFAULT_CAPACITY)),
+        ..SimplifyOptions::default()
+    };
+    if let Some(strategy) = matches.get_one::<Strategy>("strategy") {
+        options.strategy = strategy.clone();
+    }
+    if let Some(backend) = matches.get_one::<Backend>("backend") {
+        options.backend = *backend;
+    }
+    if let Some(rules) = matches.get_many::<RuleSet>("rules") {
+        options.rules.extend(rules.cloned());
+    }
+    if let Some(angle_mode) = matches.get_one::<AngleMode>("angle") {
+        options.angle_mode = *angle_mode;
+    }
+    if let Some(min_nodes) = matches.get_one::<usize>("parallel") {
+        options.parallel = Some(*min_nodes);
+    }
+    for assumption in matches
+        .get_many::<Assumption>("assume")
+        .into_iter()
+        .flatten()
+    {
+        if let Err(err) = options.assumptions.assume(assumption.clone()) {
+            println!("Error: {}", err);
+            return;
+        }
+    }
+    if let Some(expression) = matches.get_one::<String>("expression") {
+        simplify(expression, &options);
+        return;
+    }
+
     println!("Welcome to the mathmatical solver, please enter an expression: ");
+    println!("Use :simplify <expression> to simplify, :steps <expression> to see how it simplifies, :strategy <strategy> to choose the form it simplifies to, :backend <backend> to choose how it searches and :rules <file> to add rewrite rules");
+    println!("Use :cache to see how often simplified subtrees are reused and :angle <radians|degrees|gradians> to choose the unit of angles");
+    println!("Use assume <assumption> to tell the simplifier something about a variable, like assume x > 0");
+    println!("Use x = <expression> to give a variable a value, const g = <expression> for one that can't change and :vars to list them");
+    println!("Use :complex <rectangular|polar|off> to evaluate over the complex numbers, where i is the imaginary unit");
+    println!(
+        "Use :digits <N|off> to evaluate to N significant digits rather than an f64's 16 or so"
+    );
     let mut rl = DefaultEditor::new().unwrap();
     let _ = rl.load_history("history.txt");
+    let mut environment = Environment::new();
+    let mut complex = matches.get_one::<ComplexForm>("complex").copied();
+    let mut digits = None;
     loop {
-        main_loop(&mut rl);
+        main_loop(
+            &mut rl,
+            &mut options,
+            &mut environment,
+            &mut complex,
+            &mut digits,
+        );
     }
 }
 
-fn main_loop(rl: &mut DefaultEditor) {
+// `complex` is the form complex results are written in, None evaluates over the real numbers
+// `digits` is how many significant digits real results are worked out to, None for an f64's
+fn main_loop(
+    rl: &mut DefaultEditor,
+    options: &mut SimplifyOptions,
+    environment: &mut Environment,
+    complex: &mut Option<ComplexForm>,
+    digits: &mut Option<u32>,
+) {
     let input = rl.readline(">> ");
     match input {
         Ok(input) => {
@@ -21,7 +141,137 @@ fn main_loop(rl: &mut DefaultEditor) {
                 rl.save_history("history.txt").unwrap();
                 std::process::exit(0);
             }
-            compute(input);
+            if let Some(strategy) = command(&input, ":strategy") {
+                match strategy.parse() {
+                    Ok(strategy) => {
+                        options.strategy = strategy;
+                        println!("Strategy: {}", options.strategy);
+                    }
+                    Err(err) => println!("Error: {}", err),
+                }
+            } else if let Some(backend) = command(&input, ":backend") {
+                match backend.parse() {
+                    Ok(backend) => {
+                        options.backend = backend;
+                        println!("Backend: {}", options.backend);
+                    }
+                    Err(err) => println!("Error: {}", err),
+                }
+            } else if let Some(angle_mode) = command(&input, ":angle") {
+                match angle_mode.parse() {
+                    Ok(angle_mode) => {
+                        options.angle_mode = angle_mode;
+                        println!("Angle mode: {}", options.angle_mode);
+                    }
+                    Err(err) => println!("Error: {}", err),
+                }
+            } else if let Some(form) = command(&input, ":complex") {
+                if form.trim() == "off" {
+                    *complex = None;
+                    println!("Complex mode: off");
+                    return;
+                }
+                if let Some(count) = digits {
+                    println!(
+                        "Error: complex results aren't worked out to {} digits, use :digits off first",
+                        count
+                    );
+                    return;
+                }
+                match form.parse() {
+                    Ok(form) => {
+                        *complex = Some(form);
+                        println!("Complex mode: {}", form);
+                    }
+                    Err(err) => println!("Error: {}", err),
+                }
+            } else if let Some(count) = command(&input, ":digits") {
+                if count.trim() == "off" {
+                    *digits = None;
+                    println!("Digits: off");
+                    return;
+                }
+                if let Some(form) = complex {
+                    println!(
+                        "Error: :digits only applies to real results and complex mode is {}, use :complex off first",
+                        form
+                    );
+                    return;
+                }
+                match count.trim().parse::<u32>() {
+                    Ok(count) => match check_digits(count) {
+                        Ok(count) => {
+                            *digits = Some(count);
+                            println!("Digits: {}", count);
+                        }
+                        Err(err) => println!("Error: {}", err),
+                    },
+                    Err(_) => println!("Error: expected a number of digits or off"),
+                }
+            } else if input.trim() == ":cache" {
+                if let Some(cache) = &options.cache {
+                    let stats = cache.lock().unwrap().stats();
+                    println!(
+                        "Cache: {} hits, {} misses, {} of {} entries used",
+                        stats.hits, stats.misses, stats.entries, stats.capacity
+                    );
+                }
+            } else if let Some(assumption) = command(&input, "assume") {
+                if assumption.trim().is_empty() {
+                    println!("Assuming: {}", options.assumptions);
+                    return;
+                }
+                match assumption
+                    .parse()
+                    .and_then(|assumption| options.assumptions.assume(assumption))
+                {
+                    Ok(()) => println!("Assuming: {}", options.assumptions),
+                    Err(err) => println!("Error: {}", err),
+                }
+            } else if let Some(path) = command(&input, ":rules") {
+                match RuleSet::load(path.trim()) {
+                    Ok(rules) => {
+                        println!("Loaded {} rules from {}", rules.rules.len(), rules.name);
+                        options.rules.push(rules);
+                    }
+                    Err(err) => println!("Error: {}", err),
+                }
+            } else if input.trim() == ":vars" {
+                println!("Variables: {}", environment);
+            } else if let Some(name) = command(&input, ":unset") {
+                match environment.remove(name.trim()) {
+                    Some(_) => println!("Variables: {}", environment),
+                    None => println!("Error: {} has no value", name.trim()),
+                }
+            } else if let Some(definition) = command(&input, "const") {
+                match assignment(definition) {
+                    Some((name, expression)) => {
+                        environment.angle_mode = options.angle_mode;
+                        match eval_with(&expression, environment)
+                            .map_err(|err| err.to_string())
+                            .and_then(|value| {
+                                environment.define_constant(&name, value).map(|()| value)
+                            }) {
+                            Ok(value) => println!("{} = {}", name, value),
+                            Err(err) => println!("Error: {}", err),
+                        }
+                    }
+                    None => println!("Error: expected const <variable> = <expression>"),
+                }
+            } else if let Some((name, expression)) = assignment(&input) {
+                environment.angle_mode = options.angle_mode;
+                match environment.assign(&name, expression) {
+                    Ok(()) => println!("{} = {}", name, environment.get(&name).unwrap()),
+                    Err(err) => println!("Error: {}", err),
+                }
+            } else if let Some(expression) = command(&input, ":simplify") {
+                simplify(expression, options);
+            } else if let Some(expression) = command(&input, ":steps") {
+                steps(expression, options);
+            } else {
+                environment.angle_mode = options.angle_mode;
+                compute(input, options, environment, *complex, *digits);
+            }
         }
         Err(_) => {
             rl.save_history("history.txt").unwrap();
@@ -30,22 +280,153 @@ fn main_loop(rl: &mut DefaultEditor) {
     }
 }
 
-fn compute(input: String) {
+fn simplify(input: &str, options: &SimplifyOptions) {
+    let expressions = parse(tokenize(input.to_string()));
+    if expressions.len() != 1 {
+        println!("Error: expected exactly one expression to simplify");
+        return;
+    }
+    print_simplified(&simplify_bounded(expressions[0].clone(), options));
+}
+
+fn steps(input: &str, options: &SimplifyOptions) {
+    let expressions = parse(tokenize(input.to_string()));
+    if expressions.len() != 1 {
+        println!("Error: expected exactly one expression to simplify");
+        return;
+    }
+    let (simplified, steps) = simplify_with_steps(expressions[0].clone(), options);
+    for (i, step) in steps.iter().enumerate() {
+        println!(
+            "{}. {}: {} = {}",
+            i + 1,
+            step.description,
+            step.before,
+            step.after
+        );
+    }
+    print_simplified(&simplified);
+}
+
+fn print_simplified(simplified: &Simplified) {
+    if let Some(limit) = simplified.limit {
+        println!("Stopped early because {}", limit);
+    }
+    println!("Simplified: {}", to_latex(&simplified.expression));
+}
+
+// what follows the command `name` in `input`, when `name` is a whole word, so assumed = 3 is an
+// assignment rather than assume d = 3
+fn command<'a>(input: &'a str, name: &str) -> Option<&'a str> {
+    let rest = input.trim_start().strip_prefix(name)?;
+    (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some(rest)
+}
+
+// x = <expression>, where the left side is a lone variable rather than an equation to check
+fn assignment(input: &str) -> Option<(String, Expression)> {
+    let (name, value) = input.split_once('=')?;
+    if value.contains('=') {
+        return None;
+    }
+    match parse(tokenize(name.to_string())).as_slice() {
+        [Expression::Variable(name)] => {
+            let mut expressions = parse(tokenize(value.to_string()));
+            (expressions.len() == 1).then(|| (name.clone(), expressions.remove(0)))
+        }
+        _ => None,
+    }
+}
+
+fn compute(
+    input: String,
+    options: &SimplifyOptions,
+    environment: &Environment,
+    complex: Option<ComplexForm>,
+    digits: Option<u32>,
+) {
     let tokens = tokenize(input);
     let expressions = parse(tokens);
-    if expressions.len() == 1 {
-        let result = eval(&expressions[0]);
-        println!("Result: {}", result);
+    if let (1, Some(form)) = (expressions.len(), complex) {
+        match eval_complex_with(&expressions[0], environment) {
+            Ok(result) => println!(
+                "Result: {}",
+                format_complex(result, form, options.angle_mode)
+            ),
+            Err(err) => println!("Error: {}", err),
+        }
+    } else if let (1, Some(digits)) = (expressions.len(), digits) {
+        match eval_big_with(&expressions[0], digits, environment) {
+            Ok(result) => println!("Result: {}", result.to_digits(digits)),
+            Err(err) => println!("Error: {}", err),
+        }
+    } else if expressions.len() == 1 {
+        match eval_with(&expressions[0], environment) {
+            Ok(result) => println!("Result: {}", result),
+            Err(err) => println!("Error: {}", err),
+        }
     } else {
-        let evaled: Vec<f64> = expressions.iter().map(eval).collect();
-        let val = evaled[0];
-        for (i, item) in evaled.into_iter().enumerate() {
-            if val != item {
-                println!("the first expression evaluated to {}, but the {}th expression evaluated to {}", val, i, item);
-                println!("False");
-                return;
+        // a = b = c holds when every expression is equivalent to the first,
+        // with the variables that have values taking them and only the rest sampled
+        let expressions: Vec<Expression> = expressions
+            .iter()
+            .map(|expression| environment.substitute_bindings(expression))
+            .collect();
+        let mut samples = None;
+        for (i, expression) in expressions.iter().enumerate().skip(1) {
+            match equivalent_with_options(&expressions[0], expression, options) {
+                Equivalence::Proven => {}
+                Equivalence::Probable { samples: n } => {
+                    samples = Some(samples.map_or(n, |s: usize| s.min(n)))
+                }
+                Equivalence::Disproven(counterexample) => {
+                    print!(
+                        "the first expression evaluated to {}, but expression {} evaluated to {}",
+                        counterexample.left,
+                        i + 1,
+                        counterexample.right
+                    );
+                    if counterexample.values.is_empty() {
+                        println!();
+                    } else {
+                        println!(" when {}", counterexample);
+                    }
+                    println!("False");
+                    return;
+                }
+                Equivalence::Undetermined(Some(err)) => {
+                    println!("Error: {}", err);
+                    return;
+                }
+                Equivalence::Undetermined(None) => {
+                    println!("Error: no values the assumptions allow could be tried");
+                    return;
+                }
             }
         }
-        println!("True({})", val);
+        // without variables the numbers agreeing is all there is to check
+        match (samples, eval_with(&expressions[0], environment)) {
+            (_, Ok(value)) => println!("True({})", value),
+            (None, Err(_)) => println!("True"),
+            (Some(samples), Err(_)) => println!(
+                "Probably true, they agree at all {} points that were tried",
+                samples
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_whole_words() {
+        assert_eq!(command("assume x > 0", "assume"), Some(" x > 0"));
+        assert_eq!(command("assume", "assume"), Some(""));
+        assert_eq!(command("assumed = 3", "assume"), None);
+        assert_eq!(command("const g = 9.8", "const"), Some(" g = 9.8"));
+        assert_eq!(command("constant = 3", "const"), None);
+        assert_eq!(command(":digits 30", ":digits"), Some(" 30"));
+        assert_eq!(command(":digitsx", ":digits"), None);
     }
 }
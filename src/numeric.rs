@@ -0,0 +1,137 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::{
+    angle::AngleMode,
+    eval_error::EvalError,
+    expression::{apply, Function},
+};
+
+// a number type expressions can be evaluated over with eval_as, like f64 or Complex
+// the operators can't fail, the methods are where a type decides what is undefined for it
+pub trait Numeric:
+    Sized
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    fn from_f64(value: f64) -> Self;
+
+    // the value of i, which only types with imaginary numbers have
+    fn imaginary_unit() -> Result<Self, EvalError> {
+        Err(EvalError::NotReal { path: Vec::new() })
+    }
+
+    fn is_zero(&self) -> bool;
+
+    fn divide(self, divisor: Self) -> Result<Self, EvalError> {
+        if divisor.is_zero() {
+            return Err(EvalError::division_by_zero());
+        }
+        Ok(self / divisor)
+    }
+
+    fn powf(self, exponent: Self) -> Result<Self, EvalError>;
+
+    fn sqrt(self) -> Result<Self, EvalError>;
+
+    // a function of the value, with trig functions taking their arguments and inverse trig functions
+    // giving their results in angle_mode's unit, and a DomainError outside the domain or at a pole
+    fn function(function: &Function, value: Self, angle_mode: AngleMode)
+        -> Result<Self, EvalError>;
+
+    // the value of a node if it's a usable number, NaN or Overflow if not
+    fn check(self) -> Result<Self, EvalError>;
+}
+
+impl Numeric for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0.0
+    }
+
+    fn powf(self, exponent: Self) -> Result<Self, EvalError> {
+        if self == 0.0 && exponent < 0.0 {
+            return Err(EvalError::division_by_zero());
+        }
+        Ok(f64::powf(self, exponent))
+    }
+
+    fn sqrt(self) -> Result<Self, EvalError> {
+        if self < 0.0 {
+            return Err(EvalError::domain("sqrt", self));
+        }
+        Ok(f64::sqrt(self))
+    }
+
+    fn function(
+        function: &Function,
+        value: Self,
+        angle_mode: AngleMode,
+    ) -> Result<Self, EvalError> {
+        let result = apply(function, value, angle_mode)?;
+        // like arcsin(2), outside the domain without a check of its own
+        if result.is_nan() {
+            return Err(EvalError::domain(function.name(), value));
+        }
+        Ok(result)
+    }
+
+    fn check(self) -> Result<Self, EvalError> {
+        if self.is_nan() {
+            Err(EvalError::NaN { path: Vec::new() })
+        } else if self.is_infinite() {
+            Err(EvalError::Overflow { path: Vec::new() })
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl Numeric for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0.0
+    }
+
+    fn powf(self, exponent: Self) -> Result<Self, EvalError> {
+        if self == 0.0 && exponent < 0.0 {
+            return Err(EvalError::division_by_zero());
+        }
+        Ok(f32::powf(self, exponent))
+    }
+
+    fn sqrt(self) -> Result<Self, EvalError> {
+        if self < 0.0 {
+            return Err(EvalError::domain("sqrt", self as f64));
+        }
+        Ok(f32::sqrt(self))
+    }
+
+    // worked out in f64 and rounded, so the exact values and domain checks are the same as f64's
+    fn function(
+        function: &Function,
+        value: Self,
+        angle_mode: AngleMode,
+    ) -> Result<Self, EvalError> {
+        let result = <f64 as Numeric>::function(function, value as f64, angle_mode)?;
+        Ok(result as f32)
+    }
+
+    fn check(self) -> Result<Self, EvalError> {
+        if self.is_nan() {
+            Err(EvalError::NaN { path: Vec::new() })
+        } else if self.is_infinite() {
+            Err(EvalError::Overflow { path: Vec::new() })
+        } else {
+            Ok(self)
+        }
+    }
+}
@@ -1,12 +1,18 @@
-use std::{collections::VecDeque};
+use std::collections::VecDeque;
 
-use crate::expression::{convert_to_expression, Expression};
+use crate::{
+    complex::IMAGINARY_UNIT,
+    expression::{convert_to_expression, Expression},
+};
 
 #[derive(Debug, Clone)]
 pub enum Token {
     Number(f64),
+    // a number written right before i, like 4i
+    Imaginary(f64),
     Variable(String),
     PI,
+    E,
     Plus,
     Minus,
     Multiply,
@@ -28,6 +34,20 @@ pub enum Token {
     Arccsc,
     Arcsec,
     Arccot,
+    Sinh,
+    Cosh,
+    Tanh,
+    Csch,
+    Sech,
+    Coth,
+    Arcsinh,
+    Arccosh,
+    Arctanh,
+    Arccsch,
+    Arcsech,
+    Arccoth,
+    //° or deg after a number, like 30°
+    Degree,
 }
 
 fn match_token(input: &mut String, tokens: &mut Vec<Token>, token: Token, token_str: &str) -> bool {
@@ -53,6 +73,19 @@ pub fn tokenize(mut input: String) -> Vec<Token> {
         (Token::CloseParen, ")"),
         (Token::Pow, "^"),
         (Token::Equals, "="),
+        //hyperbolic functions come first so sinh isn't read as sin followed by h
+        (Token::Sinh, "sinh"),
+        (Token::Cosh, "cosh"),
+        (Token::Tanh, "tanh"),
+        (Token::Csch, "csch"),
+        (Token::Sech, "sech"),
+        (Token::Coth, "coth"),
+        (Token::Arcsinh, "arcsinh"),
+        (Token::Arccosh, "arccosh"),
+        (Token::Arctanh, "arctanh"),
+        (Token::Arccsch, "arccsch"),
+        (Token::Arcsech, "arcsech"),
+        (Token::Arccoth, "arccoth"),
         (Token::Sin, "sin"),
         (Token::Cos, "cos"),
         (Token::Tan, "tan"),
@@ -66,12 +99,15 @@ pub fn tokenize(mut input: String) -> Vec<Token> {
         (Token::Arcsec, "arcsec"),
         (Token::Arccot, "arccot"),
         (Token::Sqrt, "sqrt"),
+        (Token::Degree, "°"),
+        (Token::Degree, "deg"),
         (Token::PI, "pi"),
+        (Token::E, "e"),
     ];
 
     while !input.is_empty() {
         //check for whitespace, remove it and rerun loop if whitespace is found
-        if whitespace_chars.contains(&&input[0..1]) {
+        if whitespace_chars.iter().any(|c| input.starts_with(c)) {
             input.drain(..1);
             continue;
         }
@@ -99,7 +135,12 @@ pub fn tokenize(mut input: String) -> Vec<Token> {
         }
         input.drain(..number.len());
         if !number.is_empty() {
-            tokens.push(Token::Number(number.parse().unwrap()));
+            if input.starts_with(IMAGINARY_UNIT) {
+                input.drain(..IMAGINARY_UNIT.len());
+                tokens.push(Token::Imaginary(number.parse().unwrap()));
+            } else {
+                tokens.push(Token::Number(number.parse().unwrap()));
+            }
             continue;
         }
 
@@ -107,7 +148,7 @@ pub fn tokenize(mut input: String) -> Vec<Token> {
         let var = input.chars().next().unwrap();
         if var.is_alphabetic() {
             tokens.push(Token::Variable(var.to_string()));
-            input.drain(..1);
+            input.drain(..var.len_utf8());
             continue;
         }
 
@@ -121,7 +162,9 @@ pub fn tokenize(mut input: String) -> Vec<Token> {
 #[derive(Debug, Clone)]
 pub enum ASTNode {
     Number(f64),
+    Imaginary(f64),
     PI,
+    E,
     Variable(String),
     BinaryOp(Box<ASTNode>, Box<ASTNode>, BinaryOp),
     UnaryOp(Box<ASTNode>, UnaryOp),
@@ -151,7 +194,20 @@ pub enum UnaryOp {
     Arccsc,
     Arcsec,
     Arccot,
+    Sinh,
+    Cosh,
+    Tanh,
+    Csch,
+    Sech,
+    Coth,
+    Arcsinh,
+    Arccosh,
+    Arctanh,
+    Arccsch,
+    Arcsech,
+    Arccoth,
     Sqrt,
+    Degrees,
 }
 
 #[derive(Debug, Clone, PartialEq, Copy)]
@@ -174,6 +230,18 @@ enum ShuntingYardStack {
     Arccsc,
     Arcsec,
     Arccot,
+    Sinh,
+    Cosh,
+    Tanh,
+    Csch,
+    Sech,
+    Coth,
+    Arcsinh,
+    Arccosh,
+    Arctanh,
+    Arccsch,
+    Arcsech,
+    Arccoth,
     Sqrt,
     UnaryMinus,
 }
@@ -199,6 +267,18 @@ fn get_precedence(op: ShuntingYardStack) -> i32 {
         ShuntingYardStack::Arccsc => 0,
         ShuntingYardStack::Arcsec => 0,
         ShuntingYardStack::Arccot => 0,
+        ShuntingYardStack::Sinh => 0,
+        ShuntingYardStack::Cosh => 0,
+        ShuntingYardStack::Tanh => 0,
+        ShuntingYardStack::Csch => 0,
+        ShuntingYardStack::Sech => 0,
+        ShuntingYardStack::Coth => 0,
+        ShuntingYardStack::Arcsinh => 0,
+        ShuntingYardStack::Arccosh => 0,
+        ShuntingYardStack::Arctanh => 0,
+        ShuntingYardStack::Arccsch => 0,
+        ShuntingYardStack::Arcsech => 0,
+        ShuntingYardStack::Arccoth => 0,
         ShuntingYardStack::Sqrt => 0,
     }
 }
@@ -228,6 +308,18 @@ fn shunting_yard_to_unary_op(op: ShuntingYardStack) -> Option<UnaryOp> {
         ShuntingYardStack::Arccsc => Some(UnaryOp::Arccsc),
         ShuntingYardStack::Arcsec => Some(UnaryOp::Arcsec),
         ShuntingYardStack::Arccot => Some(UnaryOp::Arccot),
+        ShuntingYardStack::Sinh => Some(UnaryOp::Sinh),
+        ShuntingYardStack::Cosh => Some(UnaryOp::Cosh),
+        ShuntingYardStack::Tanh => Some(UnaryOp::Tanh),
+        ShuntingYardStack::Csch => Some(UnaryOp::Csch),
+        ShuntingYardStack::Sech => Some(UnaryOp::Sech),
+        ShuntingYardStack::Coth => Some(UnaryOp::Coth),
+        ShuntingYardStack::Arcsinh => Some(UnaryOp::Arcsinh),
+        ShuntingYardStack::Arccosh => Some(UnaryOp::Arccosh),
+        ShuntingYardStack::Arctanh => Some(UnaryOp::Arctanh),
+        ShuntingYardStack::Arccsch => Some(UnaryOp::Arccsch),
+        ShuntingYardStack::Arcsech => Some(UnaryOp::Arcsech),
+        ShuntingYardStack::Arccoth => Some(UnaryOp::Arccoth),
         ShuntingYardStack::Sqrt => Some(UnaryOp::Sqrt),
         ShuntingYardStack::UnaryMinus => Some(UnaryOp::Negate),
         _ => None,
@@ -242,6 +334,7 @@ pub fn parse(tokens: Vec<Token>) -> Vec<Expression> {
     let mut prev_token: Option<Token> = None; // Track previous token
 
     for token in tokens {
+        let current_token = token.clone();
         match token {
             Token::Multiply => {
                 //compare to the top of the stack
@@ -313,29 +406,8 @@ pub fn parse(tokens: Vec<Token>) -> Vec<Expression> {
 
                 if is_unary {
                     // Handle unary negation (high precedence)
-                    while !stack.is_empty()
-                        && get_precedence(*stack.last().unwrap())
-                            >= get_precedence(ShuntingYardStack::UnaryMinus)
-                    {
-                        let op = stack.pop().unwrap();
-                        match shunting_yard_to_unary_op(op) {
-                            Some(unary_op) => {
-                                // Handle unary operator
-                                let operand = Box::new(expression_output.pop_back().unwrap());
-                                expression_output.push_back(ASTNode::UnaryOp(operand, unary_op));
-                            }
-                            None => {
-                                // Handle binary operator
-                                let rhs = Box::new(expression_output.pop_back().unwrap());
-                                let lhs = Box::new(expression_output.pop_back().unwrap());
-                                expression_output.push_back(ASTNode::BinaryOp(
-                                    lhs,
-                                    rhs,
-                                    shunting_yard_to_binary_op(op),
-                                ));
-                            }
-                        }
-                    }
+                    // it is a prefix operator so there is nothing on its left to reduce yet,
+                    // this lets negations stack like --x
                     stack.push(ShuntingYardStack::UnaryMinus);
                 } else {
                     // Handle binary subtraction (normal precedence)
@@ -455,6 +527,42 @@ pub fn parse(tokens: Vec<Token>) -> Vec<Expression> {
             Token::Arccot => {
                 stack.push(ShuntingYardStack::Arccot);
             }
+            Token::Sinh => {
+                stack.push(ShuntingYardStack::Sinh);
+            }
+            Token::Cosh => {
+                stack.push(ShuntingYardStack::Cosh);
+            }
+            Token::Tanh => {
+                stack.push(ShuntingYardStack::Tanh);
+            }
+            Token::Csch => {
+                stack.push(ShuntingYardStack::Csch);
+            }
+            Token::Sech => {
+                stack.push(ShuntingYardStack::Sech);
+            }
+            Token::Coth => {
+                stack.push(ShuntingYardStack::Coth);
+            }
+            Token::Arcsinh => {
+                stack.push(ShuntingYardStack::Arcsinh);
+            }
+            Token::Arccosh => {
+                stack.push(ShuntingYardStack::Arccosh);
+            }
+            Token::Arctanh => {
+                stack.push(ShuntingYardStack::Arctanh);
+            }
+            Token::Arccsch => {
+                stack.push(ShuntingYardStack::Arccsch);
+            }
+            Token::Arcsech => {
+                stack.push(ShuntingYardStack::Arcsech);
+            }
+            Token::Arccoth => {
+                stack.push(ShuntingYardStack::Arccoth);
+            }
             Token::Sqrt => {
                 stack.push(ShuntingYardStack::Sqrt);
             }
@@ -495,16 +603,26 @@ pub fn parse(tokens: Vec<Token>) -> Vec<Expression> {
             Token::Number(n) => {
                 expression_output.push_back(ASTNode::Number(n));
             }
+            Token::Imaginary(n) => {
+                expression_output.push_back(ASTNode::Imaginary(n));
+            }
             Token::Variable(v) => {
                 expression_output.push_back(ASTNode::Variable(v));
             }
             Token::PI => {
                 expression_output.push_back(ASTNode::PI);
             }
+            Token::E => {
+                expression_output.push_back(ASTNode::E);
+            }
+            Token::Degree => {
+                //a postfix operator, it applies to whatever was just read, a number or a parenthesized group
+                let angle = Box::new(expression_output.pop_back().unwrap());
+                expression_output.push_back(ASTNode::UnaryOp(angle, UnaryOp::Degrees));
+            }
             Token::Equals => {
                 //pop all remaining operators off the stack
-                while !stack.is_empty() {
-                    let op = stack.pop().unwrap();
+                while let Some(op) = stack.pop() {
                     match shunting_yard_to_unary_op(op) {
                         Some(unary_op) => {
                             // Handle unary operator
@@ -526,9 +644,9 @@ pub fn parse(tokens: Vec<Token>) -> Vec<Expression> {
                 true_output.push(expression_output.pop_back().unwrap());
             }
         }
+        prev_token = Some(current_token);
     }
-    while !stack.is_empty() {
-        let op = stack.pop().unwrap();
+    while let Some(op) = stack.pop() {
         match shunting_yard_to_unary_op(op) {
             Some(unary_op) => {
                 // Handle unary operator
@@ -1,6 +1,5 @@
 use crate::expression::{Addition, Expression, Multiplication};
 
-
 pub fn coalesce_multiplication(expression: Expression) -> (Expression, bool) {
     match expression {
         Expression::Multiplication(multiplication) => {
@@ -44,4 +43,4 @@ pub fn coalesce_addition(expression: Expression) -> (Expression, bool) {
         }
         _ => (expression, true),
     }
-}
\ No newline at end of file
+}
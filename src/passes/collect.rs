@@ -0,0 +1,109 @@
+use crate::expression::{
+    deep_copy, Addition, Exponentiation, Expression, Multiplication, Negation,
+};
+
+pub fn collect(variable: String) -> impl Fn(Expression) -> (Expression, bool) {
+    move |expression| match expression {
+        Expression::Addition(addition) => {
+            // a*x^2 + b*x^2 + c*x + d -> (a + b)*x^2 + c*x + d
+
+            // group every term by the power of the variable it contains, highest power first
+            let mut groups: Vec<(f64, Vec<Expression>)> = Vec::new();
+            let mut rest = Vec::new();
+            for term in addition.terms.iter() {
+                match split_power(term, &variable) {
+                    Some((power, coefficient)) => {
+                        match groups.iter_mut().find(|(p, _)| *p == power) {
+                            Some((_, coefficients)) => coefficients.push(coefficient),
+                            None => groups.push((power, vec![coefficient])),
+                        }
+                    }
+                    None => rest.push(Box::new(deep_copy(term))),
+                }
+            }
+            if groups
+                .iter()
+                .all(|(_, coefficients)| coefficients.len() == 1)
+            {
+                return (Expression::Addition(addition), true);
+            }
+            groups.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+            let mut terms: Vec<Box<Expression>> = groups
+                .into_iter()
+                .map(|(power, mut coefficients)| {
+                    let power = if power == 1.0 {
+                        Expression::Variable(variable.clone())
+                    } else {
+                        Expression::Exponentiation(Exponentiation {
+                            base: Box::new(Expression::Variable(variable.clone())),
+                            exponent: Box::new(Expression::Number(power)),
+                        })
+                    };
+                    let coefficient = if coefficients.len() == 1 {
+                        coefficients.pop().unwrap()
+                    } else {
+                        Expression::Addition(Addition {
+                            terms: coefficients.into_iter().map(Box::new).collect(),
+                        })
+                    };
+                    Box::new(Expression::Multiplication(Multiplication {
+                        terms: vec![Box::new(coefficient), Box::new(power)],
+                    }))
+                })
+                .collect();
+            terms.extend(rest);
+            (Expression::Addition(Addition { terms }), false)
+        }
+        _ => (expression, true),
+    }
+}
+
+// splits a term into the power of `variable` it contains and everything else, 3*y*x^2 -> (2, 3*y)
+// gives None if the variable doesn't appear as a plain factor
+fn split_power(term: &Expression, variable: &str) -> Option<(f64, Expression)> {
+    let power_of = |factor: &Expression| match factor {
+        Expression::Variable(v) if v == variable => Some(1.0),
+        Expression::Exponentiation(exponentiation) => {
+            match (&*exponentiation.base, &*exponentiation.exponent) {
+                (Expression::Variable(v), Expression::Number(n)) if v == variable => Some(*n),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    match term {
+        Expression::Negation(negation) => {
+            let (power, coefficient) = split_power(&negation.term, variable)?;
+            Some((
+                power,
+                Expression::Negation(Negation {
+                    term: Box::new(coefficient),
+                }),
+            ))
+        }
+        Expression::Multiplication(multiplication) => {
+            let index = multiplication
+                .terms
+                .iter()
+                .position(|factor| power_of(factor).is_some())?;
+            let power = power_of(&multiplication.terms[index])?;
+            let mut coefficient: Vec<Box<Expression>> = multiplication
+                .terms
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != index)
+                .map(|(_, factor)| Box::new(deep_copy(factor)))
+                .collect();
+            Some((
+                power,
+                match coefficient.len() {
+                    0 => Expression::Number(1.0),
+                    1 => *coefficient.pop().unwrap(),
+                    _ => Expression::Multiplication(Multiplication { terms: coefficient }),
+                },
+            ))
+        }
+        term => Some((power_of(term)?, Expression::Number(1.0))),
+    }
+}
@@ -44,4 +44,4 @@ pub fn distribute_multiplication(expression: Expression) -> (Expression, bool) {
         }
         _ => (expression, true),
     }
-}
\ No newline at end of file
+}
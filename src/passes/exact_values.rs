@@ -0,0 +1,31 @@
+use crate::{
+    angle::{exact_inverse, exact_trig, special_angle, AngleMode},
+    expression::{eval_in, Expression, Function},
+};
+
+// sin(pi / 6) -> 1/2 and arcsin(1/2) -> pi / 6, and the same for the other trig functions at multiples of
+// 30° and 45°, with angles in `angle_mode`'s unit
+pub fn exact_values(angle_mode: AngleMode) -> impl Fn(Expression) -> (Expression, bool) {
+    move |expression| match expression {
+        // 30° is just 30 when every angle is in degrees
+        Expression::Function(Function::Degrees(arg)) if angle_mode == AngleMode::Degrees => {
+            (*arg, false)
+        }
+        Expression::Function(function) => match exact_value(&function, angle_mode) {
+            Some(value) => (value, false),
+            None => (Expression::Function(function), true),
+        },
+        _ => (expression, true),
+    }
+}
+
+fn exact_value(function: &Function, angle_mode: AngleMode) -> Option<Expression> {
+    // only constant arguments have a value to look up
+    let value = eval_in(function.arg(), angle_mode).ok()?;
+    match function.name() {
+        name @ ("sin" | "cos" | "tan" | "csc" | "sec" | "cot") => {
+            Some(exact_trig(name, special_angle(value, angle_mode)?)?.to_expression())
+        }
+        name => exact_inverse(name, value).map(|twelfths| angle_mode.angle(twelfths)),
+    }
+}
@@ -0,0 +1,136 @@
+use crate::expression::{deep_copy, Addition, Expression, Multiplication, Negation};
+
+use super::exponents::power;
+
+// the most terms an expanded power may have before it is left alone, (a + b + c)^n has (n + 2 choose 2)
+const MAX_EXPANDED_TERMS: u128 = 1000;
+
+// coefficients above this can't be stored exactly in an f64
+const MAX_EXACT_COEFFICIENT: u128 = 1 << 53;
+
+pub fn expand_power(expression: Expression) -> (Expression, bool) {
+    match expression {
+        Expression::Exponentiation(exponentiation) => {
+            // (a + b)^2 -> a^2 + 2ab + b^2
+
+            // (a + b + c)^n expands to the sum over every k_a + k_b + k_c = n of
+            // (n! / (k_a! k_b! k_c!)) * a^k_a * b^k_b * c^k_c
+
+            if let (Expression::Addition(addition), Expression::Number(n)) =
+                (&*exponentiation.base, &*exponentiation.exponent)
+            {
+                // (a + b)^n already has n + 1 terms, so n past the cap is left alone before a huge n
+                // like 1e300 saturates the conversion
+                if *n >= 2.0 && n.fract() == 0.0 && *n <= MAX_EXPANDED_TERMS as f64 {
+                    if let Some(expanded) = expand(addition, *n as u128) {
+                        return (expanded, false);
+                    }
+                }
+            }
+            (Expression::Exponentiation(exponentiation), true)
+        }
+        _ => (expression, true),
+    }
+}
+
+fn expand(addition: &Addition, n: u128) -> Option<Expression> {
+    let m = addition.terms.len() as u128;
+    // None from the sum or the binomial is more terms than a u128 holds, so more than the cap
+    let count = (n + m)
+        .checked_sub(1)
+        .and_then(|top| binomial(top, m.checked_sub(1)?));
+    if count.map_or(true, |count| count > MAX_EXPANDED_TERMS) {
+        return None;
+    }
+
+    let mut terms = Vec::new();
+    for exponents in compositions(n, addition.terms.len()) {
+        let mut coefficient = multinomial(&exponents)?;
+
+        // pull the signs of negated terms and integer constants out so they end up on the coefficient
+        let mut negative = false;
+        let mut factors = Vec::new();
+        for (term, k) in addition.terms.iter().zip(&exponents) {
+            if *k == 0 {
+                continue;
+            }
+            let base = match &**term {
+                Expression::Negation(negation) => {
+                    negative ^= k % 2 == 1;
+                    &negation.term
+                }
+                base => base,
+            };
+            match base {
+                Expression::Number(value)
+                    if value.fract() == 0.0 && value.abs() < MAX_EXACT_COEFFICIENT as f64 =>
+                {
+                    negative ^= *value < 0.0 && k % 2 == 1;
+                    let magnitude = (value.abs() as u128).checked_pow(u32::try_from(*k).ok()?)?;
+                    coefficient = coefficient.checked_mul(magnitude)?;
+                }
+                base if *k == 1 => factors.push(Box::new(deep_copy(base))),
+                base => factors.push(Box::new(power(
+                    deep_copy(base),
+                    Expression::Number(*k as f64),
+                ))),
+            }
+        }
+        if coefficient > MAX_EXACT_COEFFICIENT {
+            return None;
+        }
+        if coefficient != 1 || factors.is_empty() {
+            factors.insert(0, Box::new(Expression::Number(coefficient as f64)));
+        }
+
+        let term = if factors.len() == 1 {
+            *factors.pop().unwrap()
+        } else {
+            Expression::Multiplication(Multiplication { terms: factors })
+        };
+        terms.push(Box::new(if negative {
+            Expression::Negation(Negation {
+                term: Box::new(term),
+            })
+        } else {
+            term
+        }));
+    }
+    Some(Expression::Addition(Addition { terms }))
+}
+
+// every way to write n as an ordered sum of `parts` non-negative integers, highest first
+fn compositions(n: u128, parts: usize) -> Vec<Vec<u128>> {
+    if parts == 1 {
+        return vec![vec![n]];
+    }
+    let mut result = Vec::new();
+    for first in (0..=n).rev() {
+        for mut rest in compositions(n - first, parts - 1) {
+            rest.insert(0, first);
+            result.push(rest);
+        }
+    }
+    result
+}
+
+// n! / (k_1! k_2! ... k_m!) computed as a product of binomial coefficients so it stays exact
+fn multinomial(exponents: &[u128]) -> Option<u128> {
+    let mut total = 0;
+    let mut result: u128 = 1;
+    for k in exponents {
+        total += k;
+        result = result.checked_mul(binomial(total, *k)?)?;
+    }
+    Some(result)
+}
+
+fn binomial(n: u128, k: u128) -> Option<u128> {
+    let k = k.min(n - k);
+    let mut result: u128 = 1;
+    for i in 1..=k {
+        // result * (n - k + i) is always divisible by i here
+        result = result.checked_mul(n - k + i)? / i;
+    }
+    Some(result)
+}
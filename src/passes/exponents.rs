@@ -0,0 +1,314 @@
+use crate::{
+    assumptions::{Assumptions, Predicate},
+    expression::{deep_copy, Division, Exponentiation, Expression, Multiplication, Negation, Sqrt},
+    polynomial::rational_to_expression,
+    rational::Rational,
+};
+
+// which way x^-n and 1/x^n are normalized
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NegativeExponents {
+    // x^-2 -> 1/x^2
+    #[default]
+    Reciprocal,
+    // 1/x^2 -> x^-2
+    Negative,
+}
+
+// which way sqrt(x) and x^(1/2) are normalized
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Radicals {
+    // x^(1/2) -> sqrt(x)
+    #[default]
+    Sqrt,
+    // sqrt(x) -> x^(1/2)
+    Power,
+}
+
+pub fn power_of_power(assumptions: &Assumptions) -> impl Fn(Expression) -> (Expression, bool) + '_ {
+    move |expression| match expression {
+        Expression::Exponentiation(outer) => {
+            // (x^a)^b -> x^(a * b)
+            // only valid for every x when b is an integer, when x can't be negative,
+            // or when a * b is even and a is an integer so the sign of x is lost either way:
+            // (x^2)^(1/2) is |x| and not x, so it is left alone
+            let inner = match &*outer.base {
+                Expression::Exponentiation(inner) => {
+                    Some((&inner.base, deep_copy(&inner.exponent)))
+                }
+                // sqrt(x) is only defined for x >= 0 so sqrt(x)^b -> x^(b/2) is always sound
+                Expression::Sqrt(sqrt) => Some((&sqrt.arg, Expression::Number(0.5))),
+                _ => None,
+            };
+            if let Some((base, inner_exponent)) = inner {
+                let a = constant_value(&inner_exponent);
+                let b = constant_value(&outer.exponent);
+                let sound = is_integer(b)
+                    || matches!(*outer.base, Expression::Sqrt(_))
+                    || assumptions.holds(base, Predicate::Nonnegative)
+                    || (is_integer(a) && is_even(a.zip(b).map(|(a, b)| a * b)));
+                if sound {
+                    let exponent = multiply_exponents(&inner_exponent, &outer.exponent);
+                    return (
+                        Expression::Exponentiation(Exponentiation {
+                            base: Box::new(deep_copy(base)),
+                            exponent: Box::new(exponent),
+                        }),
+                        false,
+                    );
+                }
+            }
+            (Expression::Exponentiation(outer), true)
+        }
+        _ => (expression, true),
+    }
+}
+
+pub fn root_of_power(assumptions: &Assumptions) -> impl Fn(Expression) -> (Expression, bool) + '_ {
+    move |expression| match expression {
+        Expression::Sqrt(sqrt) => {
+            if let Expression::Exponentiation(exponentiation) = &*sqrt.arg {
+                let half = rational_to_expression(Rational::new(1, 2));
+                let exponent = multiply_exponents(&exponentiation.exponent, &half);
+                // sqrt(x^a) -> x^(a/2) when x >= 0, or for any x when a/2 is even like sqrt(x^4) -> x^2
+                if assumptions.holds(&exponentiation.base, Predicate::Nonnegative)
+                    || is_even(constant_value(&exponentiation.exponent).map(|a| a / 2.0))
+                {
+                    return (power(deep_copy(&exponentiation.base), exponent), false);
+                }
+                // sqrt(x^2) is |x| so when x <= 0 it is -x, sqrt(x^a) -> (-x)^(a/2) for even a
+                // with nothing known about x it is left alone since there is no absolute value
+                if is_even(constant_value(&exponentiation.exponent))
+                    && assumptions.holds(&exponentiation.base, Predicate::Nonpositive)
+                {
+                    let negated = Expression::Negation(Negation {
+                        term: Box::new(deep_copy(&exponentiation.base)),
+                    });
+                    return (power(negated, exponent), false);
+                }
+            }
+            (Expression::Sqrt(sqrt), true)
+        }
+        _ => (expression, true),
+    }
+}
+
+pub fn power_of_product(
+    assumptions: &Assumptions,
+) -> impl Fn(Expression) -> (Expression, bool) + '_ {
+    move |expression| match expression {
+        Expression::Exponentiation(exponentiation) => {
+            // (x * y)^n -> x^n * y^n, for integer n or when no factor can be negative
+            if let Expression::Multiplication(multiplication) = &*exponentiation.base {
+                if is_integer(constant_value(&exponentiation.exponent))
+                    || multiplication
+                        .terms
+                        .iter()
+                        .all(|term| assumptions.holds(term, Predicate::Nonnegative))
+                {
+                    let terms = multiplication
+                        .terms
+                        .iter()
+                        .map(|term| {
+                            Box::new(power(deep_copy(term), deep_copy(&exponentiation.exponent)))
+                        })
+                        .collect();
+                    return (Expression::Multiplication(Multiplication { terms }), false);
+                }
+            }
+            (Expression::Exponentiation(exponentiation), true)
+        }
+        _ => (expression, true),
+    }
+}
+
+pub fn power_of_quotient(
+    assumptions: &Assumptions,
+) -> impl Fn(Expression) -> (Expression, bool) + '_ {
+    move |expression| match expression {
+        Expression::Exponentiation(exponentiation) => {
+            // (x / y)^n -> x^n / y^n, with the same conditions as a product
+            if let Expression::Division(division) = &*exponentiation.base {
+                if is_integer(constant_value(&exponentiation.exponent))
+                    || (assumptions.holds(&division.numerator, Predicate::Nonnegative)
+                        && assumptions.holds(&division.denominator, Predicate::Nonnegative))
+                {
+                    return (
+                        Expression::Division(Division {
+                            numerator: Box::new(power(
+                                deep_copy(&division.numerator),
+                                deep_copy(&exponentiation.exponent),
+                            )),
+                            denominator: Box::new(power(
+                                deep_copy(&division.denominator),
+                                deep_copy(&exponentiation.exponent),
+                            )),
+                        }),
+                        false,
+                    );
+                }
+            }
+            (Expression::Exponentiation(exponentiation), true)
+        }
+        _ => (expression, true),
+    }
+}
+
+pub fn identity_exponents(expression: Expression) -> (Expression, bool) {
+    match expression {
+        Expression::Exponentiation(exponentiation) => {
+            let base = constant_value(&exponentiation.base);
+            let exponent = constant_value(&exponentiation.exponent);
+            match (base, exponent) {
+                // x^0 -> 1
+                (_, Some(0.0)) => (Expression::Number(1.0), false),
+                // x^1 -> x
+                (_, Some(1.0)) => (*exponentiation.base, false),
+                // 1^x -> 1
+                (Some(1.0), _) => (Expression::Number(1.0), false),
+                // 2^3 -> 8 when the result is an exact integer
+                (Some(b), Some(e))
+                    if b.fract() == 0.0
+                        && e.fract() == 0.0
+                        && e > 0.0
+                        && b.abs().powf(e) < 9007199254740992.0 =>
+                {
+                    (Expression::Number(b.powf(e)), false)
+                }
+                _ => (Expression::Exponentiation(exponentiation), true),
+            }
+        }
+        _ => (expression, true),
+    }
+}
+
+pub fn negative_exponents(
+    preference: NegativeExponents,
+) -> impl Fn(Expression) -> (Expression, bool) {
+    move |expression| match (preference, expression) {
+        (NegativeExponents::Reciprocal, Expression::Exponentiation(exponentiation)) => {
+            // x^-n -> 1/x^n
+            match negated_exponent(&exponentiation.exponent) {
+                Some(exponent) => (
+                    Expression::Division(Division {
+                        numerator: Box::new(Expression::Number(1.0)),
+                        denominator: Box::new(power(*exponentiation.base, exponent)),
+                    }),
+                    false,
+                ),
+                None => (Expression::Exponentiation(exponentiation), true),
+            }
+        }
+        (NegativeExponents::Negative, Expression::Division(division))
+            if constant_value(&division.numerator).is_none()
+                || constant_value(&division.denominator).is_none() =>
+        {
+            // a / x^n -> a * x^-n, plain fractions like 1/2 are left alone
+            let reciprocal = match *division.denominator {
+                Expression::Exponentiation(exponentiation) => power(
+                    *exponentiation.base,
+                    negate_exponent(*exponentiation.exponent),
+                ),
+                denominator => power(denominator, Expression::Number(-1.0)),
+            };
+            if constant_value(&division.numerator) == Some(1.0) {
+                (reciprocal, false)
+            } else {
+                (
+                    Expression::Multiplication(Multiplication {
+                        terms: vec![division.numerator, Box::new(reciprocal)],
+                    }),
+                    false,
+                )
+            }
+        }
+        (_, expression) => (expression, true),
+    }
+}
+
+pub fn radicals(preference: Radicals) -> impl Fn(Expression) -> (Expression, bool) {
+    move |expression| match (preference, expression) {
+        (Radicals::Sqrt, Expression::Exponentiation(exponentiation))
+            if constant_value(&exponentiation.exponent) == Some(0.5) =>
+        {
+            // x^(1/2) -> sqrt(x)
+            (
+                Expression::Sqrt(Sqrt {
+                    arg: exponentiation.base,
+                }),
+                false,
+            )
+        }
+        (Radicals::Power, Expression::Sqrt(sqrt)) => {
+            // sqrt(x) -> x^(1/2)
+            (
+                power(*sqrt.arg, rational_to_expression(Rational::new(1, 2))),
+                false,
+            )
+        }
+        (_, expression) => (expression, true),
+    }
+}
+
+pub(crate) fn power(base: Expression, exponent: Expression) -> Expression {
+    Expression::Exponentiation(Exponentiation {
+        base: Box::new(base),
+        exponent: Box::new(exponent),
+    })
+}
+
+// the value of an exponent made only of numbers, like 2, -3 or 1/2
+fn constant_value(expression: &Expression) -> Option<f64> {
+    match expression {
+        Expression::Number(n) => Some(*n),
+        Expression::Negation(negation) => constant_value(&negation.term).map(|n| -n),
+        Expression::Division(division) => {
+            let denominator = constant_value(&division.denominator)?;
+            if denominator == 0.0 {
+                return None;
+            }
+            Some(constant_value(&division.numerator)? / denominator)
+        }
+        _ => None,
+    }
+}
+
+fn is_integer(value: Option<f64>) -> bool {
+    value.is_some_and(|v| v.fract() == 0.0)
+}
+
+fn is_even(value: Option<f64>) -> bool {
+    value.is_some_and(|v| v % 2.0 == 0.0)
+}
+
+// a * b as an exponent, folded to a single number when both are constants
+fn multiply_exponents(a: &Expression, b: &Expression) -> Expression {
+    let product = constant_value(a)
+        .zip(constant_value(b))
+        .and_then(|(a, b)| Rational::from_f64(a * b));
+    match product {
+        Some(product) => rational_to_expression(product),
+        None => Expression::Multiplication(Multiplication {
+            terms: vec![Box::new(deep_copy(a)), Box::new(deep_copy(b))],
+        }),
+    }
+}
+
+// n for an exponent of the form -n
+fn negated_exponent(exponent: &Expression) -> Option<Expression> {
+    match exponent {
+        Expression::Negation(negation) => Some(deep_copy(&negation.term)),
+        Expression::Number(n) if *n < 0.0 => Some(Expression::Number(-n)),
+        _ => None,
+    }
+}
+
+fn negate_exponent(exponent: Expression) -> Expression {
+    match exponent {
+        Expression::Negation(negation) => *negation.term,
+        Expression::Number(n) => Expression::Number(-n),
+        exponent => Expression::Negation(Negation {
+            term: Box::new(exponent),
+        }),
+    }
+}
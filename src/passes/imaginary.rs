@@ -0,0 +1,43 @@
+use crate::{
+    complex::IMAGINARY_UNIT,
+    expression::{Division, Exponentiation, Expression, Multiplication},
+};
+
+// i^2 -> -1, and every other whole power of i to 1, i, -1 or -i, and x / i -> -i * x
+pub fn imaginary_powers(expression: Expression) -> (Expression, bool) {
+    let i = || Box::new(Expression::Variable(IMAGINARY_UNIT.to_string()));
+    match expression {
+        Expression::Division(Division {
+            numerator,
+            denominator,
+        }) if matches!(&*denominator, Expression::Variable(v) if v == IMAGINARY_UNIT) => {
+            let product = Expression::Multiplication(Multiplication {
+                terms: vec![Box::new(Expression::Number(-1.0)), i(), numerator],
+            });
+            (product, false)
+        }
+        Expression::Exponentiation(Exponentiation { base, exponent }) => match (*base, *exponent) {
+            (Expression::Variable(v), Expression::Number(n))
+                if v == IMAGINARY_UNIT && n.fract() == 0.0 =>
+            {
+                let power = match n.rem_euclid(4.0) as i64 {
+                    0 => Expression::Number(1.0),
+                    1 => *i(),
+                    2 => Expression::Number(-1.0),
+                    _ => Expression::Multiplication(Multiplication {
+                        terms: vec![Box::new(Expression::Number(-1.0)), i()],
+                    }),
+                };
+                (power, false)
+            }
+            (base, exponent) => (
+                Expression::Exponentiation(Exponentiation {
+                    base: Box::new(base),
+                    exponent: Box::new(exponent),
+                }),
+                true,
+            ),
+        },
+        _ => (expression, true),
+    }
+}
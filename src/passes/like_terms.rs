@@ -0,0 +1,297 @@
+use std::cmp::Ordering;
+
+use crate::expression::{
+    deep_copy, Addition, Exponentiation, Expression, Multiplication, Negation,
+};
+
+pub fn combine_like_factors(expression: Expression) -> (Expression, bool) {
+    match expression {
+        Expression::Multiplication(mut multiplication) => {
+            if multiplication.terms.len() == 1 {
+                return (*multiplication.terms.pop().unwrap(), false);
+            }
+            // 2 * x * 3 * x^2 -> 6 * x^3
+            let original_len = multiplication.terms.len();
+            let mut coefficient = 1.0;
+            let mut numbers = 0;
+            let mut factors: Vec<(Expression, Expression)> = Vec::new();
+            for term in multiplication.terms.iter() {
+                let (base, exponent) = match &**term {
+                    Expression::Number(n) => {
+                        coefficient *= n;
+                        numbers += 1;
+                        continue;
+                    }
+                    Expression::Exponentiation(exponentiation) => (
+                        deep_copy(&exponentiation.base),
+                        deep_copy(&exponentiation.exponent),
+                    ),
+                    term => (deep_copy(term), Expression::Number(1.0)),
+                };
+                match factors.iter_mut().find(|(existing, _)| *existing == base) {
+                    Some((_, existing_exponent)) => {
+                        *existing_exponent = add_exponents(existing_exponent, &exponent);
+                    }
+                    None => factors.push((base, exponent)),
+                }
+            }
+
+            let mut terms: Vec<Box<Expression>> = factors
+                .into_iter()
+                .map(|(base, exponent)| {
+                    Box::new(match exponent {
+                        Expression::Number(1.0) => base,
+                        exponent => Expression::Exponentiation(Exponentiation {
+                            base: Box::new(base),
+                            exponent: Box::new(exponent),
+                        }),
+                    })
+                })
+                .collect();
+            terms.sort_by(|a, b| compare_factors(a, b));
+
+            if coefficient == 0.0 {
+                return (Expression::Number(0.0), false);
+            }
+            if coefficient != 1.0 || terms.is_empty() {
+                terms.insert(0, Box::new(Expression::Number(coefficient)));
+            }
+
+            // only report a change when something was merged or reordered
+            let merged = terms.len() != original_len || numbers > 1;
+            let reordered = !merged && terms.iter().zip(&multiplication.terms).any(|(a, b)| a != b);
+            if !merged && !reordered {
+                return (Expression::Multiplication(multiplication), true);
+            }
+            if terms.len() == 1 {
+                return (*terms.pop().unwrap(), false);
+            }
+            (Expression::Multiplication(Multiplication { terms }), false)
+        }
+        _ => (expression, true),
+    }
+}
+
+pub fn combine_like_terms(expression: Expression) -> (Expression, bool) {
+    match expression {
+        Expression::Addition(mut addition) => {
+            if addition.terms.len() == 1 {
+                return (*addition.terms.pop().unwrap(), false);
+            }
+            // 2x + 3 + 3x - 1 -> 5x + 2
+            let mut constant = 0.0;
+            let mut constants = 0;
+            let mut groups: Vec<(f64, Expression)> = Vec::new();
+            let mut merged = false;
+            for term in addition.terms.iter() {
+                match split_coefficient(term) {
+                    (c, None) => {
+                        constant += c;
+                        constants += 1;
+                    }
+                    (c, Some(rest)) => {
+                        match groups.iter_mut().find(|(_, existing)| *existing == rest) {
+                            Some((existing_coefficient, _)) => {
+                                *existing_coefficient += c;
+                                merged = true;
+                            }
+                            None => groups.push((c, rest)),
+                        }
+                    }
+                }
+            }
+
+            let zero_terms = groups.iter().any(|(c, _)| *c == 0.0)
+                || (constants > 0 && constant == 0.0 && !groups.is_empty());
+            if !merged && constants <= 1 && !zero_terms {
+                return (Expression::Addition(addition), true);
+            }
+
+            let mut terms: Vec<Box<Expression>> = groups
+                .into_iter()
+                .filter(|(c, _)| *c != 0.0)
+                .map(|(c, rest)| Box::new(with_coefficient(c, rest)))
+                .collect();
+            if constant != 0.0 || terms.is_empty() {
+                terms.push(Box::new(Expression::Number(constant)));
+            }
+            if terms.len() == 1 {
+                return (*terms.pop().unwrap(), false);
+            }
+            (Expression::Addition(Addition { terms }), false)
+        }
+        _ => (expression, true),
+    }
+}
+
+pub fn cancel_division(expression: Expression) -> (Expression, bool) {
+    match expression {
+        Expression::Multiplication(mut multiplication) => {
+            // x * (y / x) -> y
+            for i in 0..multiplication.terms.len() {
+                let Expression::Division(division) = &*multiplication.terms[i] else {
+                    continue;
+                };
+                let matching = multiplication
+                    .terms
+                    .iter()
+                    .enumerate()
+                    .position(|(j, term)| j != i && **term == *division.denominator);
+                if let Some(j) = matching {
+                    *multiplication.terms[i] = deep_copy(&division.numerator);
+                    multiplication.terms.remove(j);
+                    return (Expression::Multiplication(multiplication), false);
+                }
+            }
+            (Expression::Multiplication(multiplication), true)
+        }
+        _ => (expression, true),
+    }
+}
+
+// splits a term into its numeric coefficient and the rest, 3 * x * y -> (3, x * y), 5 -> (5, None)
+pub fn split_coefficient(term: &Expression) -> (f64, Option<Expression>) {
+    match term {
+        Expression::Number(n) => (*n, None),
+        Expression::Negation(negation) => {
+            let (c, rest) = split_coefficient(&negation.term);
+            (-c, rest)
+        }
+        Expression::Multiplication(multiplication) => match multiplication.terms.split_first() {
+            Some((first, rest)) if matches!(**first, Expression::Number(_)) => {
+                let c = match **first {
+                    Expression::Number(n) => n,
+                    _ => unreachable!(),
+                };
+                match rest.len() {
+                    0 => (c, None),
+                    1 => (c, Some(deep_copy(&rest[0]))),
+                    _ => (
+                        c,
+                        Some(Expression::Multiplication(Multiplication {
+                            terms: rest.iter().map(|t| Box::new(deep_copy(t))).collect(),
+                        })),
+                    ),
+                }
+            }
+            _ => (1.0, Some(deep_copy(term))),
+        },
+        _ => (1.0, Some(deep_copy(term))),
+    }
+}
+
+pub fn with_coefficient(coefficient: f64, rest: Expression) -> Expression {
+    if coefficient == 1.0 {
+        return rest;
+    }
+    if coefficient == -1.0 {
+        return Expression::Negation(Negation {
+            term: Box::new(rest),
+        });
+    }
+    let mut terms = vec![Box::new(Expression::Number(coefficient))];
+    match rest {
+        Expression::Multiplication(multiplication) => terms.extend(multiplication.terms),
+        rest => terms.push(Box::new(rest)),
+    }
+    Expression::Multiplication(Multiplication { terms })
+}
+
+fn add_exponents(a: &Expression, b: &Expression) -> Expression {
+    match (a, b) {
+        (Expression::Number(a), Expression::Number(b)) => Expression::Number(a + b),
+        (Expression::Addition(addition), b) => {
+            let mut terms: Vec<Box<Expression>> = addition
+                .terms
+                .iter()
+                .map(|t| Box::new(deep_copy(t)))
+                .collect();
+            terms.push(Box::new(deep_copy(b)));
+            Expression::Addition(Addition { terms })
+        }
+        (a, b) => Expression::Addition(Addition {
+            terms: vec![Box::new(deep_copy(a)), Box::new(deep_copy(b))],
+        }),
+    }
+}
+
+// orders the factors of a product: numbers, then variables alphabetically, then everything else,
+// powers are ordered by their base so x and x^2 end up next to each other
+pub fn compare_factors(a: &Expression, b: &Expression) -> Ordering {
+    let base = |e: &Expression| match e {
+        Expression::Exponentiation(exponentiation) => deep_copy(&exponentiation.base),
+        e => deep_copy(e),
+    };
+    compare_expressions(&base(a), &base(b))
+}
+
+// orders the terms of a sum: highest degree first, then the same as compare_expressions,
+// so a polynomial reads x^2 + 2x + 1 with its constant last like combine_like_terms leaves it
+pub fn compare_terms(a: &Expression, b: &Expression) -> Ordering {
+    degree(b)
+        .total_cmp(&degree(a))
+        .then_with(|| compare_expressions(a, b))
+}
+
+// the total degree of a term in its variables, 0 for anything that isn't a monomial
+fn degree(term: &Expression) -> f64 {
+    match term {
+        Expression::Variable(_) => 1.0,
+        Expression::Exponentiation(exponentiation) => match *exponentiation.exponent {
+            Expression::Number(n) => n * degree(&exponentiation.base),
+            _ => 0.0,
+        },
+        Expression::Multiplication(multiplication) => {
+            multiplication.terms.iter().map(|term| degree(term)).sum()
+        }
+        Expression::Negation(negation) => degree(&negation.term),
+        _ => 0.0,
+    }
+}
+
+// a total order over expressions used to put commutative terms into a canonical order
+pub fn compare_expressions(a: &Expression, b: &Expression) -> Ordering {
+    fn rank(e: &Expression) -> u8 {
+        match e {
+            Expression::Number(_) => 0,
+            Expression::Variable(_) => 1,
+            Expression::Exponentiation(_) => 2,
+            Expression::Function(_) => 3,
+            Expression::Sqrt(_) => 4,
+            Expression::Multiplication(_) => 5,
+            Expression::Division(_) => 6,
+            Expression::Addition(_) => 7,
+            Expression::Negation(_) => 8,
+        }
+    }
+    fn compare_lists(a: &[Box<Expression>], b: &[Box<Expression>]) -> Ordering {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| compare_expressions(x, y))
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or(a.len().cmp(&b.len()))
+    }
+    match (a, b) {
+        (Expression::Number(x), Expression::Number(y)) => x.total_cmp(y),
+        (Expression::Variable(x), Expression::Variable(y)) => x.cmp(y),
+        (Expression::Exponentiation(x), Expression::Exponentiation(y)) => {
+            compare_expressions(&x.base, &y.base)
+                .then_with(|| compare_expressions(&x.exponent, &y.exponent))
+        }
+        (Expression::Function(x), Expression::Function(y)) => x
+            .name()
+            .cmp(y.name())
+            .then_with(|| compare_expressions(x.arg(), y.arg())),
+        (Expression::Sqrt(x), Expression::Sqrt(y)) => compare_expressions(&x.arg, &y.arg),
+        (Expression::Multiplication(x), Expression::Multiplication(y)) => {
+            compare_lists(&x.terms, &y.terms)
+        }
+        (Expression::Addition(x), Expression::Addition(y)) => compare_lists(&x.terms, &y.terms),
+        (Expression::Division(x), Expression::Division(y)) => {
+            compare_expressions(&x.numerator, &y.numerator)
+                .then_with(|| compare_expressions(&x.denominator, &y.denominator))
+        }
+        (Expression::Negation(x), Expression::Negation(y)) => compare_expressions(&x.term, &y.term),
+        (a, b) => rank(a).cmp(&rank(b)),
+    }
+}
@@ -1,2 +1,11 @@
 pub mod coalescing;
-pub mod distribute_multiplication;
\ No newline at end of file
+pub mod collect;
+pub mod distribute_multiplication;
+pub mod exact_values;
+pub mod expand_power;
+pub mod exponents;
+pub mod imaginary;
+pub mod like_terms;
+pub mod names;
+pub mod negation;
+pub mod trig;
@@ -0,0 +1,28 @@
+// the names the passes are shown with in the steps, the simplifier runs them under these and
+// Step::new describes the rewrites by them
+pub const COALESCE_MULTIPLICATION: &str = "coalesce multiplication";
+pub const COALESCE_ADDITION: &str = "coalesce addition";
+pub const NORMALIZE_NEGATION: &str = "normalize negation";
+pub const CANCEL_DIVISION: &str = "cancel division";
+pub const COMBINE_LIKE_FACTORS: &str = "combine like factors";
+pub const COMBINE_LIKE_TERMS: &str = "combine like terms";
+pub const POWER_OF_POWER: &str = "power of power";
+pub const POWER_OF_PRODUCT: &str = "power of product";
+pub const POWER_OF_QUOTIENT: &str = "power of quotient";
+pub const ROOT_OF_POWER: &str = "root of power";
+pub const IDENTITY_EXPONENTS: &str = "identity exponents";
+pub const NEGATIVE_EXPONENTS: &str = "negative exponents";
+pub const RADICALS: &str = "radicals";
+pub const EXACT_VALUES: &str = "exact values";
+pub const IMAGINARY_POWERS: &str = "imaginary powers";
+pub const EXPAND_POWER: &str = "expand power";
+pub const DISTRIBUTE: &str = "distribute";
+pub const FACTOR: &str = "factor";
+pub const COLLECT: &str = "collect";
+pub const RECIPROCAL_TRIG: &str = "reciprocal trig";
+pub const RECIPROCAL_HYPERBOLIC: &str = "reciprocal hyperbolic";
+pub const TRIG_PARITY: &str = "trig parity";
+pub const PYTHAGOREAN_IDENTITY: &str = "pythagorean identity";
+pub const HYPERBOLIC_IDENTITY: &str = "hyperbolic identity";
+pub const HYPERBOLIC_FORM: &str = "hyperbolic form";
+pub const EXPONENTIAL_FORM: &str = "exponential form";
@@ -0,0 +1,107 @@
+use crate::expression::{Addition, Expression, Multiplication, Negation};
+
+pub fn normalize_negation(expression: Expression) -> (Expression, bool) {
+    match expression {
+        Expression::Negation(negation) => match *negation.term {
+            // --x -> x
+            Expression::Negation(inner) => (*inner.term, false),
+            // -(2) -> -2
+            Expression::Number(n) => (Expression::Number(-n), false),
+            // -(2 * x) -> -2 * x
+            Expression::Multiplication(multiplication) => {
+                match negate_coefficient(multiplication) {
+                    Ok(multiplication) => (Expression::Multiplication(multiplication), false),
+                    Err(multiplication) => {
+                        (negate(Expression::Multiplication(multiplication)), true)
+                    }
+                }
+            }
+            term => (negate(term), true),
+        },
+        Expression::Multiplication(multiplication) => {
+            // a * -b * -c -> a * b * c and a * -b -> -(a * b)
+            // the sign is carried by a leading numeric coefficient if there is one, x * -2 -> -2 * x
+            let mut negative = false;
+            let mut changed = false;
+            let terms = multiplication
+                .terms
+                .into_iter()
+                .enumerate()
+                .map(|(i, term)| match *term {
+                    Expression::Negation(negation) => {
+                        negative = !negative;
+                        changed = true;
+                        negation.term
+                    }
+                    Expression::Number(n) if n < 0.0 && i > 0 => {
+                        negative = !negative;
+                        changed = true;
+                        Box::new(Expression::Number(-n))
+                    }
+                    _ => term,
+                })
+                .collect();
+            let multiplication = Multiplication { terms };
+            if !negative {
+                return (Expression::Multiplication(multiplication), !changed);
+            }
+            match negate_coefficient(multiplication) {
+                Ok(multiplication) => (Expression::Multiplication(multiplication), false),
+                Err(multiplication) => (negate(Expression::Multiplication(multiplication)), false),
+            }
+        }
+        Expression::Addition(addition) => {
+            // a + -(b + c) -> a + -b + -c
+            let mut simplified = true;
+            let terms = addition
+                .terms
+                .into_iter()
+                .flat_map(|term| match *term {
+                    Expression::Negation(negation) => match *negation.term {
+                        Expression::Addition(inner) => {
+                            simplified = false;
+                            inner
+                                .terms
+                                .into_iter()
+                                .map(|inner_term| Box::new(negate(*inner_term)))
+                                .collect()
+                        }
+                        inner => vec![Box::new(negate(inner))],
+                    },
+                    _ => vec![term],
+                })
+                .collect();
+            (Expression::Addition(Addition { terms }), simplified)
+        }
+        _ => (expression, true),
+    }
+}
+
+fn negate(expression: Expression) -> Expression {
+    Expression::Negation(Negation {
+        term: Box::new(expression),
+    })
+}
+
+// negates the first numeric term of a product and moves it to the front,
+// gives the product back unchanged if it has no numeric term
+fn negate_coefficient(
+    mut multiplication: Multiplication,
+) -> Result<Multiplication, Multiplication> {
+    let index = multiplication
+        .terms
+        .iter()
+        .position(|term| matches!(**term, Expression::Number(_)));
+    match index {
+        Some(index) => {
+            let coefficient = multiplication.terms.remove(index);
+            if let Expression::Number(n) = *coefficient {
+                multiplication
+                    .terms
+                    .insert(0, Box::new(Expression::Number(-n)));
+            }
+            Ok(multiplication)
+        }
+        None => Err(multiplication),
+    }
+}
@@ -0,0 +1,572 @@
+use std::f64::consts::{E, PI};
+
+use crate::expression::{
+    deep_copy, Addition, Division, Exponentiation, Expression, Function, Multiplication, Negation,
+    Sqrt,
+};
+
+use super::{
+    exponents::power,
+    like_terms::{split_coefficient, with_coefficient},
+};
+
+pub fn reciprocal_trig(expression: Expression) -> (Expression, bool) {
+    match expression {
+        // tan(x) -> sin(x) / cos(x)
+        Expression::Function(Function::Tan(arg)) => (
+            divide(
+                Function::Sin(Box::new(deep_copy(&arg))),
+                Expression::Function(Function::Cos(arg)),
+            ),
+            false,
+        ),
+        // cot(x) -> cos(x) / sin(x)
+        Expression::Function(Function::Cot(arg)) => (
+            divide(
+                Function::Cos(Box::new(deep_copy(&arg))),
+                Expression::Function(Function::Sin(arg)),
+            ),
+            false,
+        ),
+        // sec(x) -> 1 / cos(x)
+        Expression::Function(Function::Sec(arg)) => (
+            Expression::Division(Division {
+                numerator: Box::new(Expression::Number(1.0)),
+                denominator: Box::new(Expression::Function(Function::Cos(arg))),
+            }),
+            false,
+        ),
+        // csc(x) -> 1 / sin(x)
+        Expression::Function(Function::Csc(arg)) => (
+            Expression::Division(Division {
+                numerator: Box::new(Expression::Number(1.0)),
+                denominator: Box::new(Expression::Function(Function::Sin(arg))),
+            }),
+            false,
+        ),
+        _ => (expression, true),
+    }
+}
+
+pub fn trig_parity(expression: Expression) -> (Expression, bool) {
+    match expression {
+        Expression::Function(function) => match function {
+            // sin(-x) -> -sin(x), the same for every other odd function
+            Function::Sin(arg) if is_negated(&arg) => {
+                (negate(Function::Sin(Box::new(strip_negation(*arg)))), false)
+            }
+            Function::Tan(arg) if is_negated(&arg) => {
+                (negate(Function::Tan(Box::new(strip_negation(*arg)))), false)
+            }
+            Function::Csc(arg) if is_negated(&arg) => {
+                (negate(Function::Csc(Box::new(strip_negation(*arg)))), false)
+            }
+            Function::Cot(arg) if is_negated(&arg) => {
+                (negate(Function::Cot(Box::new(strip_negation(*arg)))), false)
+            }
+            Function::Sinh(arg) if is_negated(&arg) => (
+                negate(Function::Sinh(Box::new(strip_negation(*arg)))),
+                false,
+            ),
+            Function::Tanh(arg) if is_negated(&arg) => (
+                negate(Function::Tanh(Box::new(strip_negation(*arg)))),
+                false,
+            ),
+            Function::Csch(arg) if is_negated(&arg) => (
+                negate(Function::Csch(Box::new(strip_negation(*arg)))),
+                false,
+            ),
+            Function::Coth(arg) if is_negated(&arg) => (
+                negate(Function::Coth(Box::new(strip_negation(*arg)))),
+                false,
+            ),
+            // cos(-x) -> cos(x), the same for sec, cosh and sech
+            Function::Cos(arg) if is_negated(&arg) => (
+                Expression::Function(Function::Cos(Box::new(strip_negation(*arg)))),
+                false,
+            ),
+            Function::Sec(arg) if is_negated(&arg) => (
+                Expression::Function(Function::Sec(Box::new(strip_negation(*arg)))),
+                false,
+            ),
+            Function::Cosh(arg) if is_negated(&arg) => (
+                Expression::Function(Function::Cosh(Box::new(strip_negation(*arg)))),
+                false,
+            ),
+            Function::Sech(arg) if is_negated(&arg) => (
+                Expression::Function(Function::Sech(Box::new(strip_negation(*arg)))),
+                false,
+            ),
+            function => (Expression::Function(function), true),
+        },
+        _ => (expression, true),
+    }
+}
+
+pub fn pythagorean_identity(expression: Expression) -> (Expression, bool) {
+    // a * sin(x)^2 + a * cos(x)^2 -> a
+    combine_squares(expression, "sin", "cos", 1.0)
+}
+
+pub fn hyperbolic_identity(expression: Expression) -> (Expression, bool) {
+    // a * cosh(x)^2 - a * sinh(x)^2 -> a
+    combine_squares(expression, "cosh", "sinh", -1.0)
+}
+
+// a * first(x)^2 + sign * a * second(x)^2 -> a
+fn combine_squares(
+    expression: Expression,
+    first: &str,
+    second: &str,
+    sign: f64,
+) -> (Expression, bool) {
+    match expression {
+        Expression::Addition(addition) => {
+            let squares: Vec<Option<SquaredFunction>> = addition
+                .terms
+                .iter()
+                .map(|term| split_square(term))
+                .collect();
+            for (i, square) in squares.iter().enumerate() {
+                let Some(square) = square.as_ref().filter(|square| square.function == first) else {
+                    continue;
+                };
+                let partner = squares.iter().position(|other| {
+                    other.as_ref().is_some_and(|other| {
+                        other.function == second
+                            && other.coefficient == sign * square.coefficient
+                            && other.rest == square.rest
+                            && other.arg == square.arg
+                    })
+                });
+                if let Some(j) = partner {
+                    let (coefficient, rest) = (square.coefficient, &square.rest);
+                    let replacement = match rest.len() {
+                        0 => Expression::Number(coefficient),
+                        1 => with_coefficient(coefficient, deep_copy(&rest[0])),
+                        _ => with_coefficient(
+                            coefficient,
+                            Expression::Multiplication(Multiplication {
+                                terms: rest.iter().map(|e| Box::new(deep_copy(e))).collect(),
+                            }),
+                        ),
+                    };
+                    let mut terms: Vec<Box<Expression>> = addition
+                        .terms
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(k, _)| *k != i && *k != j)
+                        .map(|(_, term)| term)
+                        .collect();
+                    terms.push(Box::new(replacement));
+                    return (Expression::Addition(Addition { terms }), false);
+                }
+            }
+            (Expression::Addition(addition), true)
+        }
+        _ => (expression, true),
+    }
+}
+
+// a term of the form c * ... * f(x)^2
+struct SquaredFunction {
+    coefficient: f64,
+    // the other factors
+    rest: Vec<Expression>,
+    function: &'static str,
+    arg: Expression,
+}
+
+fn split_square(term: &Expression) -> Option<SquaredFunction> {
+    let (coefficient, rest) = split_coefficient(term);
+    let factors = match rest? {
+        Expression::Multiplication(multiplication) => {
+            multiplication.terms.into_iter().map(|t| *t).collect()
+        }
+        factor => vec![factor],
+    };
+    let index = factors
+        .iter()
+        .position(|factor| squared_function(factor).is_some())?;
+    let (function, arg) = squared_function(&factors[index])?;
+    let rest = factors
+        .iter()
+        .enumerate()
+        .filter(|(k, _)| *k != index)
+        .map(|(_, factor)| deep_copy(factor))
+        .collect();
+    Some(SquaredFunction {
+        coefficient,
+        rest,
+        function,
+        arg,
+    })
+}
+
+fn squared_function(factor: &Expression) -> Option<(&'static str, Expression)> {
+    match factor {
+        Expression::Exponentiation(exponentiation)
+            if *exponentiation.exponent == Expression::Number(2.0) =>
+        {
+            match &*exponentiation.base {
+                Expression::Function(function) => {
+                    Some((function.name(), deep_copy(function.arg())))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+pub fn reciprocal_hyperbolic(expression: Expression) -> (Expression, bool) {
+    match expression {
+        // tanh(x) -> sinh(x) / cosh(x)
+        Expression::Function(Function::Tanh(arg)) => (
+            divide(
+                Function::Sinh(Box::new(deep_copy(&arg))),
+                Expression::Function(Function::Cosh(arg)),
+            ),
+            false,
+        ),
+        // coth(x) -> cosh(x) / sinh(x)
+        Expression::Function(Function::Coth(arg)) => (
+            divide(
+                Function::Cosh(Box::new(deep_copy(&arg))),
+                Expression::Function(Function::Sinh(arg)),
+            ),
+            false,
+        ),
+        // sech(x) -> 1 / cosh(x)
+        Expression::Function(Function::Sech(arg)) => (
+            Expression::Division(Division {
+                numerator: Box::new(Expression::Number(1.0)),
+                denominator: Box::new(Expression::Function(Function::Cosh(arg))),
+            }),
+            false,
+        ),
+        // csch(x) -> 1 / sinh(x)
+        Expression::Function(Function::Csch(arg)) => (
+            Expression::Division(Division {
+                numerator: Box::new(Expression::Number(1.0)),
+                denominator: Box::new(Expression::Function(Function::Sinh(arg))),
+            }),
+            false,
+        ),
+        _ => (expression, true),
+    }
+}
+
+// writes hyperbolic functions with their definitions, sinh(x) -> (e^x - e^-x) / 2
+pub fn exponential_form(expression: Expression) -> (Expression, bool) {
+    let Expression::Function(function) = expression else {
+        return (expression, true);
+    };
+    let (numerator, denominator) = match &function {
+        // written as e^x / 2 - e^-x / 2 so the halves combine with other terms
+        Function::Sinh(arg) => return (halves(difference(arg)), false),
+        Function::Cosh(arg) => return (halves(sum(arg)), false),
+        Function::Tanh(arg) => (difference(arg), sum(arg)),
+        Function::Csch(arg) => (Expression::Number(2.0), difference(arg)),
+        Function::Sech(arg) => (Expression::Number(2.0), sum(arg)),
+        Function::Coth(arg) => (sum(arg), difference(arg)),
+        _ => return (Expression::Function(function), true),
+    };
+    (
+        Expression::Division(Division {
+            numerator: Box::new(numerator),
+            denominator: Box::new(denominator),
+        }),
+        false,
+    )
+}
+
+// e^x + e^-x
+fn sum(arg: &Expression) -> Expression {
+    Expression::Addition(Addition {
+        terms: vec![Box::new(exp(deep_copy(arg))), Box::new(exp(negated(arg)))],
+    })
+}
+
+// e^x - e^-x
+fn difference(arg: &Expression) -> Expression {
+    Expression::Addition(Addition {
+        terms: vec![
+            Box::new(exp(deep_copy(arg))),
+            Box::new(Expression::Negation(Negation {
+                term: Box::new(exp(negated(arg))),
+            })),
+        ],
+    })
+}
+
+// (a + b) -> a / 2 + b / 2
+fn halves(expression: Expression) -> Expression {
+    let Expression::Addition(addition) = expression else {
+        return expression;
+    };
+    let half = |term: Expression| {
+        Expression::Division(Division {
+            numerator: Box::new(term),
+            denominator: Box::new(Expression::Number(2.0)),
+        })
+    };
+    let terms = addition
+        .terms
+        .into_iter()
+        .map(|term| match *term {
+            Expression::Negation(negation) => Expression::Negation(Negation {
+                term: Box::new(half(*negation.term)),
+            }),
+            term => half(term),
+        })
+        .map(Box::new)
+        .collect();
+    Expression::Addition(Addition { terms })
+}
+
+fn exp(exponent: Expression) -> Expression {
+    Expression::Exponentiation(Exponentiation {
+        base: Box::new(Expression::Number(E)),
+        exponent: Box::new(exponent),
+    })
+}
+
+// the reverse of exponential_form for sums, a * e^x + a * e^-x -> 2 * a * cosh(x) and
+// a * e^x - a * e^-x -> 2 * a * sinh(x)
+pub fn hyperbolic_form(expression: Expression) -> (Expression, bool) {
+    let addition = match expression {
+        Expression::Addition(addition) => addition,
+        // the sum in (e^x + e^-x) / 2 was rewritten first, 2 * cosh(x) / 2 -> cosh(x)
+        Expression::Division(division) => {
+            let (coefficient, rest) = split_coefficient(&division.numerator);
+            return match (rest, *division.denominator) {
+                (
+                    Some(Expression::Function(function @ (Function::Sinh(_) | Function::Cosh(_)))),
+                    Expression::Number(d),
+                ) if d != 0.0 => (
+                    with_coefficient(coefficient / d, Expression::Function(function)),
+                    false,
+                ),
+                (_, denominator) => (
+                    Expression::Division(Division {
+                        numerator: division.numerator,
+                        denominator: Box::new(denominator),
+                    }),
+                    true,
+                ),
+            };
+        }
+        expression => return (expression, true),
+    };
+    let exponentials: Vec<Option<(f64, Expression)>> = addition
+        .terms
+        .iter()
+        .map(|term| split_exponential(term))
+        .collect();
+    for (i, exponential) in exponentials.iter().enumerate() {
+        let Some((coefficient, exponent)) = exponential
+            .as_ref()
+            .filter(|(_, exponent)| !is_negated(exponent))
+        else {
+            continue;
+        };
+        let opposite = negated(exponent);
+        let partner = exponentials.iter().position(|other| {
+            other
+                .as_ref()
+                .is_some_and(|(other_coefficient, other_exponent)| {
+                    (*other_exponent == opposite || negated(other_exponent) == *exponent)
+                        && other_coefficient.abs() == coefficient.abs()
+                })
+        });
+        if let Some(j) = partner {
+            let function = if exponentials[j]
+                .as_ref()
+                .is_some_and(|(other, _)| *other == *coefficient)
+            {
+                Function::Cosh(Box::new(deep_copy(exponent)))
+            } else {
+                Function::Sinh(Box::new(deep_copy(exponent)))
+            };
+            let replacement = with_coefficient(2.0 * coefficient, Expression::Function(function));
+            let mut terms: Vec<Box<Expression>> = addition
+                .terms
+                .into_iter()
+                .enumerate()
+                .filter(|(k, _)| *k != i && *k != j)
+                .map(|(_, term)| term)
+                .collect();
+            terms.push(Box::new(replacement));
+            return (Expression::Addition(Addition { terms }), false);
+        }
+    }
+    (Expression::Addition(addition), true)
+}
+
+// a term of the form c * e^x as c and x, e^-x is often written 1 / e^x
+fn split_exponential(term: &Expression) -> Option<(f64, Expression)> {
+    let (coefficient, rest) = split_coefficient(term);
+    match rest? {
+        // c * e^x / d
+        Expression::Division(division)
+            if matches!(*division.denominator, Expression::Number(_)) =>
+        {
+            let Expression::Number(d) = *division.denominator else {
+                unreachable!()
+            };
+            split_exponential(&division.numerator)
+                .map(|(c, exponent)| (coefficient * c / d, exponent))
+        }
+        Expression::Exponentiation(exponentiation)
+            if *exponentiation.base == Expression::Number(E) =>
+        {
+            Some((coefficient, *exponentiation.exponent))
+        }
+        Expression::Division(division) => match (*division.numerator, *division.denominator) {
+            (Expression::Number(n), Expression::Exponentiation(exponentiation))
+                if *exponentiation.base == Expression::Number(E) =>
+            {
+                Some((coefficient * n, negated(&exponentiation.exponent)))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// the derivative of a function with respect to its argument in radians, d/dx sinh(x) = cosh(x)
+pub fn derivative(function: &Function) -> Expression {
+    let x = function.arg();
+    let f = |function: fn(Box<Expression>) -> Function| {
+        Expression::Function(function(Box::new(deep_copy(x))))
+    };
+    let square = |expression: Expression| power(expression, Expression::Number(2.0));
+    let product = |a: Expression, b: Expression| {
+        Expression::Multiplication(Multiplication {
+            terms: vec![Box::new(a), Box::new(b)],
+        })
+    };
+    let negative = |expression: Expression| {
+        Expression::Negation(Negation {
+            term: Box::new(expression),
+        })
+    };
+    let reciprocal = |expression: Expression| {
+        Expression::Division(Division {
+            numerator: Box::new(Expression::Number(1.0)),
+            denominator: Box::new(expression),
+        })
+    };
+    // c + sign * x^2
+    let one_plus_square = |c: f64, sign: f64| {
+        Expression::Addition(Addition {
+            terms: vec![
+                Box::new(Expression::Number(c)),
+                Box::new(with_coefficient(sign, square(deep_copy(x)))),
+            ],
+        })
+    };
+    let root = |expression: Expression| {
+        Expression::Sqrt(Sqrt {
+            arg: Box::new(expression),
+        })
+    };
+    // x^2 * sqrt(1 + sign / x^2), which is |x| * sqrt(x^2 + sign) without needing |x|
+    let reciprocal_root = |sign: f64| {
+        product(
+            square(deep_copy(x)),
+            root(Expression::Addition(Addition {
+                terms: vec![
+                    Box::new(Expression::Number(1.0)),
+                    Box::new(with_coefficient(sign, reciprocal(square(deep_copy(x))))),
+                ],
+            })),
+        )
+    };
+    match function {
+        Function::Sin(_) => f(Function::Cos),
+        Function::Cos(_) => negative(f(Function::Sin)),
+        Function::Tan(_) => square(f(Function::Sec)),
+        Function::Csc(_) => negative(product(f(Function::Csc), f(Function::Cot))),
+        Function::Sec(_) => product(f(Function::Sec), f(Function::Tan)),
+        Function::Cot(_) => negative(square(f(Function::Csc))),
+        Function::Arcsin(_) => reciprocal(root(one_plus_square(1.0, -1.0))),
+        Function::Arccos(_) => negative(reciprocal(root(one_plus_square(1.0, -1.0)))),
+        Function::Arctan(_) => reciprocal(one_plus_square(1.0, 1.0)),
+        Function::Arccsc(_) => negative(reciprocal(reciprocal_root(-1.0))),
+        Function::Arcsec(_) => reciprocal(reciprocal_root(-1.0)),
+        Function::Arccot(_) => negative(reciprocal(one_plus_square(1.0, 1.0))),
+        Function::Sinh(_) => f(Function::Cosh),
+        Function::Cosh(_) => f(Function::Sinh),
+        Function::Tanh(_) => square(f(Function::Sech)),
+        Function::Csch(_) => negative(product(f(Function::Csch), f(Function::Coth))),
+        Function::Sech(_) => negative(product(f(Function::Sech), f(Function::Tanh))),
+        Function::Coth(_) => negative(square(f(Function::Csch))),
+        Function::Arcsinh(_) => reciprocal(root(one_plus_square(1.0, 1.0))),
+        Function::Arccosh(_) => reciprocal(root(one_plus_square(-1.0, 1.0))),
+        Function::Arctanh(_) | Function::Arccoth(_) => reciprocal(one_plus_square(1.0, -1.0)),
+        Function::Arccsch(_) => negative(reciprocal(reciprocal_root(1.0))),
+        // only defined for 0 < x <= 1, where x * sqrt(1 - x^2) is x^2 * sqrt(1 / x^2 - 1)
+        Function::Arcsech(_) => negative(reciprocal(product(
+            deep_copy(x),
+            root(one_plus_square(1.0, -1.0)),
+        ))),
+        // x° is x * pi / 180 radians
+        Function::Degrees(_) => Expression::Number(PI / 180.0),
+    }
+}
+
+fn divide(numerator: Function, denominator: Expression) -> Expression {
+    Expression::Division(Division {
+        numerator: Box::new(Expression::Function(numerator)),
+        denominator: Box::new(denominator),
+    })
+}
+
+fn negate(function: Function) -> Expression {
+    Expression::Negation(Negation {
+        term: Box::new(Expression::Function(function)),
+    })
+}
+
+// -x for x and x for -x
+fn negated(expression: &Expression) -> Expression {
+    if is_negated(expression) {
+        strip_negation(deep_copy(expression))
+    } else {
+        Expression::Negation(Negation {
+            term: Box::new(deep_copy(expression)),
+        })
+    }
+}
+
+// -x or -2 * x
+fn is_negated(expression: &Expression) -> bool {
+    match expression {
+        Expression::Negation(_) => true,
+        Expression::Number(n) => *n < 0.0,
+        Expression::Multiplication(multiplication) => {
+            matches!(multiplication.terms.first().map(|t| &**t), Some(Expression::Number(n)) if *n < 0.0)
+        }
+        _ => false,
+    }
+}
+
+fn strip_negation(expression: Expression) -> Expression {
+    match expression {
+        Expression::Negation(negation) => *negation.term,
+        Expression::Number(n) => Expression::Number(-n),
+        Expression::Multiplication(mut multiplication) => {
+            if let Expression::Number(n) = *multiplication.terms[0] {
+                if n == -1.0 && multiplication.terms.len() > 1 {
+                    multiplication.terms.remove(0);
+                } else {
+                    *multiplication.terms[0] = Expression::Number(-n);
+                }
+            }
+            Expression::Multiplication(multiplication)
+        }
+        expression => expression,
+    }
+}
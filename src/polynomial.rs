@@ -0,0 +1,342 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    expression::{Addition, Division, Exponentiation, Expression, Multiplication, Negation},
+    rational::{gcd, Rational},
+    substitution::free_variables,
+};
+
+// a polynomial in several variables with exact rational coefficients
+// each term is keyed by the exponent of every variable, in the same order as `variables`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial {
+    pub variables: Vec<String>,
+    pub terms: BTreeMap<Vec<u32>, Rational>,
+}
+
+// the largest exponent that will be expanded when converting from an expression
+const MAX_EXPONENT: u32 = 64;
+
+impl Polynomial {
+    pub fn constant(variables: &[String], value: Rational) -> Polynomial {
+        let mut terms = BTreeMap::new();
+        if !value.is_zero() {
+            terms.insert(vec![0; variables.len()], value);
+        }
+        Polynomial {
+            variables: variables.to_vec(),
+            terms,
+        }
+    }
+
+    pub fn variable(variables: &[String], index: usize) -> Polynomial {
+        let mut exponents = vec![0; variables.len()];
+        exponents[index] = 1;
+        Polynomial {
+            variables: variables.to_vec(),
+            terms: BTreeMap::from([(exponents, Rational::one())]),
+        }
+    }
+
+    // converts an expression made of numbers, variables, +, -, *, division by constants
+    // and non-negative integer powers, returns None for anything else or if a coefficient overflows
+    pub fn from_expression(expression: &Expression) -> Option<Polynomial> {
+        let variables: Vec<String> = free_variables(expression).into_iter().collect();
+        convert(expression, &variables)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.terms
+            .keys()
+            .all(|exponents| exponents.iter().all(|e| *e == 0))
+    }
+
+    // the index of the only variable that appears, if exactly one does
+    pub fn univariate_index(&self) -> Option<usize> {
+        let mut used = (0..self.variables.len())
+            .filter(|i| self.terms.keys().any(|exponents| exponents[*i] > 0));
+        let index = used.next()?;
+        match used.next() {
+            Some(_) => None,
+            None => Some(index),
+        }
+    }
+
+    // the arithmetic is checked, None when a coefficient doesn't fit, see Rational
+    pub fn add(&self, other: &Polynomial) -> Option<Polynomial> {
+        let mut terms = self.terms.clone();
+        for (exponents, coefficient) in &other.terms {
+            let sum = terms
+                .get(exponents)
+                .unwrap_or(&Rational::zero())
+                .checked_add(*coefficient)?;
+            if sum.is_zero() {
+                terms.remove(exponents);
+            } else {
+                terms.insert(exponents.clone(), sum);
+            }
+        }
+        Some(Polynomial {
+            variables: self.variables.clone(),
+            terms,
+        })
+    }
+
+    pub fn mul(&self, other: &Polynomial) -> Option<Polynomial> {
+        let mut product = Polynomial::constant(&self.variables, Rational::zero());
+        for (a_exponents, a) in &self.terms {
+            for (b_exponents, b) in &other.terms {
+                let exponents = a_exponents
+                    .iter()
+                    .zip(b_exponents)
+                    .map(|(x, y)| x + y)
+                    .collect();
+                product = product.add(&Polynomial {
+                    variables: self.variables.clone(),
+                    terms: BTreeMap::from([(exponents, a.checked_mul(*b)?)]),
+                })?;
+            }
+        }
+        Some(product)
+    }
+
+    pub fn scale(&self, factor: Rational) -> Option<Polynomial> {
+        if factor.is_zero() {
+            return Some(Polynomial::constant(&self.variables, factor));
+        }
+        Some(Polynomial {
+            variables: self.variables.clone(),
+            terms: self
+                .terms
+                .iter()
+                .map(|(exponents, coefficient)| {
+                    Some((exponents.clone(), coefficient.checked_mul(factor)?))
+                })
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    pub fn pow(&self, exponent: u32) -> Option<Polynomial> {
+        let mut result = Polynomial::constant(&self.variables, Rational::one());
+        for _ in 0..exponent {
+            result = result.mul(self)?;
+        }
+        Some(result)
+    }
+
+    // terms ordered by total degree and then lexicographically, highest first
+    pub fn ordered_terms(&self) -> Vec<(&Vec<u32>, &Rational)> {
+        let mut terms: Vec<_> = self.terms.iter().collect();
+        terms.sort_by(|(a, _), (b, _)| {
+            let degree_a: u32 = a.iter().sum();
+            let degree_b: u32 = b.iter().sum();
+            degree_b.cmp(&degree_a).then_with(|| b.cmp(a))
+        });
+        terms
+    }
+
+    pub fn leading_coefficient(&self) -> Rational {
+        self.ordered_terms()
+            .first()
+            .map(|(_, coefficient)| **coefficient)
+            .unwrap_or(Rational::zero())
+    }
+
+    // splits the polynomial into a rational content and a primitive part,
+    // the primitive part has coprime integer coefficients and a positive leading coefficient
+    pub fn content_and_primitive(&self) -> Option<(Rational, Polynomial)> {
+        let (numerators, denominators) =
+            self.terms
+                .values()
+                .try_fold((0, 1), |(n, d): (i128, i128), c| {
+                    Some((
+                        gcd(n, c.numerator()),
+                        (d / gcd(d, c.denominator())).checked_mul(c.denominator())?,
+                    ))
+                })?;
+        if numerators == 0 {
+            return Some((Rational::zero(), self.clone()));
+        }
+        let content = Rational::new(
+            numerators * self.leading_coefficient().signum(),
+            denominators,
+        );
+        Some((content, self.scale(Rational::one().checked_div(content)?)?))
+    }
+
+    // splits off the largest monomial that divides every term, x^3*y + x^2*y^2 -> (x^2*y, x + y)
+    pub fn split_monomial(&self) -> (Vec<u32>, Polynomial) {
+        let monomial: Vec<u32> = (0..self.variables.len())
+            .map(|i| self.terms.keys().map(|e| e[i]).min().unwrap_or(0))
+            .collect();
+        let terms = self
+            .terms
+            .iter()
+            .map(|(exponents, coefficient)| {
+                (
+                    exponents
+                        .iter()
+                        .zip(&monomial)
+                        .map(|(e, m)| e - m)
+                        .collect(),
+                    *coefficient,
+                )
+            })
+            .collect();
+        (
+            monomial,
+            Polynomial {
+                variables: self.variables.clone(),
+                terms,
+            },
+        )
+    }
+
+    // the coefficients of a polynomial in only the variable at `index`, lowest degree first
+    pub fn to_dense(&self, index: usize) -> Vec<Rational> {
+        let degree = self.terms.keys().map(|e| e[index]).max().unwrap_or(0) as usize;
+        let mut coefficients = vec![Rational::zero(); degree + 1];
+        for (exponents, coefficient) in &self.terms {
+            coefficients[exponents[index] as usize] = *coefficient;
+        }
+        coefficients
+    }
+
+    pub fn from_dense(variables: &[String], index: usize, coefficients: &[Rational]) -> Polynomial {
+        let terms = coefficients
+            .iter()
+            .enumerate()
+            .filter(|(_, coefficient)| !coefficient.is_zero())
+            .map(|(degree, coefficient)| {
+                let mut exponents = vec![0; variables.len()];
+                exponents[index] = degree as u32;
+                (exponents, *coefficient)
+            })
+            .collect();
+        Polynomial {
+            variables: variables.to_vec(),
+            terms,
+        }
+    }
+
+    pub fn to_expression(&self) -> Expression {
+        let terms: Vec<Box<Expression>> = self
+            .ordered_terms()
+            .into_iter()
+            .map(|(exponents, coefficient)| {
+                Box::new(term_to_expression(&self.variables, exponents, *coefficient))
+            })
+            .collect();
+        match terms.len() {
+            0 => Expression::Number(0.0),
+            1 => *terms.into_iter().next().unwrap(),
+            _ => Expression::Addition(Addition { terms }),
+        }
+    }
+}
+
+#[allow(clippy::vec_box)]
+pub fn monomial_to_expression(variables: &[String], exponents: &[u32]) -> Vec<Box<Expression>> {
+    variables
+        .iter()
+        .zip(exponents)
+        .filter(|(_, exponent)| **exponent > 0)
+        .map(|(variable, exponent)| {
+            let variable = Expression::Variable(variable.clone());
+            Box::new(if *exponent == 1 {
+                variable
+            } else {
+                Expression::Exponentiation(Exponentiation {
+                    base: Box::new(variable),
+                    exponent: Box::new(Expression::Number(*exponent as f64)),
+                })
+            })
+        })
+        .collect()
+}
+
+pub fn rational_to_expression(value: Rational) -> Expression {
+    let magnitude = if value.is_integer() {
+        Expression::Number(value.numerator().abs() as f64)
+    } else {
+        Expression::Division(Division {
+            numerator: Box::new(Expression::Number(value.numerator().abs() as f64)),
+            denominator: Box::new(Expression::Number(value.denominator() as f64)),
+        })
+    };
+    if value.signum() < 0 {
+        Expression::Negation(Negation {
+            term: Box::new(magnitude),
+        })
+    } else {
+        magnitude
+    }
+}
+
+fn term_to_expression(
+    variables: &[String],
+    exponents: &[u32],
+    coefficient: Rational,
+) -> Expression {
+    let mut factors = monomial_to_expression(variables, exponents);
+    if factors.is_empty() || !coefficient.abs().is_one() {
+        factors.insert(0, Box::new(rational_to_expression(coefficient.abs())));
+    }
+    let magnitude = if factors.len() == 1 {
+        *factors.pop().unwrap()
+    } else {
+        Expression::Multiplication(Multiplication { terms: factors })
+    };
+    if coefficient.signum() < 0 {
+        Expression::Negation(Negation {
+            term: Box::new(magnitude),
+        })
+    } else {
+        magnitude
+    }
+}
+
+fn convert(expression: &Expression, variables: &[String]) -> Option<Polynomial> {
+    match expression {
+        Expression::Number(n) => Some(Polynomial::constant(variables, Rational::from_f64(*n)?)),
+        Expression::Variable(v) => {
+            let index = variables.iter().position(|name| name == v)?;
+            Some(Polynomial::variable(variables, index))
+        }
+        Expression::Addition(addition) => addition.terms.iter().try_fold(
+            Polynomial::constant(variables, Rational::zero()),
+            |sum, term| sum.add(&convert(term, variables)?),
+        ),
+        Expression::Multiplication(multiplication) => multiplication.terms.iter().try_fold(
+            Polynomial::constant(variables, Rational::one()),
+            |product, term| product.mul(&convert(term, variables)?),
+        ),
+        Expression::Negation(negation) => {
+            convert(&negation.term, variables)?.scale(-Rational::one())
+        }
+        Expression::Division(division) => {
+            let denominator = convert(&division.denominator, variables)?;
+            if !denominator.is_constant() || denominator.is_zero() {
+                return None;
+            }
+            let numerator = convert(&division.numerator, variables)?;
+            numerator.scale(Rational::one().checked_div(denominator.leading_coefficient())?)
+        }
+        Expression::Exponentiation(exponentiation) => {
+            let exponent = match *exponentiation.exponent {
+                Expression::Number(n)
+                    if n >= 0.0 && n.fract() == 0.0 && n <= MAX_EXPONENT as f64 =>
+                {
+                    n as u32
+                }
+                _ => return None,
+            };
+            convert(&exponentiation.base, variables)?.pow(exponent)
+        }
+        Expression::Sqrt(_) | Expression::Function(_) => None,
+    }
+}
@@ -0,0 +1,177 @@
+use std::ops::Neg;
+
+// an exact fraction, always stored in lowest terms with a positive denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+pub fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Rational {
+    pub fn new(numerator: i128, denominator: i128) -> Rational {
+        assert!(denominator != 0, "Rational with a zero denominator");
+        let divisor = gcd(numerator, denominator).max(1) * denominator.signum();
+        Rational {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        }
+    }
+
+    pub fn from_integer(n: i128) -> Rational {
+        Rational {
+            numerator: n,
+            denominator: 1,
+        }
+    }
+
+    pub fn zero() -> Rational {
+        Rational::from_integer(0)
+    }
+
+    pub fn one() -> Rational {
+        Rational::from_integer(1)
+    }
+
+    // recovers an exact fraction from a float if it has a small denominator,
+    // 0.5 -> 1/2 but 0.1 -> 1/10 as well since the input was probably typed that way
+    pub fn from_f64(value: f64) -> Option<Rational> {
+        if !value.is_finite() || value.abs() >= 9007199254740992.0 {
+            return None;
+        }
+        if value.fract() == 0.0 {
+            return Some(Rational::from_integer(value as i128));
+        }
+        // continued fraction expansion until the approximation is exact to f64 precision
+        let (mut h0, mut h1) = (0i128, 1i128);
+        let (mut k0, mut k1) = (1i128, 0i128);
+        let mut x = value;
+        for _ in 0..40 {
+            let a = x.floor();
+            if a.abs() >= 9007199254740992.0 {
+                return None;
+            }
+            let a = a as i128;
+            (h0, h1) = (h1, a * h1 + h0);
+            (k0, k1) = (k1, a * k1 + k0);
+            if k1 > 1_000_000_000 {
+                return None;
+            }
+            if h1 as f64 / k1 as f64 == value {
+                return Some(Rational::new(h1, k1));
+            }
+            let remainder = x - a as f64;
+            if remainder == 0.0 {
+                break;
+            }
+            x = 1.0 / remainder;
+        }
+        None
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator == 0
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.numerator == 1 && self.denominator == 1
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == 1
+    }
+
+    pub fn abs(&self) -> Rational {
+        Rational {
+            numerator: self.numerator.abs(),
+            denominator: self.denominator,
+        }
+    }
+
+    pub fn signum(&self) -> i128 {
+        self.numerator.signum()
+    }
+
+    // the arithmetic is checked, None when a numerator or denominator doesn't fit in an i128
+    pub fn checked_add(self, other: Rational) -> Option<Rational> {
+        let divisor = gcd(self.denominator, other.denominator);
+        let numerator = self
+            .numerator
+            .checked_mul(other.denominator / divisor)?
+            .checked_add(other.numerator.checked_mul(self.denominator / divisor)?)?;
+        Rational::checked_new(
+            numerator,
+            (self.denominator / divisor).checked_mul(other.denominator)?,
+        )
+    }
+
+    pub fn checked_mul(self, other: Rational) -> Option<Rational> {
+        // cancel across before multiplying to keep the intermediate values small
+        let a = gcd(self.numerator, other.denominator).max(1);
+        let b = gcd(other.numerator, self.denominator).max(1);
+        Rational::checked_new(
+            (self.numerator / a).checked_mul(other.numerator / b)?,
+            (self.denominator / b).checked_mul(other.denominator / a)?,
+        )
+    }
+
+    pub fn checked_div(self, other: Rational) -> Option<Rational> {
+        if other.is_zero() {
+            return None;
+        }
+        self.checked_mul(Rational::new(other.denominator, other.numerator))
+    }
+
+    // i128::MIN has no positive counterpart, so it can't be normalized or negated
+    fn checked_new(numerator: i128, denominator: i128) -> Option<Rational> {
+        if numerator == i128::MIN || denominator == i128::MIN {
+            return None;
+        }
+        Some(Rational::new(numerator, denominator))
+    }
+
+    // the exact k-th root if there is one, for example (4/9, 2) -> 2/3
+    pub fn root(&self, k: u32) -> Option<Rational> {
+        if self.numerator < 0 && k % 2 == 0 {
+            return None;
+        }
+        let numerator = integer_root(self.numerator.abs(), k)? * self.numerator.signum();
+        let denominator = integer_root(self.denominator, k)?;
+        Some(Rational::new(numerator, denominator))
+    }
+}
+
+// the exact k-th root of a non-negative integer if there is one
+pub fn integer_root(n: i128, k: u32) -> Option<i128> {
+    if n < 2 {
+        return Some(n);
+    }
+    let guess = (n as f64).powf(1.0 / k as f64).round() as i128;
+    (guess.saturating_sub(1)..=guess + 1).find(|r| r.checked_pow(k) == Some(n))
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            numerator: -self.numerator,
+            denominator: self.denominator,
+        }
+    }
+}
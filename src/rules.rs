@@ -0,0 +1,501 @@
+use std::{fs, path::Path};
+
+use crate::{
+    angle::AngleMode,
+    assumptions::{Assumptions, Predicate},
+    expression::{
+        eval_in, Addition, Division, Exponentiation, Expression, Function, Multiplication,
+        Negation, Sqrt,
+    },
+    parser::{parse, tokenize},
+    substitution::{free_variables, substitute_with},
+};
+
+// the rules every simplification uses unless the caller picks its own
+const IDENTITIES: &str = include_str!("../rules/identities.rules");
+
+// a rewrite rule like `a*(b+c) -> a*b + a*c` or `(x^n)^(1/n) -> x where n is odd`
+// every variable in the pattern is a wildcard that matches any subexpression,
+// a variable that appears more than once has to match the same subexpression each time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    // the rule as it was written
+    pub text: String,
+    pub pattern: Expression,
+    pub replacement: Expression,
+    pub conditions: Vec<Condition>,
+}
+
+// a side condition on what a pattern variable matched, `n is integer`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub variable: String,
+    pub property: Property,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Property {
+    // a number or an expression made only of numbers
+    Constant,
+    Integer,
+    Even,
+    Odd,
+    Positive,
+    Negative,
+    Nonzero,
+    // a single variable
+    Variable,
+}
+
+// a named list of rules, tried in order at every node of the expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleSet {
+    pub name: String,
+    pub rules: Vec<Rule>,
+}
+
+// what each pattern variable matched so far
+type Bindings = Vec<(String, Expression)>;
+
+impl Rule {
+    pub fn parse(text: &str) -> Result<Rule, String> {
+        let (rule, conditions) = match text.split_once(" where ") {
+            Some((rule, conditions)) => (rule, Some(conditions)),
+            None => (text, None),
+        };
+        let (pattern, replacement) = rule
+            .split_once("->")
+            .ok_or_else(|| format!("Rule \"{}\" is missing a \"->\"", text.trim()))?;
+        let pattern = flatten(parse_expression(pattern)?);
+        let replacement = parse_expression(replacement)?;
+
+        let conditions = match conditions {
+            Some(conditions) => conditions
+                .split(',')
+                .flat_map(|condition| condition.split(" and "))
+                .map(Condition::parse)
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+
+        let pattern_variables = free_variables(&pattern);
+        let replacement_variables = free_variables(&replacement);
+        let condition_variables = conditions.iter().map(|condition| &condition.variable);
+        if let Some(unbound) = replacement_variables
+            .iter()
+            .chain(condition_variables)
+            .find(|variable| !pattern_variables.contains(*variable))
+        {
+            return Err(format!(
+                "Rule \"{}\" uses the variable {} which isn't in its pattern",
+                text.trim(),
+                unbound
+            ));
+        }
+
+        Ok(Rule {
+            text: text.trim().to_string(),
+            pattern,
+            replacement,
+            conditions,
+        })
+    }
+
+    // rewrites the expression if the pattern matches it and every condition holds,
+    // either for the numbers it matched or for every value the assumptions allow
+    // a sum or product pattern also matches some of the terms of a longer sum or product,
+    // sin(x)^2 + cos(x)^2 -> 1 rewrites y + sin(y)^2 + cos(y)^2 to y + 1
+    // numbers in a condition's value are worked out with trig functions in `angle_mode`'s unit
+    pub fn rewrite(
+        &self,
+        expression: &Expression,
+        assumptions: &Assumptions,
+        angle_mode: AngleMode,
+    ) -> Option<Expression> {
+        let rewritten = self.rewrite_with(
+            &self.pattern,
+            &self.replacement,
+            expression,
+            assumptions,
+            angle_mode,
+        );
+        if rewritten.is_some() {
+            return rewritten;
+        }
+        let (pattern, replacement) = self.with_rest()?;
+        self.rewrite_with(&pattern, &replacement, expression, assumptions, angle_mode)
+    }
+
+    // the pattern and replacement extended to match some of the terms of a longer sum or product,
+    // a + b -> c becomes a + b + rest -> rest + c, None unless the pattern is a sum or product
+    pub(crate) fn with_rest(&self) -> Option<(Expression, Expression)> {
+        // pattern variables are single letters so this name can't clash with one
+        let rest = Box::new(Expression::Variable(String::from("rest")));
+        match &self.pattern {
+            Expression::Addition(addition) => {
+                let mut terms = addition.terms.clone();
+                terms.push(rest.clone());
+                Some((
+                    Expression::Addition(Addition { terms }),
+                    Expression::Addition(Addition {
+                        terms: vec![rest, Box::new(self.replacement.clone())],
+                    }),
+                ))
+            }
+            Expression::Multiplication(multiplication) => {
+                let mut terms = multiplication.terms.clone();
+                terms.push(rest.clone());
+                Some((
+                    Expression::Multiplication(Multiplication { terms }),
+                    Expression::Multiplication(Multiplication {
+                        terms: vec![rest, Box::new(self.replacement.clone())],
+                    }),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn rewrite_with(
+        &self,
+        pattern: &Expression,
+        replacement: &Expression,
+        expression: &Expression,
+        assumptions: &Assumptions,
+        angle_mode: AngleMode,
+    ) -> Option<Expression> {
+        let mut rewritten = None;
+        matches(pattern, expression, &mut Vec::new(), &mut |bindings| {
+            if self.conditions.iter().all(|condition| {
+                bindings
+                    .iter()
+                    .find(|(variable, _)| *variable == condition.variable)
+                    .is_some_and(|(_, value)| condition.holds(value, assumptions, angle_mode))
+            }) {
+                rewritten = Some(substitute(replacement, bindings, angle_mode));
+                true
+            } else {
+                false
+            }
+        });
+        rewritten.filter(|rewritten| rewritten != expression)
+    }
+}
+
+impl Condition {
+    fn parse(text: &str) -> Result<Condition, String> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let property = match words.as_slice() {
+            [_, "is", property] => match *property {
+                "constant" | "number" => Property::Constant,
+                "integer" => Property::Integer,
+                "even" => Property::Even,
+                "odd" => Property::Odd,
+                "positive" => Property::Positive,
+                "negative" => Property::Negative,
+                "nonzero" => Property::Nonzero,
+                "variable" => Property::Variable,
+                property => {
+                    return Err(format!(
+                        "Unknown property \"{}\" in condition \"{}\"",
+                        property,
+                        text.trim()
+                    ))
+                }
+            },
+            _ => {
+                return Err(format!(
+                    "Conditions look like \"n is integer\", found \"{}\"",
+                    text.trim()
+                ))
+            }
+        };
+        Ok(Condition {
+            variable: words[0].to_lowercase(),
+            property,
+        })
+    }
+
+    // whether the condition holds for what its variable matched
+    pub(crate) fn holds(
+        &self,
+        value: &Expression,
+        assumptions: &Assumptions,
+        angle_mode: AngleMode,
+    ) -> bool {
+        if self.check(
+            eval_in(value, angle_mode).ok(),
+            matches!(value, Expression::Variable(_)),
+        ) {
+            return true;
+        }
+        // x is positive also holds for x + 1 when x > 0 is assumed
+        let predicate = match self.property {
+            Property::Integer => Predicate::Integer,
+            Property::Positive => Predicate::Positive,
+            Property::Negative => Predicate::Negative,
+            Property::Nonzero => Predicate::Nonzero,
+            _ => return false,
+        };
+        assumptions.holds(value, predicate)
+    }
+
+    // whether the condition holds for a match with the given value, if it is made only of numbers,
+    // and that may or may not be a single variable
+    fn check(&self, value: Option<f64>, is_variable: bool) -> bool {
+        if self.property == Property::Variable {
+            return is_variable;
+        }
+        // every other property needs a value, which only expressions without variables have
+        let Some(value) = value else {
+            return false;
+        };
+        match self.property {
+            Property::Constant => true,
+            Property::Integer => value.fract() == 0.0,
+            Property::Even => value % 2.0 == 0.0,
+            Property::Odd => value.abs() % 2.0 == 1.0,
+            Property::Positive => value > 0.0,
+            Property::Negative => value < 0.0,
+            Property::Nonzero => value != 0.0,
+            Property::Variable => unreachable!(),
+        }
+    }
+}
+
+impl RuleSet {
+    // one rule per line, blank lines and everything after a # are ignored
+    pub fn parse(name: &str, text: &str) -> Result<RuleSet, String> {
+        let rules = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i, line.split('#').next().unwrap_or("")))
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                Rule::parse(line).map_err(|err| format!("{} line {}: {}", name, i + 1, err))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(RuleSet {
+            name: name.to_string(),
+            rules,
+        })
+    }
+
+    // loads a rule set from a file, it is named after the file
+    pub fn load(path: impl AsRef<Path>) -> Result<RuleSet, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        RuleSet::parse(&name, &text)
+    }
+
+    // the identities from rules/identities.rules
+    pub fn identities() -> RuleSet {
+        RuleSet::parse("identities", IDENTITIES).expect("the built in rules should parse")
+    }
+
+    // a pass that rewrites a node with the first rule that matches it
+    pub fn apply(
+        &self,
+        expression: Expression,
+        assumptions: &Assumptions,
+        angle_mode: AngleMode,
+    ) -> (Expression, bool) {
+        for rule in &self.rules {
+            if let Some(rewritten) = rule.rewrite(&expression, assumptions, angle_mode) {
+                return (rewritten, false);
+            }
+        }
+        (expression, true)
+    }
+}
+
+fn parse_expression(text: &str) -> Result<Expression, String> {
+    if text.trim().is_empty() {
+        return Err(String::from("Both sides of a rule need an expression"));
+    }
+    let mut expressions = parse(tokenize(text.to_string()));
+    match expressions.len() {
+        1 => Ok(expressions.remove(0)),
+        _ => Err(format!(
+            "\"{}\" should be exactly one expression",
+            text.trim()
+        )),
+    }
+}
+
+// (a + b) + c -> a + b + c, so patterns line up with the flattened sums and products the simplifier makes
+fn flatten(expression: Expression) -> Expression {
+    match expression {
+        Expression::Addition(addition) => Expression::Addition(Addition {
+            terms: addition
+                .terms
+                .into_iter()
+                .flat_map(|term| match flatten(*term) {
+                    Expression::Addition(inner) => inner.terms,
+                    term => vec![Box::new(term)],
+                })
+                .collect(),
+        }),
+        Expression::Multiplication(multiplication) => Expression::Multiplication(Multiplication {
+            terms: multiplication
+                .terms
+                .into_iter()
+                .flat_map(|term| match flatten(*term) {
+                    Expression::Multiplication(inner) => inner.terms,
+                    term => vec![Box::new(term)],
+                })
+                .collect(),
+        }),
+        Expression::Division(division) => Expression::Division(Division {
+            numerator: Box::new(flatten(*division.numerator)),
+            denominator: Box::new(flatten(*division.denominator)),
+        }),
+        Expression::Negation(negation) => Expression::Negation(Negation {
+            term: Box::new(flatten(*negation.term)),
+        }),
+        Expression::Exponentiation(exponentiation) => Expression::Exponentiation(Exponentiation {
+            base: Box::new(flatten(*exponentiation.base)),
+            exponent: Box::new(flatten(*exponentiation.exponent)),
+        }),
+        Expression::Sqrt(sqrt) => Expression::Sqrt(Sqrt {
+            arg: Box::new(flatten(*sqrt.arg)),
+        }),
+        Expression::Function(function) => {
+            let name = function.name();
+            let arg = Box::new(flatten(*function.into_arg()));
+            Expression::Function(Function::from_name(name, arg).unwrap())
+        }
+        expression => expression,
+    }
+}
+
+// tries every way the pattern matches the expression, calling `found` with the bindings of each
+// until it returns true, returns whether it did
+fn matches(
+    pattern: &Expression,
+    expression: &Expression,
+    bindings: &mut Bindings,
+    found: &mut dyn FnMut(&mut Bindings) -> bool,
+) -> bool {
+    match (pattern, expression) {
+        (Expression::Variable(variable), expression) => {
+            match bindings.iter().find(|(bound, _)| bound == variable) {
+                Some((_, value)) => value == expression && found(bindings),
+                None => {
+                    bindings.push((variable.clone(), expression.clone()));
+                    let done = found(bindings);
+                    bindings.pop();
+                    done
+                }
+            }
+        }
+        (Expression::Number(a), Expression::Number(b)) => a == b && found(bindings),
+        (Expression::Negation(pattern), Expression::Negation(negation)) => {
+            matches(&pattern.term, &negation.term, bindings, found)
+        }
+        // -x also matches a negative number
+        (Expression::Negation(pattern), Expression::Number(n)) if *n < 0.0 => {
+            matches(&pattern.term, &Expression::Number(-n), bindings, found)
+        }
+        (Expression::Addition(pattern), Expression::Addition(addition)) => match_terms(
+            &pattern.terms.iter().map(|term| &**term).collect::<Vec<_>>(),
+            addition.terms.iter().map(|term| &**term).collect(),
+            &|terms| Expression::Addition(Addition { terms }),
+            bindings,
+            found,
+        ),
+        (Expression::Multiplication(pattern), Expression::Multiplication(multiplication)) => {
+            match_terms(
+                &pattern.terms.iter().map(|term| &**term).collect::<Vec<_>>(),
+                multiplication.terms.iter().map(|term| &**term).collect(),
+                &|terms| Expression::Multiplication(Multiplication { terms }),
+                bindings,
+                found,
+            )
+        }
+        (Expression::Division(pattern), Expression::Division(division)) => matches(
+            &pattern.numerator,
+            &division.numerator,
+            bindings,
+            &mut |bindings| matches(&pattern.denominator, &division.denominator, bindings, found),
+        ),
+        (Expression::Exponentiation(pattern), Expression::Exponentiation(exponentiation)) => {
+            matches(
+                &pattern.base,
+                &exponentiation.base,
+                bindings,
+                &mut |bindings| {
+                    matches(&pattern.exponent, &exponentiation.exponent, bindings, found)
+                },
+            )
+        }
+        (Expression::Sqrt(pattern), Expression::Sqrt(sqrt)) => {
+            matches(&pattern.arg, &sqrt.arg, bindings, found)
+        }
+        (Expression::Function(pattern), Expression::Function(function)) => {
+            pattern.name() == function.name()
+                && matches(pattern.arg(), function.arg(), bindings, found)
+        }
+        _ => false,
+    }
+}
+
+// matches the terms of a sum or product in any order,
+// the last pattern term can be a variable that matches all of the leftover terms together, a*(b+c) matches x*y*(p+q)
+fn match_terms(
+    patterns: &[&Expression],
+    terms: Vec<&Expression>,
+    combine: &dyn Fn(Vec<Box<Expression>>) -> Expression,
+    bindings: &mut Bindings,
+    found: &mut dyn FnMut(&mut Bindings) -> bool,
+) -> bool {
+    if patterns.is_empty() {
+        return terms.is_empty() && found(bindings);
+    }
+    if patterns.len() > terms.len() {
+        return false;
+    }
+    if let [Expression::Variable(_)] = patterns {
+        if terms.len() > 1 {
+            let rest = combine(terms.iter().map(|term| Box::new((*term).clone())).collect());
+            return matches(patterns[0], &rest, bindings, found);
+        }
+    }
+
+    // match the most specific pattern term first so the wildcards only take what is left
+    let index = patterns
+        .iter()
+        .position(|pattern| !matches!(pattern, Expression::Variable(_)))
+        .unwrap_or(0);
+    let mut others = patterns.to_vec();
+    let pattern = others.remove(index);
+    for i in 0..terms.len() {
+        let mut rest = terms.clone();
+        let term = rest.remove(i);
+        let done = matches(pattern, term, bindings, &mut |bindings| {
+            match_terms(&others, rest.clone(), combine, bindings, found)
+        });
+        if done {
+            return true;
+        }
+    }
+    false
+}
+
+// the replacement with every pattern variable swapped for what it matched,
+// parts that end up made only of numbers are evaluated if they come out as an integer, n/2 with n = 4 -> 2
+fn substitute(expression: &Expression, bindings: &Bindings, angle_mode: AngleMode) -> Expression {
+    substitute_with(
+        expression,
+        bindings,
+        &|substituted| match eval_in(&substituted, angle_mode) {
+            Ok(value) if value.is_finite() && value.fract() == 0.0 => Expression::Number(value),
+            _ => substituted,
+        },
+    )
+}
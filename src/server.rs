@@ -1,49 +1,270 @@
+use rustic_math::{
+    check_digits, complex_to_latex, eval_big_with, eval_complex_with, eval_with, format_complex,
+    parse, simplify_with_steps, to_latex, to_latex_highlighted, tokenize, ComplexForm, Environment,
+    EvalError, Limits, SharedCache, SimplifyCache, SimplifyOptions, DEFAULT_CAPACITY,
+};
+use std::time::Duration;
 use warp::Filter;
-use rustic_math::{tokenize, parse, simplify_expression, eval, to_latex};
-use urlencoding;
+
+#[derive(Debug, serde::Deserialize)]
+struct SimplifyQuery {
+    // expand, factor, collect(<variable>), trig_reduce, exponential or simplest, defaults to expand
+    strategy: Option<String>,
+    // passes or egraph, defaults to passes
+    backend: Option<String>,
+    // radians, degrees or gradians, defaults to radians
+    angle: Option<String>,
+    // a json object of the variables' values, numbers or expressions like {"x": 2, "y": "x^2"}
+    variables: Option<String>,
+    // rectangular or polar evaluates over the complex numbers and writes the result in that form
+    complex: Option<String>,
+    // how many significant digits a real result is worked out to, past the 16 or so of an f64, at most MAX_DIGITS
+    digits: Option<u32>,
+    // the simplifier's limits, these can only tighten the defaults in Limits::default
+    max_iterations: Option<usize>,
+    max_nodes: Option<usize>,
+    // in milliseconds
+    max_time: Option<u64>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct JsonStep {
+    pass: String,
+    path: Vec<usize>,
+    before: String,
+    after: String,
+    description: String,
+}
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 struct JsonResponse {
     simplified: String,
     result: String,
+    // why the simplification stopped early, if it did
+    limit: Option<String>,
+    // the latex of a complex result
+    result_latex: Option<String>,
+    steps: Vec<JsonStep>,
+    // why the simplified expression couldn't be evaluated, with the path to the node at fault
+    error: Option<EvalError>,
+    // the simplified expression with that node highlighted
+    highlighted: Option<String>,
+}
+
+fn environment(variables: &str) -> Result<Environment, String> {
+    let variables: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(variables).map_err(|err| format!("Invalid variables: {}", err))?;
+    let mut environment = Environment::new();
+    for (name, value) in variables {
+        match value {
+            serde_json::Value::Number(number) => {
+                environment.set(&name, number.as_f64().unwrap())?
+            }
+            serde_json::Value::String(expression) => {
+                let mut expressions = parse(tokenize(expression));
+                if expressions.len() != 1 {
+                    return Err(format!("Expected one expression for {}", name));
+                }
+                environment.set_expression(&name, expressions.remove(0))?
+            }
+            _ => return Err(format!("Expected a number or an expression for {}", name)),
+        }
+    }
+    Ok(environment)
 }
 
-fn handle_result<T: std::fmt::Debug, E: std::fmt::Display>(result: Result<T, E>) -> String {
+fn handle_result<E: std::fmt::Display>(result: Result<String, E>) -> String {
     match result {
-        Ok(value) => format!("{:?}", value), // Format the successful result
+        Ok(value) => value,                    // Format the successful result
         Err(err) => format!("Error: {}", err), // Format the error message
     }
 }
 
-#[tokio::main]
-async fn main() {
+// simplifies and evaluates one request, this blocks for up to the time limit so it runs off the async workers
+fn simplify(input: String, query: SimplifyQuery, cache: SharedCache) -> String {
+    let defaults = Limits::default();
+    let mut options = SimplifyOptions {
+        cache: Some(cache),
+        limits: Limits {
+            max_iterations: query.max_iterations.map_or(defaults.max_iterations, |max| {
+                max.min(defaults.max_iterations)
+            }),
+            max_nodes: query
+                .max_nodes
+                .map_or(defaults.max_nodes, |max| max.min(defaults.max_nodes)),
+            max_time: query.max_time.map_or(defaults.max_time, |max| {
+                Duration::from_millis(max).min(defaults.max_time)
+            }),
+        },
+        ..SimplifyOptions::default()
+    };
+    if let Some(strategy) = query.strategy {
+        match strategy.parse() {
+            Ok(strategy) => options.strategy = strategy,
+            Err(err) => return format!("Error: {}", err),
+        }
+    }
+    if let Some(backend) = query.backend {
+        match backend.parse() {
+            Ok(backend) => options.backend = backend,
+            Err(err) => return format!("Error: {}", err),
+        }
+    }
+    if let Some(angle_mode) = query.angle {
+        match angle_mode.parse() {
+            Ok(angle_mode) => options.angle_mode = angle_mode,
+            Err(err) => return format!("Error: {}", err),
+        }
+    }
+    let complex = match query.complex.map(|form| form.parse::<ComplexForm>()) {
+        Some(Ok(form)) => Some(form),
+        Some(Err(err)) => return format!("Error: {}", err),
+        None => None,
+    };
+    if let Some(Err(err)) = query.digits.map(check_digits) {
+        return format!("Error: {}", err);
+    }
+    if complex.is_some() && query.digits.is_some() {
+        return "Error: digits only apply to real results, not with complex".to_string();
+    }
+    let mut environment = match query.variables.as_deref().map(environment) {
+        Some(Ok(environment)) => environment,
+        Some(Err(err)) => return format!("Error: {}", err),
+        None => Environment::new(),
+    };
+    environment.angle_mode = options.angle_mode;
+    // un-url-encode the input
+    let input = urlencoding::decode(&input).unwrap();
+    // Tokenize the input
+    let tokens = tokenize(input.to_string());
+    // Parse the tokens into expressions
+    let expressions = parse(tokens);
+
+    if expressions.len() == 1 {
+        // Simplify the expression
+        let (simplified, steps) = simplify_with_steps(expressions[0].clone(), &options);
+        // Evaluate the simplified expression
+        let (result, result_latex) = match complex {
+            Some(form) => match eval_complex_with(&simplified.expression, &environment) {
+                Ok(value) => (
+                    Ok(format_complex(value, form, options.angle_mode)),
+                    Some(complex_to_latex(value, form, options.angle_mode)),
+                ),
+                Err(err) => (Err(err), None),
+            },
+            None => match query.digits {
+                Some(digits) => (
+                    eval_big_with(&simplified.expression, digits, &environment)
+                        .map(|value| value.to_digits(digits)),
+                    None,
+                ),
+                None => (
+                    eval_with(&simplified.expression, &environment)
+                        .map(|value| format!("{:?}", value)),
+                    None,
+                ),
+            },
+        };
+        // Return the result as a json response
+        serde_json::to_string(&JsonResponse {
+            simplified: to_latex(&simplified.expression),
+            result: handle_result(result.clone()),
+            result_latex,
+            limit: simplified.limit.map(|limit| limit.to_string()),
+            steps: steps
+                .into_iter()
+                .map(|step| JsonStep {
+                    pass: step.pass,
+                    path: step.path,
+                    before: step.before,
+                    after: step.after,
+                    description: step.description,
+                })
+                .collect(),
+            highlighted: result
+                .as_ref()
+                .err()
+                .map(|error| to_latex_highlighted(&simplified.expression, error.path())),
+            error: result.err(),
+        })
+        .unwrap()
+    } else {
+        "Error: Multiple expressions are not supported.".to_string()
+    }
+}
+
+fn routes(cache: SharedCache) -> impl Filter<Extract = (String,), Error = warp::Rejection> + Clone {
+    let simplify_cache = cache.clone();
+
     // Define the `/simplify` endpoint
     let simplify = warp::path!("simplify" / String)
-        .map(|input: String| {
-            // un-url-encode the input
-            let input = urlencoding::decode(&input).unwrap();
-            // Tokenize the input
-            let tokens = tokenize(input.to_string());
-            // Parse the tokens into expressions
-            let expressions = parse(tokens);
-
-            if expressions.len() == 1 {
-                // Simplify the expression
-                let simplified = simplify_expression(expressions[0].clone());
-                // Evaluate the simplified expression
-                let result = eval(&simplified);
-                // Return the result as a json response
-                serde_json::to_string(&JsonResponse {
-                    simplified: to_latex(&simplified),
-                    result: handle_result(result),
-                }).unwrap()
-            } else {
-                "Error: Multiple expressions are not supported.".to_string()
+        .and(warp::query::<SimplifyQuery>())
+        .and_then(move |input: String, query: SimplifyQuery| {
+            let cache = simplify_cache.clone();
+            async move {
+                tokio::task::spawn_blocking(move || simplify(input, query, cache))
+                    .await
+                    .map_err(|_| warp::reject())
             }
         });
 
+    // the `/cache` endpoint reports the cache's hits and misses, for picking its capacity
+    let cache_stats = warp::path!("cache")
+        .map(move || serde_json::to_string(&cache.lock().unwrap().stats()).unwrap());
+
+    simplify.or(cache_stats).unify()
+}
+
+#[tokio::main]
+async fn main() {
+    // every request shares one cache so a subtree simplified for one doesn't have to be simplified again
+    let cache = SimplifyCache::shared(DEFAULT_CAPACITY);
+
     // Combine routes and start the server
-    let routes = simplify.with(warp::cors().allow_any_origin());
+    let routes = routes(cache).with(warp::cors().allow_any_origin());
     println!("Starting server on http://localhost:3000");
     warp::serve(routes).run(([127, 0, 0, 1], 3000)).await;
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cache_reports_the_simplify_requests() {
+        let cache = SimplifyCache::shared(DEFAULT_CAPACITY);
+        let routes = routes(cache);
+        let stats = |body: &[u8]| -> serde_json::Value { serde_json::from_slice(body).unwrap() };
+
+        let response = warp::test::request().path("/cache").reply(&routes).await;
+        assert_eq!(response.status(), 200);
+        let empty = stats(response.body());
+        assert_eq!((&empty["hits"], &empty["misses"]), (&0.into(), &0.into()));
+
+        for _ in 0..2 {
+            let response = warp::test::request()
+                .path("/simplify/x%2Bx%2By%2By")
+                .reply(&routes)
+                .await;
+            assert_eq!(response.status(), 200);
+        }
+        let response = warp::test::request().path("/cache").reply(&routes).await;
+        let after = stats(response.body());
+        assert!(after["misses"].as_u64().unwrap() > 0);
+        assert!(after["hits"].as_u64().unwrap() > 0);
+    }
+
+    #[tokio::test]
+    async fn limits_come_from_the_query() {
+        let routes = routes(SimplifyCache::shared(DEFAULT_CAPACITY));
+        let response = warp::test::request()
+            .path("/simplify/(a%2Bb)*(c%2Bd)*(e%2Bf)?max_time=0")
+            .reply(&routes)
+            .await;
+        let response: JsonResponse = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(
+            response.limit.as_deref(),
+            Some("the time limit was reached")
+        );
+    }
+}
@@ -1,190 +1,955 @@
-use crate::{expression::{Addition, Division, Exponentiation, Expression, Function, Multiplication, Negation, Sqrt}, passes::{coalescing::{coalesce_addition, coalesce_multiplication}, distribute_multiplication::distribute_multiplication}};
+use rayon::prelude::*;
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    angle::AngleMode,
+    assumptions::Assumptions,
+    cache::{SharedCache, SimplifyCache, DEFAULT_CAPACITY},
+    egraph,
+    expression::{
+        node_hash, structural_hash, Addition, Division, Exponentiation, Expression, Function,
+        Multiplication, Negation, Sqrt,
+    },
+    factor::factor,
+    passes::{
+        coalescing::{coalesce_addition, coalesce_multiplication},
+        collect::collect,
+        distribute_multiplication::distribute_multiplication,
+        exact_values::exact_values,
+        expand_power::expand_power,
+        exponents::{
+            identity_exponents, negative_exponents, power_of_power, power_of_product,
+            power_of_quotient, radicals, root_of_power, NegativeExponents, Radicals,
+        },
+        imaginary::imaginary_powers,
+        like_terms::{cancel_division, combine_like_factors, combine_like_terms},
+        names,
+        negation::normalize_negation,
+        trig::{
+            exponential_form, hyperbolic_form, hyperbolic_identity, pythagorean_identity,
+            reciprocal_hyperbolic, reciprocal_trig, trig_parity,
+        },
+    },
+    rules::RuleSet,
+    steps::Step,
+    substitution::free_variables,
+};
+
+//the form the simplifier works towards
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Strategy {
+    //multiply everything out into a sum of terms
+    #[default]
+    Expand,
+    //expand and then factor every polynomial back into irreducible factors
+    Factor,
+    //expand and then group the terms by powers of one variable
+    Collect(String),
+    //rewrite tan, cot, sec and csc in terms of sin and cos, and tanh, coth, sech and csch in terms of
+    //sinh and cosh, and apply the trig identities
+    TrigReduce,
+    //rewrite the hyperbolic functions with their definitions in terms of e^x and expand
+    Exponential,
+    //try every other strategy and keep the result with the lowest cost
+    Simplest,
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s {
+            "expand" => Ok(Strategy::Expand),
+            "factor" => Ok(Strategy::Factor),
+            "trig_reduce" => Ok(Strategy::TrigReduce),
+            "exponential" => Ok(Strategy::Exponential),
+            "simplest" => Ok(Strategy::Simplest),
+            _ => match s.strip_prefix("collect(").and_then(|s| s.strip_suffix(')')) {
+                Some(variable) if !variable.trim().is_empty() => Ok(Strategy::Collect(variable.trim().to_string())),
+                _ => Err(format!(
+                    "Unknown strategy \"{}\", expected expand, factor, collect(<variable>), trig_reduce, exponential or simplest",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Strategy::Expand => write!(f, "expand"),
+            Strategy::Factor => write!(f, "factor"),
+            Strategy::Collect(variable) => write!(f, "collect({})", variable),
+            Strategy::TrigReduce => write!(f, "trig_reduce"),
+            Strategy::Exponential => write!(f, "exponential"),
+            Strategy::Simplest => write!(f, "simplest"),
+        }
+    }
+}
+
+//how the simplifier searches for a simpler form
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Backend {
+    //the passes and rules applied in a fixed order until nothing changes
+    #[default]
+    Passes,
+    //equality saturation over an e-graph, slower but it can't get stuck in a worse form
+    //the strategy is ignored and the cheapest form by the cost metric is always picked
+    EGraph,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "passes" => Ok(Backend::Passes),
+            "egraph" => Ok(Backend::EGraph),
+            s => Err(format!(
+                "Unknown backend \"{}\", expected passes or egraph",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Backend::Passes => write!(f, "passes"),
+            Backend::EGraph => write!(f, "egraph"),
+        }
+    }
+}
+
+//controls which of several equivalent forms the simplifier normalizes towards
+#[derive(Debug, Clone)]
+pub struct SimplifyOptions {
+    pub strategy: Strategy,
+    pub backend: Backend,
+    //used by the simplest strategy and the e-graph backend to rank candidate forms, lower is simpler
+    pub cost: fn(&Expression) -> usize,
+    pub negative_exponents: NegativeExponents,
+    pub radicals: Radicals,
+    pub limits: Limits,
+    //rewrite rules applied alongside the built in passes, in every strategy
+    pub rules: Vec<RuleSet>,
+    //what is known about the variables, rewrites that are only sound for some values are skipped unless it allows them
+    pub assumptions: Assumptions,
+    //the unit of the arguments of trig functions and the results of inverse trig functions, for their exact values
+    pub angle_mode: AngleMode,
+    //remembers simplified subtrees across calls that share it, when None each call gets its own
+    pub cache: Option<SharedCache>,
+    //simplifies the terms of sums and products with at least this many nodes on a thread pool,
+    //the result is the same as on one thread, when None everything runs on one thread
+    pub parallel: Option<usize>,
+}
+
+impl Default for SimplifyOptions {
+    fn default() -> Self {
+        SimplifyOptions {
+            strategy: Strategy::default(),
+            backend: Backend::default(),
+            cost: node_count,
+            negative_exponents: NegativeExponents::default(),
+            radicals: Radicals::default(),
+            limits: Limits::default(),
+            rules: vec![RuleSet::identities()],
+            assumptions: Assumptions::default(),
+            angle_mode: AngleMode::default(),
+            cache: None,
+            parallel: None,
+        }
+    }
+}
+
+//how much work the simplifier may do before it gives up and returns the best expression it has found
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    //rounds of every pass over the whole tree
+    pub max_iterations: usize,
+    //nodes in the expression tree, see node_count
+    pub max_nodes: usize,
+    pub max_time: Duration,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_iterations: 1000,
+            max_nodes: 100_000,
+            max_time: Duration::from_secs(5),
+        }
+    }
+}
+
+//why the simplifier stopped before it was done
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitReached {
+    Iterations,
+    Nodes,
+    Time,
+    //the passes kept undoing each other and the expression came back to a form it already had
+    Cycle,
+}
+
+impl fmt::Display for LimitReached {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LimitReached::Iterations => write!(f, "the iteration limit was reached"),
+            LimitReached::Nodes => write!(f, "the expression grew past the node limit"),
+            LimitReached::Time => write!(f, "the time limit was reached"),
+            LimitReached::Cycle => write!(f, "the simplification went in a cycle"),
+        }
+    }
+}
+
+//the result of a simplification that may have been cut short
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simplified {
+    pub expression: Expression,
+    //None if the simplifier finished, otherwise the reason it stopped early
+    pub limit: Option<LimitReached>,
+}
+
+//the default cost metric, the number of nodes in the expression tree
+pub fn node_count(expression: &Expression) -> usize {
+    1 + match expression {
+        Expression::Number(_) | Expression::Variable(_) => 0,
+        Expression::Multiplication(multiplication) => multiplication
+            .terms
+            .iter()
+            .map(|term| node_count(term))
+            .sum(),
+        Expression::Addition(addition) => addition.terms.iter().map(|term| node_count(term)).sum(),
+        Expression::Division(division) => {
+            node_count(&division.numerator) + node_count(&division.denominator)
+        }
+        Expression::Negation(negation) => node_count(&negation.term),
+        Expression::Exponentiation(exponentiation) => {
+            node_count(&exponentiation.base) + node_count(&exponentiation.exponent)
+        }
+        Expression::Sqrt(sqrt) => node_count(&sqrt.arg),
+        Expression::Function(function) => node_count(function.arg()),
+    }
+}
 
 //exposed to api consumers, simplifies an expression to standard form
 //loops until no more simplifications can be made
-pub fn simplify_expression(mut expression: Expression) -> Expression {
-    let mut simplified = false;
-    let passes = [
-        tree_walk_pass(&coalesce_multiplication),
-        tree_walk_pass(&coalesce_addition),
-        tree_walk_pass(&distribute_multiplication),
+pub fn simplify_expression(expression: Expression) -> Expression {
+    simplify_with_options(expression, &SimplifyOptions::default())
+}
+
+//exposed to api consumers, same as simplify_expression with the target form chosen by the caller
+pub fn simplify_with_options(expression: Expression, options: &SimplifyOptions) -> Expression {
+    simplify_bounded(expression, options).expression
+}
+
+//exposed to api consumers, same as simplify_with_options but also says if one of the limits was hit
+pub fn simplify_bounded(expression: Expression, options: &SimplifyOptions) -> Simplified {
+    let mut run = Run::new(options, None);
+    let expression = simplify(expression, options, &mut run);
+    Simplified {
+        expression,
+        limit: run.limit,
+    }
+}
+
+//exposed to api consumers, same as simplify_bounded but also returns every rewrite that was made, in order
+pub fn simplify_with_steps(
+    expression: Expression,
+    options: &SimplifyOptions,
+) -> (Simplified, Vec<Step>) {
+    let mut run = Run::new(options, Some(Vec::new()));
+    let expression = simplify(expression, options, &mut run);
+    (
+        Simplified {
+            expression,
+            limit: run.limit,
+        },
+        run.steps.unwrap_or_default(),
+    )
+}
+
+//a pass and the name it is shown with in the steps
+//passes are Sync so the terms of a sum or product can be walked on several threads
+type NamedPass<'a> = (
+    &'a str,
+    &'a (dyn Fn(Expression) -> (Expression, bool) + Sync),
+);
+
+//the state of one call to the simplifier, shared by every strategy it tries
+struct Run {
+    //only recorded when Some
+    steps: Option<Vec<Step>>,
+    limits: Limits,
+    cost: fn(&Expression) -> usize,
+    started: Instant,
+    iterations: usize,
+    limit: Option<LimitReached>,
+    cache: SharedCache,
+    //everything in the options that changes what an expression simplifies to, so cache entries
+    //from calls with different options are kept apart
+    context: u64,
+    parallel: Option<usize>,
+}
+
+impl Run {
+    fn new(options: &SimplifyOptions, steps: Option<Vec<Step>>) -> Run {
+        let mut hasher = DefaultHasher::new();
+        (options.cost as usize).hash(&mut hasher);
+        format!(
+            "{:?} {:?} {:?} {:?}",
+            options.negative_exponents, options.radicals, options.assumptions, options.angle_mode
+        )
+        .hash(&mut hasher);
+        for rule_set in &options.rules {
+            rule_set
+                .rules
+                .iter()
+                .for_each(|rule| rule.text.hash(&mut hasher));
+        }
+        Run {
+            steps,
+            limits: options.limits,
+            cost: options.cost,
+            started: Instant::now(),
+            iterations: 0,
+            limit: None,
+            cache: options
+                .cache
+                .clone()
+                .unwrap_or_else(|| SimplifyCache::shared(DEFAULT_CAPACITY)),
+            context: hasher.finish(),
+            parallel: options.parallel,
+        }
+    }
+
+    fn steps_len(&self) -> usize {
+        self.steps.as_ref().map_or(0, |steps| steps.len())
+    }
+}
+
+fn simplify(expression: Expression, options: &SimplifyOptions, run: &mut Run) -> Expression {
+    if options.backend == Backend::EGraph {
+        //the e-graph doesn't record steps, it only knows which forms are equal and not how it found out
+        let (expression, limit) = egraph::simplify(expression, options);
+        run.limit = run.limit.or(limit);
+        return expression;
+    }
+    let negative_exponents = negative_exponents(options.negative_exponents);
+    let radicals = radicals(options.radicals);
+    let power_of_power = power_of_power(&options.assumptions);
+    let power_of_product = power_of_product(&options.assumptions);
+    let power_of_quotient = power_of_quotient(&options.assumptions);
+    let root_of_power = root_of_power(&options.assumptions);
+    let exact_values = exact_values(options.angle_mode);
+    //passes that clean an expression up without multiplying anything out
+    let rules: Vec<_> = options
+        .rules
+        .iter()
+        .map(|rules| {
+            move |expression| rules.apply(expression, &options.assumptions, options.angle_mode)
+        })
+        .collect();
+    let mut tidy: Vec<NamedPass> = vec![
+        (names::COALESCE_MULTIPLICATION, &coalesce_multiplication),
+        (names::COALESCE_ADDITION, &coalesce_addition),
+        (names::NORMALIZE_NEGATION, &normalize_negation),
+        (names::CANCEL_DIVISION, &cancel_division),
+        (names::COMBINE_LIKE_FACTORS, &combine_like_factors),
+        (names::COMBINE_LIKE_TERMS, &combine_like_terms),
+        (names::POWER_OF_POWER, &power_of_power),
+        (names::POWER_OF_PRODUCT, &power_of_product),
+        (names::POWER_OF_QUOTIENT, &power_of_quotient),
+        (names::ROOT_OF_POWER, &root_of_power),
+        (names::IDENTITY_EXPONENTS, &identity_exponents),
+        (names::NEGATIVE_EXPONENTS, &negative_exponents),
+        (names::RADICALS, &radicals),
+        (names::EXACT_VALUES, &exact_values),
+        (names::IMAGINARY_POWERS, &imaginary_powers),
     ];
+    for (rule_set, pass) in options.rules.iter().zip(&rules) {
+        tidy.push((&rule_set.name, pass));
+    }
+    let expanding: [NamedPass; 2] = [
+        (names::EXPAND_POWER, &expand_power),
+        (names::DISTRIBUTE, &distribute_multiplication),
+    ];
+
+    match &options.strategy {
+        Strategy::Expand => run_passes(expression, &[&tidy[..], &expanding[..]].concat(), run),
+        Strategy::Factor => {
+            let expanded = run_passes(expression, &[&tidy[..], &expanding[..]].concat(), run);
+            let factored = run_once(&(names::FACTOR, &factor_sums), expanded, run);
+            run_passes(factored, &tidy, run)
+        }
+        Strategy::Collect(variable) => {
+            let expanded = run_passes(expression, &[&tidy[..], &expanding[..]].concat(), run);
+            let collect = collect(variable.clone());
+            let collected = run_once(&(names::COLLECT, &collect), expanded, run);
+            run_passes(collected, &tidy, run)
+        }
+        Strategy::TrigReduce => {
+            let trig: [NamedPass; 6] = [
+                (names::RECIPROCAL_TRIG, &reciprocal_trig),
+                (names::RECIPROCAL_HYPERBOLIC, &reciprocal_hyperbolic),
+                (names::TRIG_PARITY, &trig_parity),
+                (names::PYTHAGOREAN_IDENTITY, &pythagorean_identity),
+                (names::HYPERBOLIC_IDENTITY, &hyperbolic_identity),
+                (names::HYPERBOLIC_FORM, &hyperbolic_form),
+            ];
+            run_passes(
+                expression,
+                &[&tidy[..], &expanding[..], &trig[..]].concat(),
+                run,
+            )
+        }
+        Strategy::Exponential => {
+            let exponential: [NamedPass; 1] = [(names::EXPONENTIAL_FORM, &exponential_form)];
+            run_passes(
+                expression,
+                &[&tidy[..], &expanding[..], &exponential[..]].concat(),
+                run,
+            )
+        }
+        Strategy::Simplest => {
+            let variables = free_variables(&expression);
+            let strategies = [
+                Strategy::Expand,
+                Strategy::Factor,
+                Strategy::TrigReduce,
+                Strategy::Exponential,
+            ]
+            .into_iter()
+            .chain(variables.into_iter().map(Strategy::Collect));
+            let steps = run.steps.clone();
+            let mut best = (expression.clone(), steps.clone());
+            let mut best_cost = usize::MAX;
+            for strategy in strategies {
+                //every candidate gets its own steps so only the ones leading to the chosen form are kept
+                run.steps = steps.clone();
+                let candidate = simplify(
+                    expression.clone(),
+                    &SimplifyOptions {
+                        strategy,
+                        ..options.clone()
+                    },
+                    run,
+                );
+                let cost = (options.cost)(&candidate);
+                if cost < best_cost {
+                    best = (candidate, run.steps.take());
+                    best_cost = cost;
+                }
+            }
+            run.steps = best.1;
+            best.0
+        }
+    }
+}
 
+//applies every pass over the whole tree, in order, until none of them change anything or a limit is hit
+//when a limit is hit the lowest cost expression seen between rounds is returned
+fn run_passes(mut expression: Expression, passes: &[NamedPass], run: &mut Run) -> Expression {
+    if run.limit.is_some() {
+        return expression;
+    }
+    let mut hasher = DefaultHasher::new();
+    run.context.hash(&mut hasher);
+    passes.iter().for_each(|(name, _)| name.hash(&mut hasher));
+    let context = hasher.finish();
+    //the same expression simplified with the same passes before, the steps to it weren't kept so it is
+    //only reused when they aren't wanted, or when it was already as simple as the passes make it
+    let input = expression.clone();
+    let cached = match run.steps {
+        None => cache_key(context, Cached::Result, structural_hash(&input)),
+        Some(_) => cache_key(context, Cached::Simplified, structural_hash(&input)),
+    };
+    if let Some(cached) = run.cache.lock().unwrap().get(cached, &input) {
+        return cached;
+    }
+
+    let mut seen = HashSet::from([structural_hash(&expression)]);
+    let mut best = ((run.cost)(&expression), expression.clone(), run.steps_len());
+    let mut simplified = false;
+    let mut round_start = 0;
     while !simplified {
         simplified = true;
-        for pass in &passes {
-            let (pass_expression, pass_simplified) = pass(expression);
+        round_start = structural_hash(&expression);
+        for pass in passes {
+            let subtrees = Subtrees::new(&expression);
+            let walk = Walk {
+                cache: Some((&run.cache, context)),
+                parallel: run.parallel,
+                budget: Budget::new(run, subtrees.nodes),
+            };
+            let (pass_expression, pass_simplified, pass_nodes) =
+                if walk.known_simplified(&expression, &subtrees) {
+                    (expression, true, subtrees.nodes)
+                } else {
+                    tree_walk_pass(
+                        pass,
+                        expression,
+                        &subtrees,
+                        &mut Vec::new(),
+                        &mut run.steps,
+                        &walk,
+                    )
+                };
             expression = pass_expression;
             simplified &= pass_simplified;
+
+            //the walk only checks the limits when it rewrites something
+            run.limit = walk.budget.limit.get().copied();
+            if run.limit.is_none() {
+                if pass_nodes > run.limits.max_nodes {
+                    run.limit = Some(LimitReached::Nodes);
+                } else if run.started.elapsed() > run.limits.max_time {
+                    run.limit = Some(LimitReached::Time);
+                }
+            }
+            if run.limit.is_some() {
+                break;
+            }
+        }
+        if !simplified && run.limit.is_none() {
+            run.iterations += 1;
+            if run.iterations >= run.limits.max_iterations {
+                run.limit = Some(LimitReached::Iterations);
+            } else if !seen.insert(structural_hash(&expression)) {
+                run.limit = Some(LimitReached::Cycle);
+            }
+        }
+
+        if run.limit.is_some() {
+            //the expression may be worse than one from an earlier round, if it grew past the node limit it certainly is
+            let cost = (run.cost)(&expression);
+            if run.limit != Some(LimitReached::Nodes) && cost < best.0 {
+                return expression;
+            }
+            if let Some(steps) = &mut run.steps {
+                steps.truncate(best.2);
+            }
+            return best.1;
+        }
+        if !simplified {
+            let cost = (run.cost)(&expression);
+            if cost < best.0 {
+                best = (cost, expression.clone(), run.steps_len());
+            }
         }
     }
 
+    let mut cache = run.cache.lock().unwrap();
+    cache.insert(
+        cache_key(context, Cached::Result, structural_hash(&input)),
+        input,
+        expression.clone(),
+    );
+    //every pass left every node alone in the last round, so every subtree is as simple as these passes
+    //make it and can be skipped wherever it turns up again, unless a pass changed something while
+    //saying it didn't
+    if round_start == structural_hash(&expression) {
+        cache.store(
+            cache_key(context, Cached::Result, round_start),
+            expression.clone(),
+            expression.clone(),
+        );
+        cache_subtrees(&mut cache, context, &expression);
+    }
+    drop(cache);
     expression
 }
 
+//what a cache entry holds for a subtree
+#[derive(Hash)]
+enum Cached {
+    //what it simplifies to
+    Result,
+    //itself, it is already simplified
+    Simplified,
+}
+
+fn cache_key(context: u64, cached: Cached, hash: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    context.hash(&mut hasher);
+    cached.hash(&mut hasher);
+    hash.hash(&mut hasher);
+    hasher.finish()
+}
+
+//marks every subtree with children as simplified, leaves are too cheap to be worth an entry
+//returns the structural hash of `expression`, worked out from its children's on the way up
+fn cache_subtrees(cache: &mut SimplifyCache, context: u64, expression: &Expression) -> u64 {
+    let children: Vec<u64> = expression
+        .children()
+        .into_iter()
+        .map(|child| cache_subtrees(cache, context, child))
+        .collect();
+    let hash = node_hash(expression, &children);
+    if !children.is_empty() {
+        cache.store(
+            cache_key(context, Cached::Simplified, hash),
+            expression.clone(),
+            expression.clone(),
+        );
+    }
+    hash
+}
+
+//what a walk needs to know about every subtree before it walks it, worked out bottom-up in one walk
+//so the walk doesn't count or hash a subtree again at every node above it
+struct Subtrees {
+    hash: u64,
+    nodes: usize,
+    //in the order of Expression::children
+    children: Vec<Subtrees>,
+}
+
+impl Subtrees {
+    fn new(expression: &Expression) -> Subtrees {
+        let children: Vec<Subtrees> = expression
+            .children()
+            .into_iter()
+            .map(Subtrees::new)
+            .collect();
+        let hashes: Vec<u64> = children.iter().map(|child| child.hash).collect();
+        Subtrees {
+            hash: node_hash(expression, &hashes),
+            nodes: 1 + children.iter().map(|child| child.nodes).sum::<usize>(),
+            children,
+        }
+    }
+}
+
+//what stays the same over one walk of a pass over the tree
+struct Walk<'a> {
+    //holds subtrees already known to be simplified by the passes the pass was run with, with the
+    //context their keys are made in, only given when the pass is run to a fixpoint with the others
+    cache: Option<(&'a SharedCache, u64)>,
+    //SimplifyOptions::parallel
+    parallel: Option<usize>,
+    budget: Budget,
+}
+
+impl Walk<'_> {
+    //whether `expression` is already as simple as the passes make it
+    //only looked up at the top of a walk and of each term of a parallel walk, so the threads don't
+    //take turns on the lock for every node
+    fn known_simplified(&self, expression: &Expression, subtrees: &Subtrees) -> bool {
+        self.cache.is_some_and(|(cache, context)| {
+            !subtrees.children.is_empty()
+                && cache
+                    .lock()
+                    .unwrap()
+                    .get(
+                        cache_key(context, Cached::Simplified, subtrees.hash),
+                        expression,
+                    )
+                    .is_some()
+        })
+    }
 
+    //whether the terms of a sum or product are worth walking on several threads
+    fn is_wide(&self, subtrees: &Subtrees) -> bool {
+        self.parallel
+            .is_some_and(|min_nodes| subtrees.children.len() > 1 && subtrees.nodes > min_nodes)
+    }
+}
+
+//applies a pass over the whole tree exactly once, unless a limit was already hit
+fn run_once(pass: &NamedPass, expression: Expression, run: &mut Run) -> Expression {
+    if run.limit.is_some() {
+        return expression;
+    }
+    let subtrees = Subtrees::new(&expression);
+    let walk = Walk {
+        cache: None,
+        parallel: run.parallel,
+        budget: Budget::new(run, subtrees.nodes),
+    };
+    let (expression, _, _) = tree_walk_pass(
+        pass,
+        expression,
+        &subtrees,
+        &mut Vec::new(),
+        &mut run.steps,
+        &walk,
+    );
+    run.limit = walk.budget.limit.get().copied();
+    expression
+}
+
+//factors every sum that is a polynomial, (x^2 - 1) -> (x - 1)(x + 1)
+fn factor_sums(expression: Expression) -> (Expression, bool) {
+    match expression {
+        Expression::Addition(_) => (factor(expression), true),
+        _ => (expression, true),
+    }
+}
+
+//the limits as a walk over the tree sees them, checked after every node a pass rewrites so one pass
+//over a large tree stops close to a limit instead of only once it is done
+//shared by the threads of a parallel walk
+struct Budget {
+    max_nodes: usize,
+    max_time: Duration,
+    started: Instant,
+    //nodes in the whole tree, kept up to date as nodes are rewritten
+    nodes: AtomicUsize,
+    limit: OnceLock<LimitReached>,
+}
+
+impl Budget {
+    fn new(run: &Run, nodes: usize) -> Budget {
+        Budget {
+            max_nodes: run.limits.max_nodes,
+            max_time: run.limits.max_time,
+            started: run.started,
+            nodes: AtomicUsize::new(nodes),
+            limit: OnceLock::new(),
+        }
+    }
+
+    fn reached(&self) -> bool {
+        self.limit.get().is_some()
+    }
+
+    //a node of `before` nodes was rewritten to one of `after` nodes
+    fn rewrote(&self, before: usize, after: usize) {
+        let nodes = if after >= before {
+            self.nodes.fetch_add(after - before, Ordering::Relaxed) + (after - before)
+        } else {
+            self.nodes.fetch_sub(before - after, Ordering::Relaxed) - (before - after)
+        };
+        if nodes > self.max_nodes {
+            let _ = self.limit.set(LimitReached::Nodes);
+        } else if self.started.elapsed() > self.max_time {
+            let _ = self.limit.set(LimitReached::Time);
+        }
+    }
+}
+
+//applies a pass to every subtree, children first, and returns the result with its node count
+//`subtrees` is Subtrees::new of `expression`
+//`path` is the path from the root to `expression` and is left as it was on return
+//`expression` itself isn't looked up in the walk's cache
+//once the walk's budget runs out the rest of the tree is left as it is
 fn tree_walk_pass(
-    pass: &dyn Fn(Expression) -> (Expression, bool),
-) -> impl Fn(Expression) -> (Expression, bool) + '_ {
-    move |expression| {
-        //recurse into the expression
-        let (expr, simplified) = match expression {
-            Expression::Multiplication(multiplication) => {
-                let (terms, terms_simplified) = multiplication
-                    .terms
-                    .into_iter()
-                    .map(|term| tree_walk_pass(pass)(*term))
-                    .fold((Vec::new(), true), |mut a, e| {
-                        a.0.push(Box::new(e.0));
-                        a.1 &= e.1;
-                        a
-                    });
-                (
-                    Expression::Multiplication(Multiplication { terms }),
-                    terms_simplified,
-                )
-            }
-            Expression::Division(division) => {
-                let (numerator, numerator_simplified) = tree_walk_pass(pass)(*division.numerator);
-                let (denominator, denominator_simplified) =
-                    tree_walk_pass(pass)(*division.denominator);
-                (
-                    Expression::Division(Division {
-                        numerator: Box::new(numerator),
-                        denominator: Box::new(denominator),
-                    }),
-                    numerator_simplified && denominator_simplified,
-                )
-            }
-            Expression::Addition(addition) => {
-                let (terms, terms_simplified) = addition
-                    .terms
-                    .into_iter()
-                    .map(|term| tree_walk_pass(pass)(*term))
-                    .fold((Vec::new(), true), |mut a, e| {
-                        a.0.push(Box::new(e.0));
-                        a.1 &= e.1;
-                        a
-                    });
-                (Expression::Addition(Addition { terms }), terms_simplified)
-            }
-            Expression::Negation(negation) => {
-                let (term, term_simplified) = tree_walk_pass(pass)(*negation.term);
-                (
-                    Expression::Negation(Negation {
-                        term: Box::new(term),
-                    }),
-                    term_simplified,
-                )
-            }
-            Expression::Exponentiation(exponentiation) => {
-                let (base, base_simplified) = tree_walk_pass(pass)(*exponentiation.base);
-                let (exponent, exponent_simplified) =
-                    tree_walk_pass(pass)(*exponentiation.exponent);
-                (
-                    Expression::Exponentiation(Exponentiation {
-                        base: Box::new(base),
-                        exponent: Box::new(exponent),
-                    }),
-                    base_simplified && exponent_simplified,
-                )
+    pass: &NamedPass,
+    expression: Expression,
+    subtrees: &Subtrees,
+    path: &mut Vec<usize>,
+    steps: &mut Option<Vec<Step>>,
+    walk: &Walk,
+) -> (Expression, bool, usize) {
+    let budget = &walk.budget;
+    if budget.reached() {
+        return (expression, true, subtrees.nodes);
+    }
+    let mut child = |index: usize, expression: Expression| {
+        path.push(index);
+        let result = tree_walk_pass(
+            pass,
+            expression,
+            &subtrees.children[index],
+            path,
+            steps,
+            walk,
+        );
+        path.pop();
+        result
+    };
+    //recurse into the expression
+    let (expr, simplified, nodes) = match expression {
+        Expression::Multiplication(multiplication) if walk.is_wide(subtrees) => {
+            let (terms, terms_simplified, nodes) =
+                parallel_walk(pass, multiplication.terms, subtrees, path, steps, walk);
+            (
+                Expression::Multiplication(Multiplication { terms }),
+                terms_simplified,
+                1 + nodes,
+            )
+        }
+        Expression::Multiplication(multiplication) => {
+            let (terms, terms_simplified, nodes) = multiplication
+                .terms
+                .into_iter()
+                .enumerate()
+                .map(|(i, term)| child(i, *term))
+                .fold((Vec::new(), true, 1), |mut a, e| {
+                    a.0.push(Box::new(e.0));
+                    a.1 &= e.1;
+                    a.2 += e.2;
+                    a
+                });
+            (
+                Expression::Multiplication(Multiplication { terms }),
+                terms_simplified,
+                nodes,
+            )
+        }
+        Expression::Division(division) => {
+            let (numerator, numerator_simplified, numerator_nodes) = child(0, *division.numerator);
+            let (denominator, denominator_simplified, denominator_nodes) =
+                child(1, *division.denominator);
+            (
+                Expression::Division(Division {
+                    numerator: Box::new(numerator),
+                    denominator: Box::new(denominator),
+                }),
+                numerator_simplified && denominator_simplified,
+                1 + numerator_nodes + denominator_nodes,
+            )
+        }
+        Expression::Addition(addition) if walk.is_wide(subtrees) => {
+            let (terms, terms_simplified, nodes) =
+                parallel_walk(pass, addition.terms, subtrees, path, steps, walk);
+            (
+                Expression::Addition(Addition { terms }),
+                terms_simplified,
+                1 + nodes,
+            )
+        }
+        Expression::Addition(addition) => {
+            let (terms, terms_simplified, nodes) = addition
+                .terms
+                .into_iter()
+                .enumerate()
+                .map(|(i, term)| child(i, *term))
+                .fold((Vec::new(), true, 1), |mut a, e| {
+                    a.0.push(Box::new(e.0));
+                    a.1 &= e.1;
+                    a.2 += e.2;
+                    a
+                });
+            (
+                Expression::Addition(Addition { terms }),
+                terms_simplified,
+                nodes,
+            )
+        }
+        Expression::Negation(negation) => {
+            let (term, term_simplified, term_nodes) = child(0, *negation.term);
+            (
+                Expression::Negation(Negation {
+                    term: Box::new(term),
+                }),
+                term_simplified,
+                1 + term_nodes,
+            )
+        }
+        Expression::Exponentiation(exponentiation) => {
+            let (base, base_simplified, base_nodes) = child(0, *exponentiation.base);
+            let (exponent, exponent_simplified, exponent_nodes) =
+                child(1, *exponentiation.exponent);
+            (
+                Expression::Exponentiation(Exponentiation {
+                    base: Box::new(base),
+                    exponent: Box::new(exponent),
+                }),
+                base_simplified && exponent_simplified,
+                1 + base_nodes + exponent_nodes,
+            )
+        }
+        Expression::Sqrt(sqrt) => {
+            let (arg, arg_simplified, arg_nodes) = child(0, *sqrt.arg);
+            (
+                Expression::Sqrt(Sqrt { arg: Box::new(arg) }),
+                arg_simplified,
+                1 + arg_nodes,
+            )
+        }
+        Expression::Function(function) => {
+            let name = function.name();
+            let (arg, arg_simplified, arg_nodes) = child(0, *function.into_arg());
+            (
+                Expression::Function(Function::from_name(name, Box::new(arg)).unwrap()),
+                arg_simplified,
+                1 + arg_nodes,
+            )
+        }
+        Expression::Number(_) | Expression::Variable(_) => (expression, true, 1),
+    };
+    if budget.reached() {
+        return (expr, simplified, nodes);
+    }
+    let (name, pass) = pass;
+    let (after, self_simplified) = match steps {
+        //only copy the subtree when the steps are being recorded
+        Some(steps) => {
+            let before = expr.clone();
+            let (after, self_simplified) = pass(expr);
+            //rewrites that only change the structure, like -(1) -> -1, print the same and aren't shown
+            if after != before {
+                let step = Step::new(name, path, &before, &after);
+                if step.before != step.after {
+                    steps.push(step);
+                }
             }
-            Expression::Sqrt(sqrt) => {
-                let (arg, arg_simplified) = tree_walk_pass(pass)(*sqrt.arg);
-                (
-                    Expression::Sqrt(Sqrt { arg: Box::new(arg) }),
-                    arg_simplified,
-                )
+            (after, self_simplified)
+        }
+        None => pass(expr),
+    };
+    if self_simplified {
+        return (after, simplified, nodes);
+    }
+    let after_nodes = node_count(&after);
+    budget.rewrote(nodes, after_nodes);
+    (after, false, after_nodes)
+}
+
+//tree_walk_pass over each term on the thread pool, every term records its own steps and they are
+//joined in order afterwards, so the result and the steps are the same as walking them one by one
+//the node count returned is the terms' together
+#[allow(clippy::vec_box)]
+fn parallel_walk(
+    pass: &NamedPass,
+    terms: Vec<Box<Expression>>,
+    subtrees: &Subtrees,
+    path: &[usize],
+    steps: &mut Option<Vec<Step>>,
+    walk: &Walk,
+) -> (Vec<Box<Expression>>, bool, usize) {
+    let recording = steps.is_some();
+    let walked: Vec<(Expression, bool, usize, Option<Vec<Step>>)> = terms
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, term)| {
+            let mut path = [path, &[i]].concat();
+            let mut term_steps = recording.then(Vec::new);
+            let subtrees = &subtrees.children[i];
+            if walk.known_simplified(&term, subtrees) {
+                return (*term, true, subtrees.nodes, term_steps);
             }
-            Expression::Function(function) => match function {
-                Function::Sin(arg) => {
-                    let (arg, arg_simplified) = tree_walk_pass(pass)(*arg);
-                    (
-                        Expression::Function(Function::Sin(Box::new(arg))),
-                        arg_simplified,
-                    )
-                }
-                Function::Cos(arg) => {
-                    let (arg, arg_simplified) = tree_walk_pass(pass)(*arg);
-                    (
-                        Expression::Function(Function::Cos(Box::new(arg))),
-                        arg_simplified,
-                    )
-                }
-                Function::Tan(arg) => {
-                    let (arg, arg_simplified) = tree_walk_pass(pass)(*arg);
-                    (
-                        Expression::Function(Function::Tan(Box::new(arg))),
-                        arg_simplified,
-                    )
-                }
-                Function::Csc(arg) => {
-                    let (arg, arg_simplified) = tree_walk_pass(pass)(*arg);
-                    (
-                        Expression::Function(Function::Csc(Box::new(arg))),
-                        arg_simplified,
-                    )
-                }
-                Function::Sec(arg) => {
-                    let (arg, arg_simplified) = tree_walk_pass(pass)(*arg);
-                    (
-                        Expression::Function(Function::Sec(Box::new(arg))),
-                        arg_simplified,
-                    )
-                }
-                Function::Cot(arg) => {
-                    let (arg, arg_simplified) = tree_walk_pass(pass)(*arg);
-                    (
-                        Expression::Function(Function::Cot(Box::new(arg))),
-                        arg_simplified,
-                    )
-                }
-                Function::Arcsin(arg) => {
-                    let (arg, arg_simplified) = tree_walk_pass(pass)(*arg);
-                    (
-                        Expression::Function(Function::Arcsin(Box::new(arg))),
-                        arg_simplified,
-                    )
-                }
-                Function::Arccos(arg) => {
-                    let (arg, arg_simplified) = tree_walk_pass(pass)(*arg);
-                    (
-                        Expression::Function(Function::Arccos(Box::new(arg))),
-                        arg_simplified,
-                    )
-                }
-                Function::Arctan(arg) => {
-                    let (arg, arg_simplified) = tree_walk_pass(pass)(*arg);
-                    (
-                        Expression::Function(Function::Arctan(Box::new(arg))),
-                        arg_simplified,
-                    )
-                }
-                Function::Arccsc(arg) => {
-                    let (arg, arg_simplified) = tree_walk_pass(pass)(*arg);
-                    (
-                        Expression::Function(Function::Arccsc(Box::new(arg))),
-                        arg_simplified,
-                    )
-                }
-                Function::Arcsec(arg) => {
-                    let (arg, arg_simplified) = tree_walk_pass(pass)(*arg);
-                    (
-                        Expression::Function(Function::Arcsec(Box::new(arg))),
-                        arg_simplified,
-                    )
-                }
-                Function::Arccot(arg) => {
-                    let (arg, arg_simplified) = tree_walk_pass(pass)(*arg);
-                    (
-                        Expression::Function(Function::Arccot(Box::new(arg))),
-                        arg_simplified,
-                    )
-                }
-            },
-            Expression::Number(_) | Expression::Variable(_) => (expression, true),
-        };
-        let (self_expression, self_simplified) = pass(expr);
-        (self_expression, simplified && self_simplified)
+            let (term, simplified, nodes) =
+                tree_walk_pass(pass, *term, subtrees, &mut path, &mut term_steps, walk);
+            (term, simplified, nodes, term_steps)
+        })
+        .collect();
+    let mut terms = Vec::with_capacity(walked.len());
+    let mut simplified = true;
+    let mut nodes = 0;
+    for (term, term_simplified, term_nodes, term_steps) in walked {
+        terms.push(Box::new(term));
+        simplified &= term_simplified;
+        nodes += term_nodes;
+        if let (Some(steps), Some(term_steps)) = (steps.as_mut(), term_steps) {
+            steps.extend(term_steps);
+        }
     }
-}
\ No newline at end of file
+    (terms, simplified, nodes)
+}
@@ -0,0 +1,110 @@
+use crate::{
+    expression::{deep_copy, Addition, Expression, Multiplication},
+    latex::to_latex,
+    passes::names,
+};
+
+// one rewrite made by the simplifier, recorded so the simplification can be shown step by step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    // the name of the pass that made the rewrite
+    pub pass: String,
+    // the child indices leading from the root to the rewritten subtree, empty for the root itself
+    // the numerator, base and function argument are child 0, the denominator and exponent are child 1
+    pub path: Vec<usize>,
+    // the subtree before and after the rewrite as LaTeX
+    pub before: String,
+    pub after: String,
+    // what happened in words, like "distribute 5 over (3 + 5)"
+    pub description: String,
+}
+
+impl Step {
+    pub fn new(pass: &str, path: &[usize], before: &Expression, after: &Expression) -> Step {
+        Step {
+            pass: pass.to_string(),
+            path: path.to_vec(),
+            before: to_latex(before),
+            after: to_latex(after),
+            description: describe(pass, before, after),
+        }
+    }
+}
+
+fn describe(pass: &str, before: &Expression, after: &Expression) -> String {
+    let before_latex = to_latex(before);
+    let after_latex = to_latex(after);
+    match pass {
+        names::DISTRIBUTE => match distributed(after) {
+            Some((others, sum)) => format!("distribute {} over ({})", others, sum),
+            None => format!("distribute {}", before_latex),
+        },
+        names::EXPAND_POWER => format!("expand {}", before_latex),
+        names::COALESCE_MULTIPLICATION => format!("flatten the nested product {}", before_latex),
+        names::COALESCE_ADDITION => format!("flatten the nested sum {}", before_latex),
+        names::NORMALIZE_NEGATION => format!("simplify the signs in {}", before_latex),
+        names::CANCEL_DIVISION => format!("cancel the common factor in {}", before_latex),
+        names::COMBINE_LIKE_FACTORS => match (before, after) {
+            (Expression::Multiplication(b), Expression::Multiplication(a))
+                if a.terms.len() == b.terms.len() =>
+            {
+                format!("reorder the factors of {}", before_latex)
+            }
+            _ => format!("combine like factors in {}", before_latex),
+        },
+        names::COMBINE_LIKE_TERMS => format!("combine like terms in {}", before_latex),
+        names::POWER_OF_POWER => format!("multiply the exponents of {}", before_latex),
+        names::POWER_OF_PRODUCT => format!("raise each factor of {} to the power", before_latex),
+        names::POWER_OF_QUOTIENT => format!(
+            "raise the numerator and denominator of {} to the power",
+            before_latex
+        ),
+        names::ROOT_OF_POWER => format!("take the root of {}", before_latex),
+        names::IDENTITY_EXPONENTS => format!("evaluate the power {}", before_latex),
+        names::EXACT_VALUES => format!("use the exact value of {}", before_latex),
+        names::IMAGINARY_POWERS => format!("use i^2 = -1 in {}", before_latex),
+        names::FACTOR => format!("factor {}", before_latex),
+        names::COLLECT => format!("collect the terms of {} by powers", before_latex),
+        names::RECIPROCAL_TRIG => format!("write {} in terms of sin and cos", before_latex),
+        names::TRIG_PARITY => format!("use the symmetry of {}", before_latex),
+        names::PYTHAGOREAN_IDENTITY => format!("use sin^2 + cos^2 = 1 in {}", before_latex),
+        names::RECIPROCAL_HYPERBOLIC => format!("write {} in terms of sinh and cosh", before_latex),
+        names::HYPERBOLIC_IDENTITY => format!("use cosh^2 - sinh^2 = 1 in {}", before_latex),
+        names::HYPERBOLIC_FORM => format!("write {} with hyperbolic functions", before_latex),
+        names::EXPONENTIAL_FORM => format!("write {} with exponentials", before_latex),
+        _ => format!("rewrite {} as {}", before_latex, after_latex),
+    }
+}
+
+// the factors distributed and the sum they were distributed over, read off the pass's result, where
+// every product has the other factors in the same order followed by one term of the sum
+fn distributed(after: &Expression) -> Option<(String, String)> {
+    let Expression::Addition(addition) = after else {
+        return None;
+    };
+    let products = addition
+        .terms
+        .iter()
+        .map(|term| match &**term {
+            Expression::Multiplication(multiplication) => multiplication.terms.split_last(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let (_, others) = products.first()?;
+    let others = match others {
+        [other] => to_latex(other),
+        _ => to_latex(&Expression::Multiplication(Multiplication {
+            terms: others
+                .iter()
+                .map(|other| Box::new(deep_copy(other)))
+                .collect(),
+        })),
+    };
+    let sum = Expression::Addition(Addition {
+        terms: products
+            .iter()
+            .map(|(term, _)| Box::new(deep_copy(term)))
+            .collect(),
+    });
+    Some((others, to_latex(&sum)))
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeSet;
+
+use crate::expression::{
+    Addition, Division, Exponentiation, Expression, Function, Multiplication, Negation, Sqrt,
+};
+
+// the expression with every `variable` replaced by `replacement`
+pub fn substitute(expression: &Expression, variable: &str, replacement: &Expression) -> Expression {
+    substitute_all(expression, &[(variable.to_string(), replacement.clone())])
+}
+
+// replaces every variable at once, so x -> y, y -> x swaps them rather than making both x
+// nothing binds a variable yet, when sums or integrals do their bound variable has to be left alone
+// and renamed if a replacement uses it
+pub fn substitute_all(
+    expression: &Expression,
+    substitutions: &[(String, Expression)],
+) -> Expression {
+    substitute_with(expression, substitutions, &|expression| expression)
+}
+
+// substitute_all, with `rebuilt` applied to every node of `expression` above a variable or number
+// after its children are substituted, the rule engine uses it to work out the numbers in a replacement
+pub fn substitute_with(
+    expression: &Expression,
+    substitutions: &[(String, Expression)],
+    rebuilt: &dyn Fn(Expression) -> Expression,
+) -> Expression {
+    let terms = |terms: &Vec<Box<Expression>>| {
+        terms
+            .iter()
+            .map(|term| Box::new(substitute_with(term, substitutions, rebuilt)))
+            .collect()
+    };
+    let child =
+        |expression: &Expression| Box::new(substitute_with(expression, substitutions, rebuilt));
+    let substituted = match expression {
+        Expression::Variable(variable) => {
+            return match substitutions.iter().find(|(name, _)| name == variable) {
+                Some((_, replacement)) => replacement.clone(),
+                None => expression.clone(),
+            };
+        }
+        Expression::Number(_) => return expression.clone(),
+        Expression::Addition(addition) => Expression::Addition(Addition {
+            terms: terms(&addition.terms),
+        }),
+        Expression::Multiplication(multiplication) => Expression::Multiplication(Multiplication {
+            terms: terms(&multiplication.terms),
+        }),
+        Expression::Division(division) => Expression::Division(Division {
+            numerator: child(&division.numerator),
+            denominator: child(&division.denominator),
+        }),
+        Expression::Negation(negation) => Expression::Negation(Negation {
+            term: child(&negation.term),
+        }),
+        Expression::Exponentiation(exponentiation) => Expression::Exponentiation(Exponentiation {
+            base: child(&exponentiation.base),
+            exponent: child(&exponentiation.exponent),
+        }),
+        Expression::Sqrt(sqrt) => Expression::Sqrt(Sqrt {
+            arg: child(&sqrt.arg),
+        }),
+        Expression::Function(function) => Expression::Function(
+            Function::from_name(function.name(), child(function.arg())).unwrap(),
+        ),
+    };
+    rebuilt(substituted)
+}
+
+pub fn rename(expression: &Expression, from: &str, to: &str) -> Expression {
+    substitute(expression, from, &Expression::Variable(to.to_string()))
+}
+
+// the variables an expression depends on, in order
+pub fn free_variables(expression: &Expression) -> BTreeSet<String> {
+    let mut variables = BTreeSet::new();
+    collect_variables(expression, &mut variables);
+    variables
+}
+
+fn collect_variables(expression: &Expression, variables: &mut BTreeSet<String>) {
+    match expression {
+        Expression::Variable(variable) => {
+            variables.insert(variable.clone());
+        }
+        _ => expression
+            .children()
+            .into_iter()
+            .for_each(|child| collect_variables(child, variables)),
+    }
+}
@@ -0,0 +1,105 @@
+// rewrites that are only sound for some values of their variables, and the assumptions that allow them
+mod common;
+
+use common::expression;
+use rustic_math::{simplify_with_options, Backend, Expression, SimplifyOptions};
+
+// input simplified with each of the assumptions, like "x > 0"
+fn simplify(input: &str, assumptions: &[&str]) -> Expression {
+    let mut options = SimplifyOptions::default();
+    for assumption in assumptions {
+        options
+            .assumptions
+            .assume(assumption.parse().unwrap())
+            .unwrap();
+    }
+    simplify_with_options(expression(input), &options)
+}
+
+fn assert_simplifies(input: &str, assumptions: &[&str], expected: &str) {
+    assert_eq!(
+        simplify(input, assumptions),
+        simplify(expected, &[]),
+        "{} assuming {:?}",
+        input,
+        assumptions
+    );
+}
+
+// nothing that changes the value for some x is done, so the input comes back as it was written
+fn assert_unchanged(input: &str, assumptions: &[&str]) {
+    let without_rules = SimplifyOptions {
+        rules: Vec::new(),
+        ..SimplifyOptions::default()
+    };
+    let unchanged = simplify_with_options(expression(input), &without_rules);
+    assert_eq!(
+        simplify(input, assumptions),
+        unchanged,
+        "{} assuming {:?}",
+        input,
+        assumptions
+    );
+}
+
+#[test]
+fn square_root_of_a_square() {
+    // sqrt(x^2) is |x|
+    assert_unchanged("sqrt(x^2)", &[]);
+    assert_unchanged("sqrt(x^2)", &["x is integer"]);
+    assert_unchanged("sqrt(x^2)", &["y > 0"]);
+    assert_simplifies("sqrt(x^2)", &["x >= 0"], "x");
+    assert_simplifies("sqrt(x^2)", &["x > 0"], "x");
+    assert_simplifies("sqrt(x^2)", &["x <= 0"], "0 - x");
+    assert_simplifies("sqrt((x + 1)^2)", &["x > 0"], "x + 1");
+    // an even power is left whatever the sign
+    assert_simplifies("sqrt(x^4)", &[], "x^2");
+}
+
+#[test]
+fn power_of_a_power() {
+    // an integer outer exponent is always fine
+    assert_simplifies("(x^3)^2", &[], "x^6");
+    assert_simplifies("(x^(1/3))^3", &[], "x");
+    // so is an even product of an integer inner exponent, the sign is lost either way
+    assert_simplifies("(x^4)^(1/2)", &[], "x^2");
+    assert_simplifies("(x^6)^(1/3)", &[], "x^2");
+    // but (x^2)^(1/2) is |x| and (x^(2/3))^(3/2) is |x| as well
+    assert_unchanged("(x^2)^(1/2)", &[]);
+    assert_unchanged("(x^(2/3))^(3/2)", &[]);
+    assert_unchanged("(x^(2/3))^(3/2)", &["x is integer"]);
+    assert_simplifies("(x^2)^(1/2)", &["x >= 0"], "x");
+    assert_simplifies("(x^(2/3))^(3/2)", &["x > 0"], "x");
+}
+
+#[test]
+fn powers_of_products_and_quotients() {
+    assert_unchanged("(x * y)^(1/2)", &[]);
+    // every factor has to be nonnegative, not just one
+    assert_unchanged("(x * y)^(1/2)", &["x > 0"]);
+    assert_simplifies("(x * y)^(1/2)", &["x > 0", "y >= 0"], "sqrt(x) * sqrt(y)");
+    assert_simplifies("(x / y)^(1/2)", &["x > 0", "y > 0"], "sqrt(x) / sqrt(y)");
+    assert_simplifies("(x * y)^3", &[], "x^3 * y^3");
+}
+
+#[test]
+fn the_egraph_uses_them_too() {
+    for input in ["sqrt(x^2)", "(x^2)^(1/2)", "(x^(1/2))^2"] {
+        let mut options = SimplifyOptions {
+            backend: Backend::EGraph,
+            ..SimplifyOptions::default()
+        };
+        let unchanged = simplify_with_options(expression(input), &options);
+        assert_eq!(unchanged, simplify(input, &[]), "{}", input);
+        options
+            .assumptions
+            .assume("x > 0".parse().unwrap())
+            .unwrap();
+        assert_eq!(
+            simplify_with_options(expression(input), &options),
+            expression("x"),
+            "{} assuming x > 0",
+            input
+        );
+    }
+}
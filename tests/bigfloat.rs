@@ -0,0 +1,211 @@
+// arbitrary precision evaluation, against reference values and against eval
+mod common;
+
+use common::expression;
+use rustic_math::{
+    check_digits, eval_big, eval_big_with, eval_in, eval_with, with_precision, AngleMode, BigFloat,
+    Environment, MAX_DIGITS,
+};
+
+fn digits(input: &str, digits: u32, angle_mode: AngleMode) -> String {
+    eval_big(&expression(input), digits, angle_mode)
+        .unwrap_or_else(|err| panic!("{} failed with {}", input, err))
+        .to_digits(digits)
+}
+
+#[test]
+fn reference_values() {
+    for (input, expected) in [
+        ("pi", "3.141592653589793238462643383279502884197169399375105820974944592307816406286208998628034825342117068"),
+        ("sqrt(2)", "1.414213562373095048801688724209698078569671875376948073176679737990732478462107038850387534327641573"),
+        ("sin(1)", "0.8414709848078965066525023216302989996225630607983710656727517099919104043912396689486397435430526959"),
+        ("e", "2.718281828459045235360287471352662497757247093699959574966967627724076630353547594571382178525166427"),
+    ] {
+        assert_eq!(digits(input, 100, AngleMode::Radians), expected, "{}", input);
+    }
+    assert_eq!(
+        digits("e^pi", 40, AngleMode::Radians),
+        "23.14069263277926900572908636794854738027"
+    );
+    assert_eq!(
+        digits("3 * 2^0.5", 40, AngleMode::Radians),
+        "4.242640687119285146405066172629094235709"
+    );
+    // reduced with enough of pi for every bit of the argument
+    assert_eq!(
+        digits("sin(10^22)", 40, AngleMode::Radians),
+        "-0.8522008497671888017727058937530293682618"
+    );
+}
+
+#[test]
+fn every_function() {
+    for (input, expected) in [
+        ("sin(0.7)", "0.6442176872376910536726143513987201830658"),
+        ("cos(0.7)", "0.7648421872844884262558599901918649092682"),
+        ("tan(0.7)", "0.8422883804630794481281350022129377171872"),
+        ("csc(0.7)", "1.552270326957103911989973640954927830636"),
+        ("sec(0.7)", "1.307459259733593869874672835305311454228"),
+        ("cot(0.7)", "1.1872418321266793536723626936911574421"),
+        ("arcsin(0.7)", "0.7753974966107530637403533527149871135558"),
+        ("arccos(0.7)", "0.7953988301841435554909683389247643285428"),
+        ("arctan(0.7)", "0.6107259643892086165437588764902360938185"),
+        ("arccsc(1.7)", "0.6288749254950517743404916501449976035207"),
+        ("arcsec(1.7)", "0.9419214012998448448908300414947538385779"),
+        ("arccot(0.7)", "0.9600703624056880026875628151495153482801"),
+        ("sinh(0.7)", "0.7585837018395335034598746475927681541549"),
+        ("cosh(0.7)", "1.255169005630943018164674740990297115863"),
+        ("tanh(0.7)", "0.6043677771171634963086871831038264750156"),
+        ("csch(0.7)", "1.31824609146629719165164762154575013838"),
+        ("sech(0.7)", "0.7967054599928750252113020491821918288424"),
+        ("coth(0.7)", "1.654621635802629404713224280805494729354"),
+        ("arcsinh(0.7)", "0.6526665660823557868086863441096758974424"),
+        ("arccosh(1.7)", "1.123230982587295889531145796227985407213"),
+        ("arctanh(0.7)", "0.8673005276940531944271446904753004154704"),
+        ("arccsch(0.7)", "1.154477394237068449033699024437752985214"),
+        ("arcsech(0.7)", "0.8955880995299758224096299146133231717037"),
+        ("arccoth(1.7)", "0.6749633584745078845401914836621986921527"),
+    ] {
+        assert_eq!(digits(input, 40, AngleMode::Radians), expected, "{}", input);
+    }
+}
+
+#[test]
+fn agrees_with_eval() {
+    let inputs = [
+        "sin(2) * cos(3) - tan(0.4)",
+        "arcsin(0.3) + arccos(-0.2) / arctan(5)",
+        "sinh(1.5) - cosh(0.5) * tanh(2)",
+        "arcsinh(3) + arccosh(2) - arctanh(0.5)",
+        "sqrt(7) ^ 1.3 / (2 + 1 / 3)",
+        "(-2)^5 + 30° - e^(-3)",
+        "sin(0.00001) / 0.00001",
+    ];
+    for angle_mode in [AngleMode::Radians, AngleMode::Degrees, AngleMode::Gradians] {
+        for input in inputs {
+            let expected = eval_in(&expression(input), angle_mode).unwrap();
+            let actual = eval_big(&expression(input), 30, angle_mode)
+                .unwrap()
+                .to_f64();
+            assert!(
+                (actual - expected).abs() <= 1e-14 * expected.abs().max(1.0),
+                "{} in {} was {}, eval gave {}",
+                input,
+                angle_mode,
+                actual,
+                expected
+            );
+        }
+    }
+    let mut environment = Environment::new();
+    environment.set("x", 0.1).unwrap();
+    environment
+        .set_expression("y", expression("x * 3"))
+        .unwrap();
+    // the numbers are the decimals they were written as, not the f64s nearest them
+    assert_eq!(
+        eval_big_with(&expression("y"), 50, &environment)
+            .unwrap()
+            .to_digits(50),
+        "0.3"
+    );
+    assert_eq!(
+        eval_big_with(&expression("y / 7"), 30, &environment)
+            .unwrap()
+            .to_digits(30),
+        "0.0428571428571428571428571428571"
+    );
+}
+
+#[test]
+fn exact_angles() {
+    assert_eq!(digits("sin(pi)", 50, AngleMode::Radians), "0");
+    assert_eq!(digits("cos(pi / 2)", 50, AngleMode::Radians), "0");
+    assert_eq!(digits("sin(180)", 50, AngleMode::Degrees), "0");
+    assert_eq!(digits("cos(300)", 50, AngleMode::Gradians), "0");
+    assert_eq!(digits("sin(30)", 50, AngleMode::Degrees), "0.5");
+    assert_eq!(digits("arcsin(0.5)", 50, AngleMode::Degrees), "30");
+    assert_eq!(digits("90°", 50, AngleMode::Gradians), "100");
+    // not snapped, just close enough to round to the exact value
+    assert_eq!(digits("tan(pi / 4)", 50, AngleMode::Radians), "1");
+    assert_eq!(digits("cos(pi / 3)", 50, AngleMode::Radians), "0.5");
+}
+
+#[test]
+fn errors_are_evals() {
+    let environment = Environment::new();
+    for input in [
+        "1 / (2 - 2)",
+        "sqrt(0 - 1)",
+        "arcsin(2)",
+        "csc(0)",
+        "tan(pi / 2)",
+        "0^(0 - 1)",
+        "(0 - 8)^(1 / 3)",
+        "x + 1",
+        "i",
+    ] {
+        let expected = eval_with(&expression(input), &environment).unwrap_err();
+        assert_eq!(
+            eval_big_with(&expression(input), 50, &environment).unwrap_err(),
+            expected,
+            "{}",
+            input
+        );
+    }
+    // past the largest f64 isn't past the largest BigFloat
+    assert!(eval_with(&expression("10^400"), &environment).is_err());
+    assert_eq!(digits("10^400 + 1", 5, AngleMode::Radians), "1e400");
+}
+
+#[test]
+fn arithmetic_and_formatting() {
+    assert_eq!(
+        digits("1 / 3", 30, AngleMode::Radians),
+        "0.333333333333333333333333333333"
+    );
+    assert_eq!(digits("0.1 + 0.2", 30, AngleMode::Radians), "0.3");
+    assert_eq!(
+        digits("2^100", 40, AngleMode::Radians),
+        "1267650600228229401496703205376"
+    );
+    assert_eq!(digits("2^100", 10, AngleMode::Radians), "1.2676506e30");
+    assert_eq!(digits("2^(0 - 30)", 5, AngleMode::Radians), "9.3132e-10");
+    assert_eq!(digits("0.00012345", 10, AngleMode::Radians), "0.00012345");
+    assert_eq!(digits("0 - 123.456", 4, AngleMode::Radians), "-123.5");
+    let third = with_precision(200, || {
+        "1".parse::<BigFloat>().unwrap() / "3".parse::<BigFloat>().unwrap()
+    });
+    assert_eq!(
+        third.to_digits(60),
+        "0.333333333333333333333333333333333333333333333333333333333333"
+    );
+    // displaying a value that takes only a few bits still shows every digit of it
+    for (input, angle_mode, expected) in [
+        (
+            "2^100",
+            AngleMode::Radians,
+            "1267650600228229401496703205376",
+        ),
+        ("512", AngleMode::Radians, "512"),
+        ("(0 - 8)^3", AngleMode::Radians, "-512"),
+        ("arcsin(0.5)", AngleMode::Degrees, "30"),
+    ] {
+        let displayed = with_precision(200, || {
+            eval_big(&expression(input), 40, angle_mode)
+                .unwrap()
+                .to_string()
+        });
+        assert_eq!(displayed, expected, "{}", input);
+    }
+    assert_eq!("-1.5e-3".parse::<BigFloat>().unwrap().to_f64(), -0.0015);
+    assert!("1.2.3".parse::<BigFloat>().is_err());
+}
+
+#[test]
+fn digit_bounds() {
+    assert_eq!(check_digits(1), Ok(1));
+    assert_eq!(check_digits(MAX_DIGITS), Ok(MAX_DIGITS));
+    assert!(check_digits(0).is_err());
+    assert!(check_digits(MAX_DIGITS + 1).is_err());
+}
@@ -0,0 +1,100 @@
+// compiled programs have to give exactly what eval gives, errors included
+mod common;
+
+use common::expression;
+use rustic_math::{compile, eval_with, AngleMode, Environment, Instruction};
+
+fn points() -> Vec<(f64, f64)> {
+    let values = [-3.0, -1.0, -0.5, 0.0, 0.25, 1.0, 2.0, 3.7, 90.0, 1e300];
+    values
+        .iter()
+        .flat_map(|&x| values.iter().map(move |&y| (x, y)))
+        .collect()
+}
+
+#[test]
+fn identical_to_eval() {
+    let inputs = [
+        "x + y * 2 - 1",
+        "x / y",
+        "x^y",
+        "sqrt(x) + sqrt(y)",
+        "sin(x) * cos(y) + tan(x / y)",
+        "arcsin(x) + arccosh(y)",
+        "csch(x) - coth(y)",
+        "(x + 0.1 + 0.2) * (y - 0.3)",
+        "-x",
+        "x",
+        "2^10 + x",
+        "e^(x * y)",
+        "x * i",
+        "x + z",
+        "1 / (1 - 1) + x",
+    ];
+    for angle_mode in [AngleMode::Radians, AngleMode::Degrees] {
+        for input in inputs {
+            let expr = expression(input);
+            let program = compile(&expr, &["x", "y"], angle_mode);
+            let mut environment = Environment::new();
+            environment.angle_mode = angle_mode;
+            for (x, y) in points() {
+                environment.set("x", x).unwrap();
+                environment.set("y", y).unwrap();
+                let expected = eval_with(&expr, &environment);
+                let actual = program.eval(&[x, y]);
+                // compared as bits so -0 and 0 differ
+                assert_eq!(
+                    actual.clone().map(f64::to_bits),
+                    expected.clone().map(f64::to_bits),
+                    "{} at x = {}, y = {}: {:?} and {:?}",
+                    input,
+                    x,
+                    y,
+                    actual,
+                    expected
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn batches() {
+    let expr = expression("sqrt(x) / y");
+    let program = compile(&expr, &["x", "y"], AngleMode::Radians);
+    let points: Vec<f64> = points().into_iter().flat_map(|(x, y)| [x, y]).collect();
+    let mut output = vec![0.0; points.len() / 2];
+    program.eval_batch(&points, &mut output);
+    for (i, value) in output.iter().enumerate() {
+        match program.eval(&points[2 * i..2 * i + 2]) {
+            Ok(expected) => assert_eq!(value.to_bits(), expected.to_bits()),
+            Err(_) => assert!(value.is_nan()),
+        }
+    }
+    let constant = compile(&expression("2 + 3"), &[], AngleMode::Radians);
+    let mut output = [0.0; 3];
+    constant.eval_batch(&[], &mut output);
+    assert_eq!(output, [5.0; 3]);
+}
+
+#[test]
+fn folds_constants() {
+    let program = compile(
+        &expression("x * (2 + 3) + sin(pi / 6)"),
+        &["x"],
+        AngleMode::Radians,
+    );
+    assert_eq!(
+        program.instructions(),
+        &[
+            Instruction::Load(0),
+            Instruction::Constant(5.0),
+            Instruction::Multiply(2),
+            Instruction::Constant(0.5),
+            Instruction::Add(2)
+        ]
+    );
+    let failing = compile(&expression("x + 1 / 0"), &["x"], AngleMode::Radians);
+    assert_eq!(failing.instructions()[1], Instruction::Fail);
+    assert_eq!(failing.eval(&[1.0]).unwrap_err().path(), &[1]);
+}
@@ -0,0 +1,69 @@
+// the cache of simplified subtrees
+mod common;
+
+use common::expression;
+use rustic_math::{simplify_with_options, SimplifyCache, SimplifyOptions};
+
+#[test]
+fn colliding_keys_miss() {
+    let mut cache = SimplifyCache::new(10);
+    cache.insert(1, expression("x + x"), expression("2 * x"));
+    cache.insert(2, expression("y ^ 2"), expression("y ^ 2"));
+    assert_eq!(
+        cache.get(1, &expression("x + x")),
+        Some(expression("2 * x"))
+    );
+    assert_eq!(
+        cache.get(2, &expression("y ^ 2")),
+        Some(expression("y ^ 2"))
+    );
+    // the same key for another expression, as two expressions with the same hash would have
+    assert_eq!(cache.get(1, &expression("x * x")), None);
+    assert_eq!(cache.get(3, &expression("x + x")), None);
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (2, 2, 2));
+}
+
+#[test]
+fn the_least_recently_used_entry_goes_first() {
+    let mut cache = SimplifyCache::new(2);
+    cache.insert(1, expression("x + x"), expression("2 * x"));
+    cache.insert(2, expression("y * y"), expression("y ^ 2"));
+    // using the first entry makes the second the older one
+    assert_eq!(
+        cache.get(1, &expression("x + x")),
+        Some(expression("2 * x"))
+    );
+    cache.insert(3, expression("z - z"), expression("0"));
+    assert_eq!(cache.get(2, &expression("y * y")), None);
+    assert_eq!(
+        cache.get(1, &expression("x + x")),
+        Some(expression("2 * x"))
+    );
+    assert_eq!(cache.get(3, &expression("z - z")), Some(expression("0")));
+    assert_eq!(cache.stats().entries, 2);
+}
+
+#[test]
+fn misses_are_counted_when_the_work_is_done() {
+    let mut cache = SimplifyCache::new(10);
+    assert_eq!(cache.get(1, &expression("x + x")), None);
+    assert_eq!(cache.stats().misses, 0);
+    cache.insert(1, expression("x + x"), expression("2 * x"));
+    assert_eq!(cache.stats().misses, 1);
+
+    // simplifying again finds the result and works nothing out
+    let shared = SimplifyCache::shared(1000);
+    let options = SimplifyOptions {
+        cache: Some(shared.clone()),
+        ..SimplifyOptions::default()
+    };
+    let input = "(x + 1) * (x + 2) + sin(x)^2 + cos(x)^2";
+    let first = simplify_with_options(expression(input), &options);
+    let cold = shared.lock().unwrap().stats();
+    assert!(cold.misses > 0);
+    assert_eq!(simplify_with_options(expression(input), &options), first);
+    let warm = shared.lock().unwrap().stats();
+    assert_eq!(warm.misses, cold.misses);
+    assert_eq!(warm.hits, cold.hits + 1);
+}
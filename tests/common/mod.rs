@@ -0,0 +1,14 @@
+// helpers shared by the integration tests
+use rustic_math::{parse, tokenize, Expression};
+
+// the one expression input parses to
+pub fn expression(input: &str) -> Expression {
+    let expressions = parse(tokenize(input.to_string()));
+    assert_eq!(
+        expressions.len(),
+        1,
+        "{} should parse to one expression",
+        input
+    );
+    expressions.into_iter().next().unwrap()
+}
@@ -0,0 +1,211 @@
+// complex evaluation on principal branches, against values from the complex definitions
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use rustic_math::{
+    complex_to_latex, eval_complex, eval_in, format_complex, parse, simplify_expression, to_latex,
+    tokenize, AngleMode, Complex, ComplexForm, EvalError,
+};
+
+fn evaluate(input: &str, angle_mode: AngleMode) -> Result<Complex, EvalError> {
+    let expressions = parse(tokenize(input.to_string()));
+    assert_eq!(
+        expressions.len(),
+        1,
+        "{} should parse to one expression",
+        input
+    );
+    eval_complex(&expressions[0], angle_mode)
+}
+
+fn assert_value(input: &str, re: f64, im: f64) {
+    match evaluate(input, AngleMode::Radians) {
+        Ok(value) => assert!(
+            (value.re - re).abs() <= 1e-12 * re.abs().max(1.0)
+                && (value.im - im).abs() <= 1e-12 * im.abs().max(1.0),
+            "{} was {}, expected {} + {}i",
+            input,
+            value,
+            re,
+            im
+        ),
+        Err(err) => panic!("{} failed with {}, expected {} + {}i", input, err, re, im),
+    }
+}
+
+fn assert_exact(input: &str, re: f64, im: f64) {
+    assert_eq!(
+        evaluate(input, AngleMode::Radians),
+        Ok(Complex::new(re, im)),
+        "{}",
+        input
+    );
+}
+
+fn simplified(input: &str) -> String {
+    to_latex(&simplify_expression(
+        parse(tokenize(input.to_string())).remove(0),
+    ))
+}
+
+#[test]
+fn literals() {
+    assert_exact("i", 0.0, 1.0);
+    assert_exact("3 + 4i", 3.0, 4.0);
+    assert_exact("2.5i - 1", -1.0, 2.5);
+    assert_eq!(simplified("3 + 4i"), "3 + 4i");
+    assert_eq!(simplified("2i/3"), "\\frac{2i}{3}");
+}
+
+#[test]
+fn arithmetic() {
+    assert_exact("(1 + 2i) * (3 - i)", 5.0, 5.0);
+    assert_exact("i^2", -1.0, 0.0);
+    assert_value("1 / (3 + 4i)", 0.12, -0.16);
+    assert!(matches!(
+        evaluate("1 / (0i)", AngleMode::Radians),
+        Err(EvalError::DivisionByZero { .. })
+    ));
+}
+
+#[test]
+fn powers_and_roots() {
+    assert_exact("sqrt(-4)", 0.0, 2.0);
+    assert_exact("(-1)^(1/2)", 0.0, 1.0);
+    assert_exact("e^(i*pi)", -1.0, 0.0);
+    assert_value("(-8)^(1/3)", 1.0, 3.0f64.sqrt());
+    assert_value("2^(1 + i)", 1.5384778027279442, 1.2779225526272695);
+    assert_value("i^i", (-FRAC_PI_2).exp(), 0.0);
+    assert_value("(1 + i)^(1/2)", 1.0986841134678098, 0.45508986056222733);
+}
+
+#[test]
+fn principal_branches() {
+    assert_value("sin(1 + i)", 1.2984575814159773, 0.6349639147847361);
+    assert_value("cos(1 + i)", 0.8337300251311491, -0.9888977057628651);
+    assert_value("arcsin(2)", FRAC_PI_2, -(2.0 + 3.0f64.sqrt()).ln());
+    assert_value("arccos(2)", 0.0, (2.0 + 3.0f64.sqrt()).ln());
+    assert_value("arccosh(0)", 0.0, FRAC_PI_2);
+    assert_value("arctanh(2)", 0.5493061443340548, FRAC_PI_2);
+    assert_exact("sinh(i*pi/2)", 0.0, 1.0);
+    assert_exact("cosh(i*pi)", -1.0, 0.0);
+}
+
+#[test]
+fn far_from_the_real_line() {
+    // sin and cos overflow long before their ratio gets anywhere near it
+    assert_value("tan(1000*i)", 0.0, 1.0);
+    assert_value("tan(-1000*i)", 0.0, -1.0);
+    assert_value("cot(1000*i)", 0.0, -1.0);
+    assert_value("tan(2 + 400*i)", 0.0, 1.0);
+    assert_value("cot(2 - 400*i)", 0.0, 1.0);
+    assert_value("tanh(1000 + i)", 1.0, 0.0);
+    assert_value("coth(-1000 + i)", -1.0, 0.0);
+    // and nearer it they still match the complex definitions
+    assert_value("tan(1 + i)", 0.2717525853195118, 1.0839233273386946);
+    assert_value("cot(1 + i)", 0.21762156185440268, -0.8680141428959249);
+    assert_value("tan(1 + 30*i)", 2.3968964977567e-26, 1.0);
+    assert_value("tanh(1 + i)", 1.0839233273386946, 0.2717525853195118);
+    assert_value("coth(1 + i)", 0.8680141428959249, -0.21762156185440268);
+}
+
+#[test]
+fn real_arguments_match_real_evaluation() {
+    for input in [
+        "sin(pi/6)",
+        "arccsc(2)",
+        "tanh(1)",
+        "arcsec(-2)",
+        "sqrt(2)",
+        "2^10",
+    ] {
+        let real = eval_in(&parse(tokenize(input.to_string()))[0], AngleMode::Radians).unwrap();
+        assert_eq!(
+            evaluate(input, AngleMode::Radians),
+            Ok(Complex::from(real)),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn poles() {
+    for (input, function) in [
+        ("tan(pi/2)", "tan"),
+        ("csch(i*pi)", "csch"),
+        ("arctan(i)", "arctan"),
+        ("arctanh(1)", "arctanh"),
+    ] {
+        match evaluate(input, AngleMode::Radians) {
+            Err(EvalError::DomainError {
+                function: failed, ..
+            }) => assert_eq!(failed, function, "{}", input),
+            result => panic!(
+                "{} gave {:?}, expected {} to be undefined",
+                input, result, function
+            ),
+        }
+    }
+}
+
+#[test]
+fn imaginary_unit_is_not_real() {
+    assert!(matches!(
+        eval_in(&parse(tokenize("i".to_string()))[0], AngleMode::Radians),
+        Err(EvalError::NotReal { .. })
+    ));
+}
+
+#[test]
+fn simplifying_powers_of_i() {
+    assert_eq!(simplified("i*i"), "-1");
+    assert_eq!(simplified("i^3"), "-i");
+    assert_eq!(simplified("i^4 * x"), "x");
+    assert_eq!(simplified("1/i"), "-i");
+    assert_eq!(simplified("(3 + 4i) * (3 - 4i)"), "25");
+}
+
+#[test]
+fn forms() {
+    let z = Complex::new(3.0, -4.0);
+    assert_eq!(
+        format_complex(z, ComplexForm::Rectangular, AngleMode::Radians),
+        "3 - 4i"
+    );
+    assert_eq!(
+        format_complex(
+            Complex::new(0.0, -1.0),
+            ComplexForm::Rectangular,
+            AngleMode::Radians
+        ),
+        "-i"
+    );
+    assert_eq!(
+        format_complex(
+            Complex::new(-1.0, 0.0),
+            ComplexForm::Polar,
+            AngleMode::Degrees
+        ),
+        "1 ∠ 180°"
+    );
+    assert_eq!(
+        complex_to_latex(z, ComplexForm::Rectangular, AngleMode::Radians),
+        "3 - 4i"
+    );
+    assert_eq!(
+        complex_to_latex(
+            Complex::new(-1.0, 0.0),
+            ComplexForm::Polar,
+            AngleMode::Radians
+        ),
+        "1 \\angle \\pi"
+    );
+    assert_eq!(
+        format!("{}", PI),
+        format_complex(
+            Complex::from(PI),
+            ComplexForm::Rectangular,
+            AngleMode::Radians
+        )
+    );
+}
@@ -0,0 +1,203 @@
+// derivatives from dual numbers, against exact ones and against finite differences of eval
+mod common;
+
+use common::expression;
+use rustic_math::{eval_with, gradient, hessian, AngleMode, Environment, EvalError};
+
+fn at(values: &[(&str, f64)], angle_mode: AngleMode) -> Environment {
+    let mut environment = Environment::new();
+    environment.angle_mode = angle_mode;
+    for (name, value) in values {
+        environment.set(name, *value).unwrap();
+    }
+    environment
+}
+
+fn close(actual: f64, expected: f64, tolerance: f64) -> bool {
+    (actual - expected).abs() <= tolerance * expected.abs().max(1.0)
+}
+
+// the first and second derivatives in x against central differences of eval
+fn assert_matches_differences(input: &str, x: f64, angle_mode: AngleMode) {
+    let expr = expression(input);
+    let f = |x: f64| eval_with(&expr, &at(&[("x", x)], angle_mode)).unwrap();
+    let h = 1e-4 * x.abs().max(1.0);
+    let first = (f(x + h) - f(x - h)) / (2.0 * h);
+    let second = (f(x + h) - 2.0 * f(x) + f(x - h)) / (h * h);
+    let derivatives = hessian(&expr, &["x"], &at(&[("x", x)], angle_mode))
+        .unwrap_or_else(|err| panic!("{} at {} failed with {}", input, x, err));
+    assert_eq!(derivatives.value, f(x), "{} at {}", input, x);
+    assert!(
+        close(derivatives.gradient[0], first, 1e-6),
+        "{}' at {} in {} was {}, expected {}",
+        input,
+        x,
+        angle_mode,
+        derivatives.gradient[0],
+        first
+    );
+    assert!(
+        close(derivatives.hessian[0][0], second, 1e-4),
+        "{}'' at {} in {} was {}, expected {}",
+        input,
+        x,
+        angle_mode,
+        derivatives.hessian[0][0],
+        second
+    );
+}
+
+#[test]
+fn polynomials_are_exact() {
+    let dual = gradient(
+        &expression("x^2 * y + 3 * y"),
+        &["x", "y"],
+        &at(&[("x", 2.0), ("y", 5.0)], AngleMode::Radians),
+    )
+    .unwrap();
+    assert_eq!(dual.value, 35.0);
+    assert_eq!(dual.derivatives, vec![20.0, 7.0]);
+    let hyper_dual = hessian(
+        &expression("x^3 * y - y^2 / x"),
+        &["x", "y"],
+        &at(&[("x", 1.0), ("y", 2.0)], AngleMode::Radians),
+    )
+    .unwrap();
+    assert_eq!(hyper_dual.value, -2.0);
+    assert_eq!(hyper_dual.gradient, vec![10.0, -3.0]);
+    assert_eq!(hyper_dual.hessian, vec![vec![4.0, 7.0], vec![7.0, -2.0]]);
+}
+
+#[test]
+fn selected_variables_only() {
+    let environment = at(&[("x", 3.0), ("a", 2.0)], AngleMode::Radians);
+    let dual = gradient(&expression("a * x^2"), &["x"], &environment).unwrap();
+    assert_eq!(dual.derivatives, vec![12.0]);
+    // a constant expression still has a derivative for every variable
+    assert_eq!(
+        gradient(&expression("a + 1"), &["x", "y"], &environment)
+            .unwrap()
+            .derivatives,
+        vec![0.0, 0.0]
+    );
+}
+
+#[test]
+fn bindings_follow_the_variables() {
+    let mut environment = at(&[("x", 3.0)], AngleMode::Radians);
+    environment.set_expression("y", expression("x^2")).unwrap();
+    let dual = gradient(&expression("y + x"), &["x"], &environment).unwrap();
+    assert_eq!((dual.value, dual.derivatives), (12.0, vec![7.0]));
+}
+
+#[test]
+fn every_function() {
+    for (function, points) in [
+        ("sin", &[0.3, -2.0, 5.0][..]),
+        ("cos", &[0.3, -2.0, 5.0]),
+        ("tan", &[0.3, -1.2]),
+        ("csc", &[0.3, -2.0]),
+        ("sec", &[0.3, 2.0]),
+        ("cot", &[0.3, -2.0]),
+        ("arcsin", &[0.3, -0.7]),
+        ("arccos", &[0.3, -0.7]),
+        ("arctan", &[0.3, -4.0]),
+        ("arccsc", &[1.5, -3.0]),
+        ("arcsec", &[1.5, -3.0]),
+        ("arccot", &[0.5, -3.0]),
+        ("sinh", &[0.3, -2.0]),
+        ("cosh", &[0.3, -2.0]),
+        ("tanh", &[0.3, -2.0]),
+        ("csch", &[0.3, -2.0]),
+        ("sech", &[0.3, -2.0]),
+        ("coth", &[0.3, -2.0]),
+        ("arcsinh", &[0.3, -2.0]),
+        ("arccosh", &[1.5, 4.0]),
+        ("arctanh", &[0.3, -0.6]),
+        ("arccsch", &[0.3, -2.0]),
+        ("arcsech", &[0.3, 0.8]),
+        ("arccoth", &[1.5, -3.0]),
+    ] {
+        for &x in points {
+            assert_matches_differences(&format!("{}(x)", function), x, AngleMode::Radians);
+        }
+    }
+    for input in ["sqrt(x)", "x^x", "2^x", "e^(x^2) / x", "(x + 1)^3"] {
+        assert_matches_differences(input, 1.3, AngleMode::Radians);
+    }
+}
+
+#[test]
+fn angle_modes() {
+    for angle_mode in [AngleMode::Degrees, AngleMode::Gradians] {
+        for function in ["sin", "cos", "tan", "csc", "sec", "cot"] {
+            assert_matches_differences(&format!("{}(x)", function), 40.0, angle_mode);
+        }
+        for function in ["arcsin", "arccos", "arctan", "arccot"] {
+            assert_matches_differences(&format!("{}(x)", function), 0.4, angle_mode);
+        }
+    }
+    // sin(30°) and cos(30°) are exact, so is the derivative
+    let dual = gradient(
+        &expression("sin(x)"),
+        &["x"],
+        &at(&[("x", 60.0)], AngleMode::Degrees),
+    )
+    .unwrap();
+    assert_eq!(dual.derivatives, vec![0.5 * std::f64::consts::PI / 180.0]);
+}
+
+#[test]
+fn errors_are_evals() {
+    let fails = |input: &str, x: f64| {
+        let environment = at(&[("x", x)], AngleMode::Radians);
+        let expected = eval_with(&expression(input), &environment).unwrap_err();
+        assert_eq!(
+            gradient(&expression(input), &["x"], &environment).unwrap_err(),
+            expected,
+            "{}",
+            input
+        );
+        assert_eq!(
+            hessian(&expression(input), &["x"], &environment).unwrap_err(),
+            expected,
+            "{}",
+            input
+        );
+    };
+    fails("1 / (x - 2)", 2.0);
+    fails("sqrt(x)", -1.0);
+    fails("arcsin(x)", 2.0);
+    fails("tan(x)", std::f64::consts::FRAC_PI_2);
+    fails("x + y", 1.0);
+    match gradient(
+        &expression("1 + sqrt(x)"),
+        &["x"],
+        &at(&[("x", 0.0)], AngleMode::Radians),
+    ) {
+        Err(EvalError::NotDifferentiable { function, path, .. }) => {
+            assert_eq!(function, "sqrt");
+            assert_eq!(path, vec![1]);
+        }
+        result => panic!("gave {:?}", result),
+    }
+    assert!(matches!(
+        gradient(
+            &expression("arcsin(x)"),
+            &["x"],
+            &at(&[("x", 1.0)], AngleMode::Radians)
+        ),
+        Err(EvalError::NotDifferentiable { .. })
+    ));
+    // x^2 is differentiable at 0 even though x^y isn't
+    assert_eq!(
+        hessian(
+            &expression("x^2"),
+            &["x"],
+            &at(&[("x", 0.0)], AngleMode::Radians)
+        )
+        .unwrap()
+        .hessian,
+        vec![vec![2.0]]
+    );
+}
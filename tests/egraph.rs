@@ -0,0 +1,159 @@
+// the e-graph backend, against the pass simplifier
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::expression;
+use rustic_math::{
+    equivalent, node_count, simplify_bounded, simplify_with_options, to_latex, Backend,
+    Equivalence, Expression, LimitReached, Limits, SimplifyOptions, Strategy,
+};
+
+fn simplify(input: &str, backend: Backend, strategy: Strategy) -> Expression {
+    simplify_with_options(
+        expression(input),
+        &SimplifyOptions {
+            backend,
+            strategy,
+            ..SimplifyOptions::default()
+        },
+    )
+}
+
+#[test]
+fn trig_and_hyperbolic_identities() {
+    for (input, expected) in [
+        ("cosh(x)^2 - sinh(x)^2", "1"),
+        ("sin(x)^2 + cos(x)^2", "1"),
+        ("tan(x) * cos(x)", "sin(x)"),
+        ("cot(x) * sin(x)", "cos(x)"),
+        ("tanh(x) * cosh(x)", "sinh(x)"),
+        ("sin(x) / cos(x)", "tan(x)"),
+        ("sin(0 - x) + sin(x)", "0"),
+        ("2 * cosh(y)^2 - 2 * sinh(y)^2 + x", "x + 2"),
+    ] {
+        let simplified = simplify(input, Backend::EGraph, Strategy::default());
+        assert_eq!(
+            simplified,
+            simplify(expected, Backend::Passes, Strategy::default()),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn never_worse_than_the_passes() {
+    for input in [
+        "x * (x + 1) - x",
+        "(x + 1)^2 - 2 * x",
+        "2 * x + 3 * x - x",
+        "(a + b) * c + (a + b) * d",
+        "x^2 * x^3 / x",
+        "sin(x)^2 + x + cos(x)^2",
+        "tan(y) * cos(y) - sin(y)",
+        "sqrt(4 * x^2) - 2 * sqrt(x^2)",
+    ] {
+        let options = SimplifyOptions {
+            backend: Backend::EGraph,
+            ..SimplifyOptions::default()
+        };
+        let egraph = simplify_bounded(expression(input), &options);
+        // the e-graph's rules cover what the expand and factor passes do to these
+        for strategy in [Strategy::Expand, Strategy::Factor] {
+            let passes = simplify(input, Backend::Passes, strategy.clone());
+            assert!(
+                node_count(&egraph.expression) <= node_count(&passes),
+                "{} simplified to {:?} with the e-graph but {:?} with {} passes",
+                input,
+                egraph.expression,
+                passes,
+                strategy
+            );
+        }
+        assert!(
+            matches!(
+                equivalent(&egraph.expression, &expression(input)),
+                Equivalence::Proven | Equivalence::Probable { .. }
+            ),
+            "{} simplified to {:?}",
+            input,
+            egraph.expression
+        );
+    }
+}
+
+#[test]
+fn trig_reduce_agrees() {
+    // the rules the e-graph uses for these are the trig_reduce passes
+    for input in [
+        "cosh(x)^2 - sinh(x)^2",
+        "sin(x)^2 + cos(x)^2 + 1",
+        "3 * sin(y)^2 + 3 * cos(y)^2",
+    ] {
+        assert_eq!(
+            simplify(input, Backend::EGraph, Strategy::default()),
+            simplify(input, Backend::Passes, Strategy::TrigReduce),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn terms_come_out_in_order() {
+    for (input, expected) in [
+        ("c + b + a", "a + b + c"),
+        ("1 + x + x^2", "x^{2} + x + 1"),
+        ("z * y * x", "x \\cdot y \\cdot z"),
+    ] {
+        let simplified = simplify(input, Backend::EGraph, Strategy::default());
+        assert_eq!(to_latex(&simplified), expected, "{}", input);
+    }
+}
+
+#[test]
+fn stops_at_the_time_limit() {
+    let options = SimplifyOptions {
+        backend: Backend::EGraph,
+        limits: Limits {
+            max_time: Duration::from_millis(50),
+            ..Limits::default()
+        },
+        ..SimplifyOptions::default()
+    };
+    let started = Instant::now();
+    let simplified = simplify_bounded(expression("(a+b+c)*(a+b+c)*(a+b+c)"), &options);
+    assert_eq!(simplified.limit, Some(LimitReached::Time));
+    // the limit is checked while the rules are searched, not only between iterations
+    assert!(
+        started.elapsed() < Duration::from_millis(500),
+        "took {:?}",
+        started.elapsed()
+    );
+}
+
+#[test]
+fn finishes_where_the_passes_do() {
+    // distributing and factoring these could go on until the time limit, and
+    // the cheapest form is one the passes find straight away
+    for (input, expected) in [
+        ("(x + y)^2 - x^2 - y^2", "2 \\cdot x \\cdot y"),
+        ("(x - y) * (x + y) - x^2", "-y^{2}"),
+    ] {
+        let options = SimplifyOptions {
+            backend: Backend::EGraph,
+            ..SimplifyOptions::default()
+        };
+        let started = Instant::now();
+        let simplified = simplify_bounded(expression(input), &options);
+        assert_eq!(simplified.limit, None, "{}", input);
+        assert_eq!(to_latex(&simplified.expression), expected, "{}", input);
+        assert!(
+            started.elapsed() < Duration::from_secs(1),
+            "{} took {:?}",
+            input,
+            started.elapsed()
+        );
+    }
+}
@@ -0,0 +1,41 @@
+// variables assigned in the repl, x = 3 and const g = 9.81
+mod common;
+
+use common::expression;
+use rustic_math::{eval_with, Binding, Environment};
+
+#[test]
+fn values_and_expressions() {
+    let mut environment = Environment::new();
+    environment.assign("x", expression("3")).unwrap();
+    assert_eq!(environment.get("x"), Some(&Binding::Value(3.0)));
+
+    // y keeps the expression and follows x
+    environment.assign("y", expression("x^2")).unwrap();
+    assert_eq!(
+        environment.get("y"),
+        Some(&Binding::Expression(expression("x^2")))
+    );
+    assert_eq!(eval_with(&expression("y"), &environment), Ok(9.0));
+    environment.assign("x", expression("4")).unwrap();
+    assert_eq!(eval_with(&expression("y"), &environment), Ok(16.0));
+
+    // the x on the right is the one from before the assignment
+    environment.assign("x", expression("x + 1")).unwrap();
+    assert_eq!(environment.get("x"), Some(&Binding::Value(5.0)));
+    assert_eq!(eval_with(&expression("y"), &environment), Ok(25.0));
+}
+
+#[test]
+fn constants_and_the_imaginary_unit_are_kept() {
+    let mut environment = Environment::new();
+    environment.define_constant("g", 9.81).unwrap();
+    assert!(environment.assign("g", expression("10")).is_err());
+    assert!(environment.define_constant("g", 10.0).is_err());
+    assert!(environment.set_expression("g", expression("x")).is_err());
+    assert_eq!(environment.get("g"), Some(&Binding::Constant(9.81)));
+
+    assert!(environment.assign("i", expression("2")).is_err());
+    assert!(environment.define_constant("i", 2.0).is_err());
+    assert_eq!(environment.get("i"), None);
+}
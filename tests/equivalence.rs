@@ -0,0 +1,109 @@
+// the equivalence check behind a = b in the repl
+mod common;
+
+use common::expression;
+use rustic_math::{equivalent, Environment, Equivalence, EvalError, Expression};
+
+#[test]
+fn undefined_everywhere_is_undetermined() {
+    assert_eq!(
+        equivalent(&expression("1 / 0"), &expression("5")),
+        Equivalence::Undetermined(Some(EvalError::DivisionByZero { path: Vec::new() }))
+    );
+    for (a, b) in [("sqrt(0 - 1 - x^2)", "7"), ("arcsin(x + 20)", "3")] {
+        assert!(
+            matches!(
+                equivalent(&expression(a), &expression(b)),
+                Equivalence::Undetermined(Some(EvalError::DomainError { .. }))
+            ),
+            "{} = {}",
+            a,
+            b
+        );
+    }
+    assert!(matches!(
+        equivalent(&expression("sin(x)^2 + cos(x)^2 + x"), &expression("1 + x")),
+        Equivalence::Proven | Equivalence::Probable { .. }
+    ));
+}
+
+#[test]
+fn bound_variables_take_their_values() {
+    let mut environment = Environment::new();
+    environment.set("x", 3.0).unwrap();
+    environment
+        .set_expression("y", expression("x^2 + z"))
+        .unwrap();
+    environment.set_expression("p", expression("q")).unwrap();
+    environment.set_expression("q", expression("p")).unwrap();
+    assert_eq!(
+        environment.substitute_bindings(&expression("x + 1")),
+        expression("3 + 1")
+    );
+    assert_eq!(
+        environment.substitute_bindings(&expression("y - z")),
+        expression("3^2 + z - z")
+    );
+    assert_eq!(
+        equivalent(
+            &environment.substitute_bindings(&expression("x + 1")),
+            &expression("4")
+        ),
+        Equivalence::Proven
+    );
+    assert!(matches!(
+        equivalent(
+            &environment.substitute_bindings(&expression("x + 1")),
+            &expression("5")
+        ),
+        Equivalence::Disproven(_)
+    ));
+    // a cycle is left as variables rather than substituted forever
+    assert!(matches!(
+        environment.substitute_bindings(&expression("p")),
+        Expression::Variable(_)
+    ));
+}
+
+#[test]
+fn agreeing_everywhere_tried_is_probable() {
+    // the simplifier doesn't expand these, so they are only compared at points
+    for (a, b) in [
+        ("sin(2 * x)", "2 * sin(x) * cos(x)"),
+        ("cos(2 * x)", "1 - 2 * sin(x)^2"),
+        ("sin(x + y)", "sin(x) * cos(y) + cos(x) * sin(y)"),
+    ] {
+        assert_eq!(
+            equivalent(&expression(a), &expression(b)),
+            Equivalence::Probable { samples: 100 },
+            "{} = {}",
+            a,
+            b
+        );
+    }
+}
+
+#[test]
+fn counterexamples_are_rounded() {
+    for (a, b) in [
+        ("x * y", "x + y"),
+        ("x^2 + y", "x + y^2"),
+        ("x / 3", "x / 4"),
+    ] {
+        let Equivalence::Disproven(counterexample) = equivalent(&expression(a), &expression(b))
+        else {
+            panic!("{} = {} should be disproven", a, b);
+        };
+        assert!(
+            counterexample
+                .values
+                .iter()
+                .all(|(_, value)| value.fract() == 0.0),
+            "{} = {} at {:?}",
+            a,
+            b,
+            counterexample.values
+        );
+        assert_ne!(counterexample.left, counterexample.right);
+    }
+}
@@ -0,0 +1,395 @@
+// reference values for every function, around the edges of their domains and at their poles
+use std::f64::consts::PI;
+
+use rustic_math::{eval_in, parse, tokenize, AngleMode, EvalError};
+
+fn evaluate(input: &str, angle_mode: AngleMode) -> Result<f64, EvalError> {
+    let expressions = parse(tokenize(input.to_string()));
+    assert_eq!(
+        expressions.len(),
+        1,
+        "{} should parse to one expression",
+        input
+    );
+    eval_in(&expressions[0], angle_mode)
+}
+
+fn assert_value_in(input: &str, angle_mode: AngleMode, expected: f64) {
+    match evaluate(input, angle_mode) {
+        Ok(value) => assert!(
+            (value - expected).abs() <= 1e-12 * expected.abs().max(1.0),
+            "{} in {} was {}, expected {}",
+            input,
+            angle_mode,
+            value,
+            expected
+        ),
+        Err(err) => panic!(
+            "{} in {} failed with {}, expected {}",
+            input, angle_mode, err, expected
+        ),
+    }
+}
+
+fn assert_value(input: &str, expected: f64) {
+    assert_value_in(input, AngleMode::Radians, expected);
+}
+
+// values that are exact, like sin(pi / 6) = 0.5, have to come out without rounding error
+fn assert_exact_in(input: &str, angle_mode: AngleMode, expected: f64) {
+    assert_eq!(
+        evaluate(input, angle_mode),
+        Ok(expected),
+        "{} in {}",
+        input,
+        angle_mode
+    );
+}
+
+fn assert_exact(input: &str, expected: f64) {
+    assert_exact_in(input, AngleMode::Radians, expected);
+}
+
+fn assert_undefined_in(input: &str, angle_mode: AngleMode, function: &str) {
+    match evaluate(input, angle_mode) {
+        Err(EvalError::DomainError {
+            function: failed, ..
+        }) => assert_eq!(failed, function, "{}", input),
+        result => panic!(
+            "{} in {} gave {:?}, expected {} to be undefined",
+            input, angle_mode, result, function
+        ),
+    }
+}
+
+fn assert_undefined(input: &str, function: &str) {
+    assert_undefined_in(input, AngleMode::Radians, function);
+}
+
+#[test]
+fn sin() {
+    assert_exact("sin(0)", 0.0);
+    assert_exact("sin(pi/6)", 0.5);
+    assert_exact("sin(pi/2)", 1.0);
+    assert_exact("sin(pi)", 0.0);
+    assert_exact("sin(-pi/2)", -1.0);
+    assert_value("sin(1)", 0.8414709848078965);
+}
+
+#[test]
+fn cos() {
+    assert_exact("cos(0)", 1.0);
+    assert_exact("cos(pi/3)", 0.5);
+    assert_exact("cos(pi/2)", 0.0);
+    assert_exact("cos(pi)", -1.0);
+    assert_value("cos(1)", 0.5403023058681398);
+}
+
+#[test]
+fn tan() {
+    assert_exact("tan(0)", 0.0);
+    assert_exact("tan(pi/4)", 1.0);
+    assert_exact("tan(-pi/4)", -1.0);
+    assert_value("tan(1)", 1.5574077246549023);
+    assert_undefined("tan(pi/2)", "tan");
+    assert_undefined("tan(-pi/2)", "tan");
+    assert_undefined("tan(5*pi/2)", "tan");
+}
+
+#[test]
+fn csc() {
+    assert_exact("csc(pi/6)", 2.0);
+    assert_exact("csc(pi/2)", 1.0);
+    assert_value("csc(1)", 1.1883951057781212);
+    assert_undefined("csc(0)", "csc");
+    assert_undefined("csc(pi)", "csc");
+    assert_undefined("csc(-2*pi)", "csc");
+}
+
+#[test]
+fn sec() {
+    assert_exact("sec(0)", 1.0);
+    assert_exact("sec(pi/3)", 2.0);
+    assert_exact("sec(pi)", -1.0);
+    assert_value("sec(1)", 1.8508157176809255);
+    assert_undefined("sec(pi/2)", "sec");
+    assert_undefined("sec(3*pi/2)", "sec");
+}
+
+#[test]
+fn cot() {
+    assert_exact("cot(pi/4)", 1.0);
+    assert_exact("cot(pi/2)", 0.0);
+    assert_value("cot(1)", 0.6420926159343306);
+    assert_undefined("cot(0)", "cot");
+    assert_undefined("cot(pi)", "cot");
+    assert_undefined("cot(3*pi)", "cot");
+}
+
+#[test]
+fn poles_within_rounding() {
+    // too far from 0 to be taken as a multiple of 15°, but still at the pole as far as a float can tell
+    assert_undefined("csc(10^13 * pi)", "csc");
+    assert_undefined("cot(10^13 * pi)", "cot");
+    assert_undefined("sec(10^13 * pi + pi/2)", "sec");
+    // near a pole is not at it
+    match evaluate("tan(pi/2 - 10^(-6))", AngleMode::Radians) {
+        Ok(value) => assert!(
+            (value - 1e6).abs() < 1.0,
+            "tan(pi/2 - 10^(-6)) was {}",
+            value
+        ),
+        Err(err) => panic!("tan(pi/2 - 10^(-6)) failed with {}", err),
+    }
+}
+
+#[test]
+fn right_next_to_poles() {
+    for (input, expected) in [
+        ("tan(pi/2 + 0.0000000001)", -1e10),
+        ("sec(pi/2 + 0.0000000001)", -1e10),
+        ("cot(0.0000000001)", 1e10),
+        ("csc(pi - 0.0000000001)", 1e10),
+        ("tan(pi/2 + 10^(-15))", -1e15),
+    ] {
+        match evaluate(input, AngleMode::Radians) {
+            Ok(value) => assert!(
+                (value - expected).abs() <= 0.1 * expected.abs(),
+                "{} was {}, expected about {}",
+                input,
+                value,
+                expected
+            ),
+            Err(err) => panic!("{} failed with {}", input, err),
+        }
+    }
+}
+
+#[test]
+fn near_exact_values() {
+    // the inverse functions only give an exact angle for the exact value, not for one a tiny bit off it
+    assert_value(
+        "arcsin(0.5 + 0.0000000001)",
+        PI / 6.0 + 1e-10 * 2.0 / (3.0_f64).sqrt(),
+    );
+    assert_value("arctan(1 - 0.0000000001)", PI / 4.0 - 0.5e-10);
+    assert_ne!(
+        evaluate("arccos(0.0000000001)", AngleMode::Radians),
+        Ok(PI / 2.0)
+    );
+    assert_exact("arccot(sqrt(3))", PI / 6.0);
+    assert_exact("arcsec(2/sqrt(3))", PI / 6.0);
+}
+
+#[test]
+fn near_special_angles() {
+    // only the multiples of 15° themselves have exact values, not angles a tiny bit off them
+    assert_value("sin(0.0000000001)", 1e-10);
+    assert_value("tan(0.0000000002)", 2e-10);
+    assert_value("cos(pi/2 + 0.0000000001)", -1e-10);
+    assert_value(
+        "sin(pi/6 + 0.0000000001)",
+        0.5 + 1e-10 * (3.0_f64).sqrt() / 2.0,
+    );
+    assert_value_in("sin(0.0000000001)", AngleMode::Degrees, 1e-10 * PI / 180.0);
+    assert_ne!(
+        evaluate("sin(pi/6 + 0.0000000001)", AngleMode::Radians),
+        Ok(0.5)
+    );
+    // while the rounding in working out a multiple still leaves it exact
+    assert_exact("sin(5*pi/6)", 0.5);
+    assert_exact("cos(-2*pi/3)", -0.5);
+    assert_exact("tan(7*pi/4)", -1.0);
+    assert_exact_in("sin(150)", AngleMode::Degrees, 0.5);
+    assert_exact_in("cos(1000/3)", AngleMode::Gradians, 0.5);
+}
+
+#[test]
+fn arcsin() {
+    assert_exact("arcsin(0)", 0.0);
+    assert_exact("arcsin(1/2)", PI / 6.0);
+    assert_exact("arcsin(1)", PI / 2.0);
+    assert_exact("arcsin(-1)", -PI / 2.0);
+    assert_value("arcsin(0.3)", 0.3046926540153975);
+    assert_undefined("arcsin(1.5)", "arcsin");
+    assert_undefined("arcsin(-2)", "arcsin");
+}
+
+#[test]
+fn arccos() {
+    assert_exact("arccos(1)", 0.0);
+    assert_exact("arccos(0)", PI / 2.0);
+    assert_exact("arccos(-1)", PI);
+    assert_value("arccos(0.3)", 1.2661036727794992);
+    assert_undefined("arccos(2)", "arccos");
+    assert_undefined("arccos(-1.5)", "arccos");
+}
+
+#[test]
+fn arctan() {
+    assert_exact("arctan(0)", 0.0);
+    assert_exact("arctan(1)", PI / 4.0);
+    assert_exact("arctan(-1)", -PI / 4.0);
+    assert_value("arctan(0.3)", 0.2914567944778671);
+    assert_value("arctan(10^300)", PI / 2.0);
+}
+
+#[test]
+fn arccsc() {
+    assert_exact("arccsc(2)", PI / 6.0);
+    assert_exact("arccsc(-2)", -PI / 6.0);
+    assert_exact("arccsc(1)", PI / 2.0);
+    assert_exact("arccsc(-1)", -PI / 2.0);
+    assert_value("arccsc(3)", 0.3398369094541219);
+    assert_value("arccsc(10^300)", 0.0);
+    assert_undefined("arccsc(0.5)", "arccsc");
+    assert_undefined("arccsc(-0.5)", "arccsc");
+    assert_undefined("arccsc(0)", "arccsc");
+}
+
+#[test]
+fn arcsec() {
+    assert_exact("arcsec(1)", 0.0);
+    assert_exact("arcsec(2)", PI / 3.0);
+    assert_exact("arcsec(-2)", 2.0 * PI / 3.0);
+    assert_exact("arcsec(-1)", PI);
+    assert_value("arcsec(3)", 1.2309594173407747);
+    assert_undefined("arcsec(0.5)", "arcsec");
+    assert_undefined("arcsec(0)", "arcsec");
+}
+
+#[test]
+fn arccot() {
+    assert_exact("arccot(1)", PI / 4.0);
+    assert_exact("arccot(-1)", -PI / 4.0);
+    assert_exact("arccot(0)", PI / 2.0);
+    assert_value("arccot(3)", 0.3217505543966422);
+    assert_value("arccot(10^300)", 0.0);
+}
+
+#[test]
+fn sinh() {
+    assert_exact("sinh(0)", 0.0);
+    assert_value("sinh(1)", 1.1752011936438014);
+    assert_value("sinh(-1)", -1.1752011936438014);
+    assert!(matches!(
+        evaluate("sinh(1000)", AngleMode::Radians),
+        Err(EvalError::Overflow { .. })
+    ));
+}
+
+#[test]
+fn cosh() {
+    assert_exact("cosh(0)", 1.0);
+    assert_value("cosh(1)", 1.5430806348152437);
+    assert_value("cosh(-1)", 1.5430806348152437);
+    assert!(matches!(
+        evaluate("cosh(1000)", AngleMode::Radians),
+        Err(EvalError::Overflow { .. })
+    ));
+}
+
+#[test]
+fn tanh() {
+    assert_exact("tanh(0)", 0.0);
+    assert_value("tanh(1)", 0.7615941559557649);
+    assert_exact("tanh(1000)", 1.0);
+    assert_exact("tanh(-1000)", -1.0);
+}
+
+#[test]
+fn csch() {
+    assert_value("csch(1)", 0.8509181282393216);
+    assert_value("csch(-1)", -0.8509181282393216);
+    assert_exact("csch(1000)", 0.0);
+    assert_undefined("csch(0)", "csch");
+}
+
+#[test]
+fn sech() {
+    assert_exact("sech(0)", 1.0);
+    assert_value("sech(1)", 0.6480542736638855);
+    assert_exact("sech(1000)", 0.0);
+}
+
+#[test]
+fn coth() {
+    assert_value("coth(1)", 1.3130352854993315);
+    assert_value("coth(-1)", -1.3130352854993315);
+    assert_exact("coth(1000)", 1.0);
+    assert_undefined("coth(0)", "coth");
+}
+
+#[test]
+fn arcsinh() {
+    assert_exact("arcsinh(0)", 0.0);
+    assert_value("arcsinh(1)", 0.881373587019543);
+    assert_value("arcsinh(-1)", -0.881373587019543);
+}
+
+#[test]
+fn arccosh() {
+    assert_exact("arccosh(1)", 0.0);
+    assert_value("arccosh(2)", 1.3169578969248166);
+    assert_undefined("arccosh(0.5)", "arccosh");
+    assert_undefined("arccosh(-2)", "arccosh");
+}
+
+#[test]
+fn arctanh() {
+    assert_exact("arctanh(0)", 0.0);
+    assert_value("arctanh(0.5)", 0.5493061443340548);
+    assert_value("arctanh(-0.5)", -0.5493061443340548);
+    assert_undefined("arctanh(1)", "arctanh");
+    assert_undefined("arctanh(-1)", "arctanh");
+    assert_undefined("arctanh(2)", "arctanh");
+}
+
+#[test]
+fn arccsch() {
+    assert_value("arccsch(1)", 0.881373587019543);
+    assert_value("arccsch(-1)", -0.881373587019543);
+    assert_undefined("arccsch(0)", "arccsch");
+}
+
+#[test]
+fn arcsech() {
+    assert_exact("arcsech(1)", 0.0);
+    assert_value("arcsech(0.5)", 1.3169578969248166);
+    assert_undefined("arcsech(2)", "arcsech");
+    assert_undefined("arcsech(-0.5)", "arcsech");
+    assert_undefined("arcsech(0)", "arcsech");
+}
+
+#[test]
+fn arccoth() {
+    assert_value("arccoth(2)", 0.5493061443340548);
+    assert_value("arccoth(-2)", -0.5493061443340548);
+    assert_undefined("arccoth(1)", "arccoth");
+    assert_undefined("arccoth(-1)", "arccoth");
+    assert_undefined("arccoth(0.5)", "arccoth");
+    assert_undefined("arccoth(0)", "arccoth");
+}
+
+#[test]
+fn degrees() {
+    assert_exact_in("180°", AngleMode::Radians, PI);
+    assert_exact_in("180°", AngleMode::Degrees, 180.0);
+    assert_exact_in("180°", AngleMode::Gradians, 200.0);
+    assert_exact_in("sin(30°)", AngleMode::Radians, 0.5);
+    assert_undefined_in("tan(90°)", AngleMode::Radians, "tan");
+}
+
+#[test]
+fn angle_modes() {
+    assert_exact_in("sin(30)", AngleMode::Degrees, 0.5);
+    assert_exact_in("cos(100)", AngleMode::Gradians, 0.0);
+    assert_undefined_in("tan(90)", AngleMode::Degrees, "tan");
+    assert_undefined_in("csc(200)", AngleMode::Gradians, "csc");
+    assert_exact_in("arcsin(1/2)", AngleMode::Degrees, 30.0);
+    assert_exact_in("arccsc(2)", AngleMode::Degrees, 30.0);
+    assert_exact_in("arcsec(-2)", AngleMode::Degrees, 120.0);
+    assert_exact_in("arccot(0)", AngleMode::Degrees, 90.0);
+    assert_exact_in("arccos(0)", AngleMode::Gradians, 100.0);
+    assert_undefined_in("arcsec(0.5)", AngleMode::Degrees, "arcsec");
+}
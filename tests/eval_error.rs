@@ -0,0 +1,94 @@
+// the errors evaluation gives and the path to the node that caused them
+mod common;
+
+use common::expression;
+use rustic_math::{eval, eval_with, Environment, EvalError};
+
+#[test]
+fn kinds() {
+    assert_eq!(
+        eval(&expression("1/0")),
+        Err(EvalError::DivisionByZero { path: vec![] })
+    );
+    assert_eq!(
+        eval(&expression("sqrt(-4)")),
+        Err(EvalError::DomainError {
+            function: "sqrt".to_string(),
+            value: -4.0,
+            imaginary: 0.0,
+            path: vec![],
+        })
+    );
+    assert_eq!(
+        eval(&expression("x")),
+        Err(EvalError::UnboundVariable {
+            name: "x".to_string(),
+            path: vec![],
+        })
+    );
+    assert_eq!(
+        eval(&expression("10^400")),
+        Err(EvalError::Overflow { path: vec![] })
+    );
+    assert_eq!(
+        eval(&expression("e^1000")),
+        Err(EvalError::Overflow { path: vec![] })
+    );
+}
+
+#[test]
+fn paths_lead_to_the_failing_node() {
+    // the sqrt in the second term
+    assert_eq!(
+        eval(&expression("1 + 2 * sqrt(-1)")).unwrap_err().path(),
+        &[1, 1]
+    );
+    // the division inside the sum inside the product
+    assert_eq!(
+        eval(&expression("2 * (3 + 1/(1-1))")).unwrap_err().path(),
+        &[1, 1]
+    );
+    assert_eq!(eval(&expression("x + 1")).unwrap_err().path(), &[0]);
+}
+
+#[test]
+fn bindings_that_fail_are_the_variables_fault() {
+    let mut environment = Environment::new();
+    environment
+        .set_expression("y", expression("sqrt(-1)"))
+        .unwrap();
+    match eval_with(&expression("1 + y"), &environment) {
+        Err(EvalError::DomainError { function, path, .. }) => {
+            assert_eq!(function, "sqrt");
+            assert_eq!(path, vec![1]);
+        }
+        result => panic!("gave {:?}", result),
+    }
+
+    environment
+        .set_expression("a", expression("b + 1"))
+        .unwrap();
+    environment
+        .set_expression("b", expression("a * 2"))
+        .unwrap();
+    match eval_with(&expression("a"), &environment) {
+        Err(EvalError::CircularDefinition { path, .. }) => assert!(path.is_empty()),
+        result => panic!("gave {:?}", result),
+    }
+}
+
+#[test]
+fn display_and_json() {
+    let err = eval(&expression("2 * (3 + 1/(1-1))")).unwrap_err();
+    assert_eq!(err.to_string(), "Division by zero");
+    assert_eq!(
+        serde_json::to_string(&err).unwrap(),
+        r#"{"kind":"DivisionByZero","path":[1,1]}"#
+    );
+    let err = eval(&expression("arcsin(2)")).unwrap_err();
+    assert_eq!(err.to_string(), "arcsin is undefined for 2");
+    assert_eq!(
+        serde_json::to_string(&err).unwrap(),
+        r#"{"kind":"DomainError","function":"arcsin","value":2.0,"imaginary":0.0,"path":[]}"#
+    );
+}
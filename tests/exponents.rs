@@ -0,0 +1,62 @@
+// the exponent laws and the preferred forms of negative and fractional powers
+mod common;
+
+use common::expression;
+use rustic_math::{simplify_with_options, to_latex, NegativeExponents, Radicals, SimplifyOptions};
+
+fn simplified(input: &str, options: &SimplifyOptions) -> String {
+    to_latex(&simplify_with_options(expression(input), options))
+}
+
+fn assert_simplifies(input: &str, expected: &str) {
+    assert_eq!(
+        simplified(input, &SimplifyOptions::default()),
+        expected,
+        "{}",
+        input
+    );
+}
+
+#[test]
+fn power_laws() {
+    assert_simplifies("(x^2)^3", "x^{6}");
+    assert_simplifies("(x*y)^3", "x^{3} \\cdot y^{3}");
+    assert_simplifies("(x/y)^2", "\\frac{x^{2}}{y^{2}}");
+    assert_simplifies("sqrt(x)^2", "x");
+    assert_simplifies("2^3", "8");
+}
+
+#[test]
+fn identity_exponents() {
+    assert_simplifies("x^0", "1");
+    assert_simplifies("x^1", "x");
+    assert_simplifies("1^x", "1");
+}
+
+#[test]
+fn sign_dependent_laws_are_left_alone() {
+    // (x^2)^(1/2) is |x|, not x
+    assert_simplifies("(x^2)^(1/2)", "\\sqrt{x^{2}}");
+    // x * y can be positive with both factors negative
+    let options = SimplifyOptions {
+        radicals: Radicals::Power,
+        ..SimplifyOptions::default()
+    };
+    assert_eq!(
+        simplified("(x*y)^(1/2)", &options),
+        "(x \\cdot y)^{\\frac{1}{2}}"
+    );
+}
+
+#[test]
+fn preferred_forms() {
+    assert_simplifies("x^-2", "\\frac{1}{x^{2}}");
+    assert_simplifies("x^(1/2)", "\\sqrt{x}");
+    let options = SimplifyOptions {
+        negative_exponents: NegativeExponents::Negative,
+        radicals: Radicals::Power,
+        ..SimplifyOptions::default()
+    };
+    assert_eq!(simplified("1/x^2", &options), "x^{-2}");
+    assert_eq!(simplified("sqrt(x)", &options), "x^{\\frac{1}{2}}");
+}
@@ -0,0 +1,101 @@
+// factoring polynomials over the rationals
+mod common;
+
+use common::expression;
+use rustic_math::{factor, to_latex, Expression};
+
+// the factors of input in latex, in no particular order
+fn factors(input: &str) -> Vec<String> {
+    let factored = factor(expression(input));
+    let mut factors: Vec<String> = match &factored {
+        Expression::Multiplication(multiplication) => multiplication
+            .terms
+            .iter()
+            .map(|term| to_latex(term))
+            .collect(),
+        factored => vec![to_latex(factored)],
+    };
+    factors.sort();
+    factors
+}
+
+fn assert_factors(input: &str, expected: &[&str]) {
+    let mut expected: Vec<String> = expected.iter().map(|factor| factor.to_string()).collect();
+    expected.sort();
+    assert_eq!(factors(input), expected, "{}", input);
+}
+
+#[test]
+fn quadratics() {
+    assert_factors("x^2 - 5*x + 6", &["x - 3", "x - 2"]);
+    assert_factors("2*x^2 - 2", &["2", "x - 1", "x + 1"]);
+    assert_factors("x^2 + 2*x + 1", &["(x + 1)^{2}"]);
+}
+
+#[test]
+fn irreducibles_are_unwrapped() {
+    assert_factors("x^2 - 2", &["x^{2} - 2"]);
+    assert_factors("x^2 + 1", &["x^{2} + 1"]);
+    assert_factors("x^2", &["x^{2}"]);
+    assert_factors("6", &["6"]);
+    // not a polynomial, left as it is
+    assert_eq!(
+        to_latex(&factor(expression("sin(x) + 1"))),
+        to_latex(&expression("sin(x) + 1"))
+    );
+}
+
+#[test]
+fn no_rational_roots() {
+    // Sophie Germain's identity, x^4 + 4 has no linear factors but splits into two quadratics
+    assert_factors(
+        "x^4 + 4",
+        &["x^{2} + 2 \\cdot x + 2", "x^{2} - 2 \\cdot x + 2"],
+    );
+}
+
+#[test]
+fn cyclotomics() {
+    assert_factors(
+        "x^6 - 1",
+        &["x - 1", "x + 1", "x^{2} + x + 1", "x^{2} - x + 1"],
+    );
+    // one factor for every divisor of 30
+    assert_factors(
+        "x^30 - 1",
+        &[
+            "x - 1",
+            "x + 1",
+            "x^{2} + x + 1",
+            "x^{2} - x + 1",
+            "x^{4} + x^{3} + x^{2} + x + 1",
+            "x^{4} - x^{3} + x^{2} - x + 1",
+            "x^{8} + x^{7} - x^{5} - x^{4} - x^{3} + x + 1",
+            "x^{8} - x^{7} + x^{5} - x^{4} + x^{3} - x + 1",
+        ],
+    );
+}
+
+#[test]
+fn multivariate_binomials() {
+    assert_factors("x^2 - y^2", &["x - y", "x + y"]);
+    assert_factors("x^3 + y^3", &["x + y", "x^{2} - x \\cdot y + y^{2}"]);
+    assert_factors("x^3 - y^3", &["x - y", "x^{2} + x \\cdot y + y^{2}"]);
+    assert_factors("4*x^2*y - 4*y^3", &["4", "y", "x - y", "x + y"]);
+}
+
+#[test]
+fn overflowing_coefficients_are_left_unfactored() {
+    // the expanded coefficients reach 10^60, past an i128
+    let input = "(x + 1000000)^10";
+    assert_eq!(
+        to_latex(&factor(expression(input))),
+        to_latex(&expression(input))
+    );
+    // b^2 - 4ac overflows
+    let input = "x^2 + 20000000000000000000*x + 3";
+    assert_eq!(
+        to_latex(&factor(expression(input))),
+        to_latex(&expression(input))
+    );
+}
@@ -0,0 +1,84 @@
+// the hyperbolic functions, their identities and their exponential definitions
+mod common;
+
+use common::expression;
+use rustic_math::{
+    derivative, eval, simplify_expression, simplify_with_options, to_latex, Expression,
+    SimplifyOptions,
+};
+
+fn simplified(input: &str, strategy: &str) -> String {
+    let options = SimplifyOptions {
+        strategy: strategy.parse().unwrap(),
+        ..SimplifyOptions::default()
+    };
+    to_latex(&simplify_with_options(expression(input), &options))
+}
+
+fn derivative_of(input: &str) -> String {
+    match expression(input) {
+        Expression::Function(function) => to_latex(&simplify_expression(derivative(&function))),
+        _ => panic!("{} is not a function", input),
+    }
+}
+
+#[test]
+fn parsing_and_evaluation() {
+    assert_eq!(to_latex(&expression("sinh(x)")), "\\sinh(x)");
+    assert_eq!(
+        to_latex(&expression("arccosh(x)")),
+        "\\operatorname{arccosh}(x)"
+    );
+    assert_eq!(to_latex(&expression("csch(x)")), "\\operatorname{csch}(x)");
+    assert!((eval(&expression("sinh(1)")).unwrap() - 1f64.sinh()).abs() < 1e-12);
+    assert!((eval(&expression("arccosh(2)")).unwrap() - 2f64.acosh()).abs() < 1e-12);
+    assert!((eval(&expression("arcsech(0.5)")).unwrap() - 2f64.acosh()).abs() < 1e-12);
+    assert!((eval(&expression("csch(1)")).unwrap() - 1.0 / 1f64.sinh()).abs() < 1e-12);
+    assert!(eval(&expression("coth(0)")).is_err());
+}
+
+#[test]
+fn identities() {
+    assert_eq!(simplified("cosh(x)^2 - sinh(x)^2", "trig_reduce"), "1");
+    assert_eq!(simplified("sinh(x)^2 - cosh(x)^2", "trig_reduce"), "-1");
+    assert_eq!(
+        simplified("2*cosh(x)^2 - 2*sinh(x)^2 + y", "trig_reduce"),
+        "y + 2"
+    );
+    assert_eq!(simplified("tanh(x)*cosh(x)", "trig_reduce"), "\\sinh(x)");
+    assert_eq!(simplified("sech(x)*cosh(x)", "trig_reduce"), "1");
+    // sinh is odd and cosh is even
+    assert_eq!(simplified("sinh(-x)", "trig_reduce"), "-\\sinh(x)");
+    assert_eq!(simplified("cosh(-x)", "trig_reduce"), "\\cosh(x)");
+    // the same as the circular ones, without asking for trig_reduce
+    assert_eq!(simplified("cosh(x)^2 - sinh(x)^2", "expand"), "1");
+    assert_eq!(simplified("tanh(x)*coth(x)", "expand"), "1");
+    assert_eq!(simplified("sech(x)*cosh(x)", "expand"), "1");
+    assert_eq!(simplified("csch(x)*sinh(x) + y", "expand"), "1 + y");
+}
+
+#[test]
+fn exponential_definitions() {
+    assert_eq!(simplified("cosh(x) + sinh(x)", "exponential"), "e^{x}");
+    assert_eq!(
+        simplified("cosh(x) - sinh(x)", "exponential"),
+        "\\frac{1}{e^{x}}"
+    );
+    assert_eq!(
+        simplified("sinh(x)", "exponential"),
+        "\\frac{e^{x}}{2} - \\frac{\\frac{1}{e^{x}}}{2}"
+    );
+}
+
+#[test]
+fn derivatives() {
+    assert_eq!(derivative_of("sinh(x)"), "\\cosh(x)");
+    assert_eq!(derivative_of("cosh(x)"), "\\sinh(x)");
+    assert_eq!(derivative_of("tanh(x)"), "\\operatorname{sech}(x)^{2}");
+    assert_eq!(
+        derivative_of("sech(x)"),
+        "-\\operatorname{sech}(x) \\cdot \\tanh(x)"
+    );
+    assert_eq!(derivative_of("arcsinh(x)"), "\\frac{1}{\\sqrt{1 + x^{2}}}");
+    assert_eq!(derivative_of("arctanh(x)"), "\\frac{1}{1 - x^{2}}");
+}
@@ -0,0 +1,266 @@
+// interval evaluation has to enclose the value at every point of its inputs, and be tight where it can
+mod common;
+
+use common::expression;
+use rustic_math::{eval_interval, eval_with, AngleMode, Environment, EvalError, Interval};
+
+fn over(input: &str, x: Interval, angle_mode: AngleMode) -> Result<Interval, EvalError> {
+    eval_interval(&expression(input), &[(String::from("x"), x)], angle_mode)
+}
+
+// every point of x where input is defined has its value inside the interval's
+fn assert_encloses(input: &str, x: Interval, angle_mode: AngleMode) {
+    let enclosure = over(input, x, angle_mode)
+        .unwrap_or_else(|err| panic!("{} over {} failed with {}", input, x, err));
+    let mut environment = Environment::new();
+    environment.angle_mode = angle_mode;
+    for i in 0..=200 {
+        let point = x.lo + (x.hi - x.lo) * i as f64 / 200.0;
+        environment.set("x", point).unwrap();
+        if let Ok(value) = eval_with(&expression(input), &environment) {
+            assert!(
+                enclosure.contains(value),
+                "{} at x = {} in {} is {}, outside {} over {}",
+                input,
+                point,
+                angle_mode,
+                value,
+                enclosure,
+                x
+            );
+        }
+    }
+}
+
+#[test]
+fn arithmetic_is_exact_where_f64_is() {
+    let x = Interval::new(1.0, 2.0);
+    assert_eq!(
+        over("x^2 - 2", x, AngleMode::Radians),
+        Ok(Interval::new(-1.0, 2.0))
+    );
+    assert_eq!(
+        over("x * 3 + 1", x, AngleMode::Radians),
+        Ok(Interval::new(4.0, 7.0))
+    );
+    assert_eq!(
+        over("2^10", x, AngleMode::Radians),
+        Ok(Interval::point(1024.0))
+    );
+    assert_eq!(over("sqrt(x^2)", x, AngleMode::Radians), Ok(x));
+    assert_eq!(
+        over("(x - 3)^2", x, AngleMode::Radians),
+        Ok(Interval::new(1.0, 4.0))
+    );
+    assert_eq!(
+        over("x^2", Interval::new(-1.0, 2.0), AngleMode::Radians),
+        Ok(Interval::new(0.0, 4.0))
+    );
+    assert_eq!(
+        over("x^3", Interval::new(-1.0, 2.0), AngleMode::Radians),
+        Ok(Interval::new(-1.0, 8.0))
+    );
+}
+
+#[test]
+fn rounding_is_outward() {
+    let third = over("1 / 3", Interval::point(0.0), AngleMode::Radians).unwrap();
+    assert!(
+        third.lo < third.hi && third.contains(1.0 / 3.0),
+        "{}",
+        third
+    );
+    assert!(third.width() < 1e-15);
+    let tenth = over("0.1 + 0.2", Interval::point(0.0), AngleMode::Radians).unwrap();
+    assert!(
+        tenth.lo < tenth.hi && tenth.contains(0.1 + 0.2),
+        "{}",
+        tenth
+    );
+    // the true sum of the f64s 0.1 and 0.2 is below the f64 0.30000000000000004 they round to
+    assert!(tenth.lo < 0.1 + 0.2);
+    let huge = over("10^400", Interval::point(0.0), AngleMode::Radians).unwrap();
+    assert_eq!(
+        huge,
+        Interval {
+            lo: f64::MAX,
+            hi: f64::INFINITY
+        }
+    );
+}
+
+#[test]
+fn extended_division() {
+    let pieces = Interval::new(1.0, 2.0)
+        .extended_division(Interval::new(-1.0, 1.0))
+        .unwrap();
+    assert_eq!(
+        pieces,
+        vec![
+            Interval::new(f64::NEG_INFINITY, -1.0),
+            Interval::new(1.0, f64::INFINITY)
+        ]
+    );
+    assert_eq!(
+        over("1 / x", Interval::new(-1.0, 1.0), AngleMode::Radians),
+        Ok(Interval::entire())
+    );
+    assert_eq!(
+        over("1 / x", Interval::new(0.0, 2.0), AngleMode::Radians),
+        Ok(Interval::new(0.5, f64::INFINITY))
+    );
+    assert_eq!(
+        over("-1 / x", Interval::new(-2.0, 0.0), AngleMode::Radians),
+        Ok(Interval::new(0.5, f64::INFINITY))
+    );
+    assert_eq!(
+        over("0 / x", Interval::new(-1.0, 1.0), AngleMode::Radians),
+        Ok(Interval::point(0.0))
+    );
+    assert!(matches!(
+        over("1 / (x - x)", Interval::point(1.0), AngleMode::Radians),
+        Err(EvalError::DivisionByZero { .. })
+    ));
+    assert_eq!(
+        over(
+            "1 / x + 1",
+            Interval::new(4.0, f64::INFINITY),
+            AngleMode::Radians
+        ),
+        Ok(Interval::new(1.0, 1.25))
+    );
+}
+
+#[test]
+fn periodic_functions() {
+    let sin = over("sin(x)", Interval::new(0.0, 3.0), AngleMode::Radians).unwrap();
+    assert_eq!(sin.hi, 1.0);
+    assert!(sin.lo <= 0.0 && sin.lo > -1e-15, "{}", sin);
+    assert_eq!(
+        over("cos(x)", Interval::new(-10.0, 10.0), AngleMode::Radians),
+        Ok(Interval::new(-1.0, 1.0))
+    );
+    assert_eq!(
+        over("sin(x)", Interval::new(0.0, 360.0), AngleMode::Degrees),
+        Ok(Interval::new(-1.0, 1.0))
+    );
+    let cos = over("cos(x)", Interval::new(0.1, 0.2), AngleMode::Radians).unwrap();
+    assert!(
+        cos.contains(0.2f64.cos()) && cos.contains(0.1f64.cos()),
+        "{}",
+        cos
+    );
+    assert!(
+        cos.lo > 0.2f64.cos() - 1e-15 && cos.hi < 0.1f64.cos() + 1e-15,
+        "{}",
+        cos
+    );
+    let right_angle = over("90°", Interval::point(0.0), AngleMode::Gradians).unwrap();
+    assert!(
+        right_angle.contains(100.0) && right_angle.width() < 1e-13,
+        "{}",
+        right_angle
+    );
+    assert_eq!(
+        over("tan(x)", Interval::new(1.0, 2.0), AngleMode::Radians),
+        Ok(Interval::entire())
+    );
+    assert_eq!(
+        over("tan(x)", Interval::new(80.0, 100.0), AngleMode::Degrees),
+        Ok(Interval::entire())
+    );
+    assert_eq!(
+        over("cot(x)", Interval::new(-0.5, 0.5), AngleMode::Radians),
+        Ok(Interval::entire())
+    );
+    let cot = over("cot(x)", Interval::new(1.0, 2.0), AngleMode::Radians).unwrap();
+    assert!(cot.lo < 0.0 && cot.hi > 0.0 && cot.hi < 1.0, "{}", cot);
+    assert_eq!(
+        over("csc(x)", Interval::new(-1.0, 1.0), AngleMode::Radians)
+            .map(|y| y.hull(Interval::point(0.0))),
+        Ok(Interval::entire())
+    );
+}
+
+#[test]
+fn every_function_encloses() {
+    let functions = [
+        "sin", "cos", "tan", "csc", "sec", "cot", "sinh", "cosh", "tanh", "csch", "sech", "coth",
+        "arctan", "arcsinh",
+    ];
+    let intervals = [
+        Interval::new(-0.3, 0.4),
+        Interval::new(0.5, 1.5),
+        Interval::new(-4.0, -2.5),
+        Interval::new(1.0, 7.0),
+        Interval::new(-0.01, 0.01),
+    ];
+    for function in functions {
+        for x in intervals {
+            assert_encloses(&format!("{}(x)", function), x, AngleMode::Radians);
+        }
+    }
+    for (function, x) in [
+        ("arcsin", Interval::new(-1.0, 0.3)),
+        ("arccos", Interval::new(-0.5, 1.0)),
+        ("arccsc", Interval::new(1.0, 20.0)),
+        ("arcsec", Interval::new(-9.0, -1.0)),
+        ("arccot", Interval::new(-2.0, 3.0)),
+        ("arccot", Interval::new(0.5, 3.0)),
+        ("arccosh", Interval::new(1.0, 4.0)),
+        ("arctanh", Interval::new(-0.9, 0.5)),
+        ("arccsch", Interval::new(-2.0, 3.0)),
+        ("arcsech", Interval::new(0.1, 1.0)),
+        ("arccoth", Interval::new(1.5, 6.0)),
+        ("arccoth", Interval::new(-6.0, -1.01)),
+    ] {
+        for angle_mode in [AngleMode::Radians, AngleMode::Degrees, AngleMode::Gradians] {
+            assert_encloses(&format!("{}(x)", function), x, angle_mode);
+        }
+    }
+    for angle_mode in [AngleMode::Degrees, AngleMode::Gradians] {
+        for function in ["sin", "cos", "tan", "sec"] {
+            assert_encloses(
+                &format!("{}(x)", function),
+                Interval::new(10.0, 170.0),
+                angle_mode,
+            );
+        }
+    }
+    for input in [
+        "x^x",
+        "2^x - x^2",
+        "x^(1/3)",
+        "sqrt(x) * e^(-x)",
+        "(x + 1) / (x - 2)",
+    ] {
+        assert_encloses(input, Interval::new(0.0, 3.0), AngleMode::Radians);
+    }
+}
+
+#[test]
+fn domains() {
+    let failing = |input: &str, x: Interval| match over(input, x, AngleMode::Radians) {
+        Err(EvalError::DomainError { value, .. }) => value,
+        result => panic!("{} over {} gave {:?}", input, x, result),
+    };
+    assert_eq!(failing("sqrt(x)", Interval::new(-1.0, 4.0)), -1.0);
+    assert_eq!(failing("arcsin(x)", Interval::new(0.0, 2.0)), 2.0);
+    assert_eq!(failing("arccsc(x)", Interval::new(0.5, 2.0)), 0.5);
+    assert_eq!(failing("arctanh(x)", Interval::new(-1.0, 0.0)), -1.0);
+    assert_eq!(failing("csch(x)", Interval::point(0.0)), 0.0);
+    assert!(matches!(
+        over("x^(1/2)", Interval::new(-1.0, 1.0), AngleMode::Radians),
+        Err(EvalError::NaN { .. })
+    ));
+    assert!(matches!(
+        over("y", Interval::point(0.0), AngleMode::Radians),
+        Err(EvalError::UnboundVariable { .. })
+    ));
+    assert_eq!(
+        over("cosh(x)", Interval::new(-1.0, 2.0), AngleMode::Radians)
+            .unwrap()
+            .lo,
+        1.0
+    );
+}
@@ -0,0 +1,71 @@
+// the limits that stop the simplifier before it runs away
+mod common;
+
+use std::time::Duration;
+
+use common::expression;
+use rustic_math::{
+    node_count, simplify_bounded, simplify_with_options, to_latex, LimitReached, Limits,
+    Simplified, SimplifyOptions,
+};
+
+fn bounded(input: &str, limits: Limits) -> Simplified {
+    let options = SimplifyOptions {
+        limits,
+        ..SimplifyOptions::default()
+    };
+    simplify_bounded(expression(input), &options)
+}
+
+#[test]
+fn finishing_reports_no_limit() {
+    let simplified = bounded("(x+1)^2 + (x+2)^2", Limits::default());
+    assert_eq!(simplified.limit, None);
+    assert_eq!(
+        simplified.expression,
+        simplify_with_options(expression("(x+1)^2 + (x+2)^2"), &SimplifyOptions::default())
+    );
+}
+
+#[test]
+fn each_limit_stops_the_simplifier() {
+    let iterations = Limits {
+        max_iterations: 1,
+        ..Limits::default()
+    };
+    assert_eq!(
+        bounded("x*(y+z)", iterations).limit,
+        Some(LimitReached::Iterations)
+    );
+
+    // expanding (x + 1)^8 takes it well past 30 nodes
+    let nodes = Limits {
+        max_nodes: 30,
+        ..Limits::default()
+    };
+    let simplified = bounded("(x+1)^8", nodes);
+    assert_eq!(simplified.limit, Some(LimitReached::Nodes));
+    assert_eq!(to_latex(&simplified.expression), "(x + 1)^{8}");
+
+    let time = Limits {
+        max_time: Duration::ZERO,
+        ..Limits::default()
+    };
+    assert_eq!(bounded("(x+1)^8", time).limit, Some(LimitReached::Time));
+}
+
+#[test]
+fn limits_stop_a_pass_partway() {
+    // the 17 nodes parse as nested products, coalescing flattens all three in one pass and checking
+    // the limit only after the pass would leave 14
+    let options = SimplifyOptions {
+        limits: Limits {
+            max_time: Duration::ZERO,
+            ..Limits::default()
+        },
+        ..SimplifyOptions::default()
+    };
+    let simplified = simplify_bounded(expression("a*(b*c) + d*(f*g) + h*(k*m)"), &options);
+    assert_eq!(simplified.limit, Some(LimitReached::Time));
+    assert_eq!(node_count(&simplified.expression), 16);
+}
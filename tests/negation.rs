@@ -0,0 +1,52 @@
+// normalizing negation and writing it as subtraction
+mod common;
+
+use common::expression;
+use rustic_math::{simplify_expression, to_latex};
+
+fn assert_simplifies(input: &str, expected: &str) {
+    assert_eq!(
+        to_latex(&simplify_expression(expression(input))),
+        expected,
+        "{}",
+        input
+    );
+}
+
+#[test]
+fn double_negation_cancels() {
+    assert_simplifies("--x", "x");
+    assert_simplifies("-(-(x))", "x");
+    assert_simplifies("-x * -y", "x \\cdot y");
+}
+
+#[test]
+fn signs_move_into_coefficients() {
+    assert_simplifies("-(2*x)", "-2 \\cdot x");
+    assert_simplifies("x * -2", "-2 \\cdot x");
+}
+
+#[test]
+fn negation_distributes_over_inner_sums() {
+    assert_simplifies("a - (b + c)", "a - b - c");
+    // a lone negated sum is already as simple as it gets
+    assert_simplifies("-(a + b)", "-(a + b)");
+}
+
+#[test]
+fn negated_addends_are_written_as_subtraction() {
+    assert_eq!(to_latex(&expression("a - b")), "a - b");
+    assert_eq!(to_latex(&expression("a + -3")), "a - 3");
+    assert_eq!(to_latex(&expression("a - (b + c)")), "a - (b + c)");
+    assert_eq!(to_latex(&expression("x - 2*y")), "x - 2 \\cdot y");
+}
+
+#[test]
+fn a_power_of_a_power_keeps_its_parentheses() {
+    assert_eq!(
+        to_latex(&expression("(x^2)^(1/2)")),
+        "(x^{2})^{\\frac{1}{2}}"
+    );
+    assert_eq!(to_latex(&expression("x^(2^3)")), "x^{2^{3}}");
+    assert_eq!(to_latex(&expression("(-x)^2")), "(-x)^{2}");
+}
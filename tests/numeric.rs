@@ -0,0 +1,91 @@
+// the same expressions evaluated over each Numeric type
+mod common;
+
+use common::expression;
+use rustic_math::{eval_as, eval_as_with, eval_in, AngleMode, Complex, Environment, EvalError};
+
+#[test]
+fn f64_is_eval() {
+    for input in [
+        "1 + 2 * 3",
+        "sin(pi/6)",
+        "2^0.5",
+        "arctan(1) * 4",
+        "sqrt(2) / 3",
+        "-cosh(1)",
+    ] {
+        let expr = expression(input);
+        assert_eq!(
+            eval_as::<f64>(&expr, AngleMode::Radians),
+            eval_in(&expr, AngleMode::Radians),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn f32_rounds_f64() {
+    for input in [
+        "1 / 3",
+        "sin(1) + cos(1)",
+        "e^2",
+        "arcsin(0.5)",
+        "sqrt(2)",
+        "2^10 - 1",
+    ] {
+        let expr = expression(input);
+        let single = eval_as::<f32>(&expr, AngleMode::Radians).unwrap();
+        let double = eval_in(&expr, AngleMode::Radians).unwrap();
+        assert!(
+            ((single as f64) - double).abs() <= 1e-6 * double.abs().max(1.0),
+            "{} was {} in f32 and {} in f64",
+            input,
+            single,
+            double
+        );
+    }
+    assert_eq!(
+        eval_as::<f32>(&expression("sin(30)"), AngleMode::Degrees),
+        Ok(0.5)
+    );
+}
+
+#[test]
+fn errors_are_shared() {
+    for input in ["1 / (1 - 1)", "sqrt(-1)", "arcsin(2)", "x + 1", "i"] {
+        let expr = expression(input);
+        let double = eval_as::<f64>(&expr, AngleMode::Radians).unwrap_err();
+        assert_eq!(
+            eval_as::<f32>(&expr, AngleMode::Radians).unwrap_err(),
+            double,
+            "{}",
+            input
+        );
+    }
+    // f32 overflows long before f64 does
+    assert!(matches!(
+        eval_as::<f32>(&expression("10^39"), AngleMode::Radians),
+        Err(EvalError::Overflow { .. })
+    ));
+    assert_eq!(
+        eval_as::<f64>(&expression("10^39"), AngleMode::Radians),
+        Ok(1e39)
+    );
+}
+
+#[test]
+fn environments() {
+    let mut environment = Environment::new();
+    environment.set("x", 2.0).unwrap();
+    environment
+        .set_expression("y", expression("x^2 + 1"))
+        .unwrap();
+    let expr = expression("y * x");
+    assert_eq!(eval_as_with::<f64>(&expr, &environment), Ok(10.0));
+    assert_eq!(eval_as_with::<f32>(&expr, &environment), Ok(10.0));
+    assert_eq!(
+        eval_as_with::<Complex>(&expression("y + i"), &environment),
+        Ok(Complex::new(5.0, 1.0))
+    );
+}
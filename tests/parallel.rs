@@ -0,0 +1,63 @@
+// simplifying the terms of wide sums and products on several threads
+mod common;
+
+use common::expression;
+use rustic_math::{
+    simplify_with_options, simplify_with_steps, SimplifyCache, SimplifyOptions, Strategy,
+};
+
+#[test]
+fn same_result_on_any_thread_and_cache() {
+    let inputs = [
+        "(x + 1)^3 * (x - 2)^2 + (y + 1)^2 * (x + 3) + sin(x)^2 + cos(x)^2",
+        "(a + b) * (c + d) * (e + f) - (a * c + b * d) * (e - f) + (x^2 - 1) / (x - 1)",
+        "tan(x) * cos(x) + (x * y)^2 * (x + y)^2",
+        "x * (x + 1) * (x + 2) * (x + 3) + 2 * x * (x + 1) + sqrt(x^4) + (y^3)^2",
+    ];
+    for strategy in [
+        Strategy::Expand,
+        Strategy::Factor,
+        Strategy::TrigReduce,
+        Strategy::Simplest,
+    ] {
+        for input in inputs {
+            let options = |parallel, cache| SimplifyOptions {
+                strategy: strategy.clone(),
+                parallel,
+                cache,
+                ..SimplifyOptions::default()
+            };
+            let cold = simplify_with_steps(expression(input), &options(None, None));
+            assert!(
+                !cold.1.is_empty(),
+                "{} with {} took no steps",
+                input,
+                strategy
+            );
+            for parallel in [None, Some(1), Some(4)] {
+                // the first call fills the shared cache and the second finds it warm
+                let shared = SimplifyCache::shared(1000);
+                for warmth in ["cold", "warm"] {
+                    let run = simplify_with_steps(
+                        expression(input),
+                        &options(parallel, Some(shared.clone())),
+                    );
+                    assert_eq!(
+                        run, cold,
+                        "{} with {} on {:?} with a {} cache",
+                        input, strategy, parallel, warmth
+                    );
+                }
+                // a call that didn't want steps leaves results the steps can't be recovered from
+                let shared = SimplifyCache::shared(1000);
+                simplify_with_options(expression(input), &options(parallel, Some(shared.clone())));
+                let run = simplify_with_steps(expression(input), &options(parallel, Some(shared)));
+                assert_eq!(
+                    run, cold,
+                    "{} with {} on {:?} after a call without steps",
+                    input, strategy, parallel
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+// walking the terms of a wide sum on several threads is faster than walking them one by one
+// in a file of its own so no other test is running alongside it, and ignored since timings on a busy
+// machine are no reason to fail a build, run it with cargo test --test parallel_speedup -- --ignored
+mod common;
+
+use common::expression;
+use rustic_math::{simplify_with_options, SimplifyOptions};
+use std::{
+    thread::available_parallelism,
+    time::{Duration, Instant},
+};
+
+#[test]
+#[ignore]
+fn parallel_walks_are_faster() {
+    // there is nothing to gain on fewer threads
+    if available_parallelism().map_or(1, |threads| threads.get()) < 4 {
+        return;
+    }
+    let input = (0..40)
+        .map(|i| {
+            format!(
+                "(x + {0})^3 * (y - {0})^2 + sin({0} * x)^2 + cos({0} * x)^2",
+                i
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" + ");
+    // the fastest of a few runs, to leave out the time the thread pool takes to start
+    let fastest = |parallel| -> Duration {
+        let options = SimplifyOptions {
+            parallel,
+            ..SimplifyOptions::default()
+        };
+        (0..3)
+            .map(|_| {
+                let started = Instant::now();
+                simplify_with_options(expression(&input), &options);
+                started.elapsed()
+            })
+            .min()
+            .unwrap()
+    };
+    let serial = fastest(None);
+    let parallel = fastest(Some(64));
+    assert!(
+        parallel * 3 < serial * 2,
+        "{:?} on several threads against {:?} on one",
+        parallel,
+        serial
+    );
+}
@@ -0,0 +1,159 @@
+// rewrite rules, parsed from text and matched against expressions
+mod common;
+
+use common::expression;
+use rustic_math::{
+    simplify_with_options, AngleMode, Assumptions, Expression, Property, Rule, RuleSet,
+    SimplifyOptions,
+};
+
+fn rewrite(rule: &str, input: &str, assumptions: &Assumptions) -> Option<Expression> {
+    Rule::parse(rule)
+        .unwrap()
+        .rewrite(&expression(input), assumptions, AngleMode::Radians)
+}
+
+#[test]
+fn parsing() {
+    let rule = Rule::parse("(x^n)^(1/n) -> x where n is odd").unwrap();
+    assert_eq!(rule.text, "(x^n)^(1/n) -> x where n is odd");
+    assert_eq!(rule.pattern, expression("(x^n)^(1/n)"));
+    assert_eq!(rule.replacement, expression("x"));
+    assert_eq!(rule.conditions.len(), 1);
+    assert_eq!(
+        (
+            rule.conditions[0].variable.as_str(),
+            rule.conditions[0].property
+        ),
+        ("n", Property::Odd)
+    );
+
+    let rule = Rule::parse("a*x + b*x -> (a + b)*x where a is constant, b is number").unwrap();
+    let properties: Vec<Property> = rule
+        .conditions
+        .iter()
+        .map(|condition| condition.property)
+        .collect();
+    assert_eq!(properties, [Property::Constant, Property::Constant]);
+    assert_eq!(
+        Rule::parse("x -> x where x is positive and x is integer")
+            .unwrap()
+            .conditions
+            .len(),
+        2
+    );
+
+    // comments and blank lines are skipped, the built in identities parse
+    let rules =
+        RuleSet::parse("test", "# a comment\n\nx + 0 -> x # trailing\nx * 1 -> x\n").unwrap();
+    assert_eq!((rules.name.as_str(), rules.rules.len()), ("test", 2));
+    assert!(!RuleSet::identities().rules.is_empty());
+}
+
+#[test]
+fn malformed_rules() {
+    for rule in [
+        "x + 0",
+        "-> x",
+        "x + 0 ->",
+        "x -> y",
+        "x -> x where y is odd",
+        "x -> x where x is prime",
+        "x -> x where x odd",
+        "x + 0 -> x = 0",
+    ] {
+        assert!(Rule::parse(rule).is_err(), "{} should be rejected", rule);
+    }
+    // the error says where the bad rule is
+    let err = RuleSet::parse("mine", "x + 0 -> x\n\nx -> y\n").unwrap_err();
+    assert!(err.starts_with("mine line 3:"), "{}", err);
+    assert!(RuleSet::load("no/such/file.rules").is_err());
+}
+
+#[test]
+fn matching() {
+    let none = Assumptions::new();
+    assert_eq!(
+        rewrite("a * (b + c) -> a*b + a*c", "2 * (x + y)", &none),
+        Some(expression("2*x + 2*y"))
+    );
+    // a repeated variable has to match the same subexpression each time
+    assert_eq!(rewrite("x - x -> 0", "y - y", &none), Some(expression("0")));
+    assert_eq!(rewrite("x - x -> 0", "y - z", &none), None);
+    // a rule that doesn't change anything isn't a rewrite
+    assert_eq!(rewrite("x + 0 -> x + 0", "y + 0", &none), None);
+}
+
+#[test]
+fn commutative_and_associative_matching() {
+    let none = Assumptions::new();
+    let pythagorean = "sin(x)^2 + cos(x)^2 -> 1";
+    assert_eq!(
+        rewrite(pythagorean, "cos(y)^2 + sin(y)^2", &none),
+        Some(expression("1"))
+    );
+    // some of the terms of a longer sum, in any order, flattened by simplifying without any rules
+    let without_rules = SimplifyOptions {
+        rules: Vec::new(),
+        ..SimplifyOptions::default()
+    };
+    let sum = simplify_with_options(expression("cos(y)^2 + y + sin(y)^2"), &without_rules);
+    let rewritten = Rule::parse(pythagorean)
+        .unwrap()
+        .rewrite(&sum, &none, AngleMode::Radians)
+        .unwrap();
+    assert_eq!(
+        simplify_with_options(rewritten, &without_rules),
+        simplify_with_options(expression("y + 1"), &without_rules)
+    );
+    assert_eq!(rewrite(pythagorean, "sin(y)^2 + cos(z)^2", &none), None);
+    assert_eq!(
+        rewrite("tan(x) * cot(x) -> 1", "cot(2 * y) * tan(2 * y)", &none),
+        Some(expression("1"))
+    );
+}
+
+#[test]
+fn conditions() {
+    let none = Assumptions::new();
+    let odd = "(x^n)^(1/n) -> x where n is odd";
+    assert_eq!(rewrite(odd, "(y^3)^(1/3)", &none), Some(expression("y")));
+    assert_eq!(rewrite(odd, "(y^2)^(1/2)", &none), None);
+    assert_eq!(
+        rewrite("x^a -> 1 where a is constant", "y^(2 + 3)", &none),
+        Some(expression("1"))
+    );
+    assert_eq!(rewrite("x^a -> 1 where a is constant", "y^z", &none), None);
+    assert_eq!(
+        rewrite("x * 1 -> x where x is variable", "y * 1", &none),
+        Some(expression("y"))
+    );
+    assert_eq!(
+        rewrite("x * 1 -> x where x is variable", "(y + 2) * 1", &none),
+        None
+    );
+
+    // conditions on expressions with variables hold when the assumptions say so
+    let positive = "sqrt(x^2) -> x where x is positive";
+    assert_eq!(rewrite(positive, "sqrt(y^2)", &none), None);
+    let mut assumptions = Assumptions::new();
+    assumptions.assume("y > 0".parse().unwrap()).unwrap();
+    assert_eq!(
+        rewrite(positive, "sqrt(y^2)", &assumptions),
+        Some(expression("y"))
+    );
+    assert_eq!(rewrite(positive, "sqrt(z^2)", &assumptions), None);
+}
+
+#[test]
+fn conditions_use_the_angle_mode() {
+    let rule = Rule::parse("y * a -> y where a is positive").unwrap();
+    let none = Assumptions::new();
+    // sin(150) is 1/2 in degrees and about -0.71 in radians
+    let product = expression("x * sin(150)");
+    assert_eq!(
+        rule.rewrite(&product, &none, AngleMode::Degrees),
+        Some(expression("x"))
+    );
+    assert_eq!(rule.rewrite(&product, &none, AngleMode::Radians), None);
+}
@@ -0,0 +1,57 @@
+// simplification of whole expressions
+mod common;
+
+use common::expression;
+use rustic_math::{simplify_expression, to_latex, Expression};
+
+#[test]
+fn huge_powers_are_left_alone() {
+    for (input, n) in [
+        ("(x + 1)^2", 1e300),
+        ("(x + y + z)^2", 2f64.powi(70)),
+        ("(x + 1)^2", 1001.0),
+    ] {
+        let mut power = expression(input);
+        if let Expression::Exponentiation(exponentiation) = &mut power {
+            *exponentiation.exponent = Expression::Number(n);
+        }
+        let simplified = simplify_expression(power);
+        assert!(
+            matches!(&simplified, Expression::Exponentiation(exponentiation) if *exponentiation.exponent == Expression::Number(n)),
+            "{} with exponent {} simplified to {:?}",
+            input,
+            n,
+            simplified
+        );
+    }
+    assert_eq!(
+        simplify_expression(expression("(x + 1)^2")),
+        simplify_expression(expression("x^2 + 2 * x + 1"))
+    );
+}
+
+#[test]
+fn powers_of_sums_expand() {
+    for (input, expected) in [
+        (
+            "(a + b + c)^2",
+            "a^{2} + 2 \\cdot a \\cdot b + 2 \\cdot a \\cdot c + b^{2} + 2 \\cdot b \\cdot c + c^{2}",
+        ),
+        (
+            "(x - 1)^4",
+            "x^{4} - 4 \\cdot x^{3} + 6 \\cdot x^{2} - 4 \\cdot x + 1",
+        ),
+        // a product of powers, each expanded before they are multiplied out
+        (
+            "(a + b)^2 * (a - b)^3",
+            "a^{5} - a^{4} \\cdot b - 2 \\cdot a^{3} \\cdot b^{2} + 2 \\cdot a^{2} \\cdot b^{3} + a \\cdot b^{4} - b^{5}",
+        ),
+    ] {
+        assert_eq!(
+            to_latex(&simplify_expression(expression(input))),
+            expected,
+            "{}",
+            input
+        );
+    }
+}
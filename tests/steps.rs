@@ -0,0 +1,80 @@
+// the rewrites the simplifier records so a simplification can be shown step by step
+mod common;
+
+use common::expression;
+use rustic_math::{simplify_with_options, simplify_with_steps, to_latex, SimplifyOptions, Step};
+
+fn steps(input: &str) -> Vec<Step> {
+    simplify_with_steps(expression(input), &SimplifyOptions::default()).1
+}
+
+#[test]
+fn steps_are_described_in_words() {
+    let steps = steps("x*(y+z)");
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].pass, "distribute");
+    assert_eq!(steps[0].before, "x \\cdot (y + z)");
+    assert_eq!(steps[0].after, "x \\cdot y + x \\cdot z");
+    assert_eq!(steps[0].description, "distribute x over (y + z)");
+}
+
+#[test]
+fn steps_point_at_the_rewritten_subtree() {
+    let nested = steps("a + x*(y+z)");
+    assert_eq!(nested[0].path, vec![1]);
+    assert_eq!(nested[0].before, "x \\cdot (y + z)");
+
+    let arithmetic = steps("5*(3+5)");
+    let paths: Vec<&[usize]> = arithmetic.iter().map(|step| step.path.as_slice()).collect();
+    assert_eq!(paths, vec![&[1][..], &[][..]]);
+    assert_eq!(arithmetic[1].after, "40");
+}
+
+#[test]
+fn steps_end_at_the_simplified_expression() {
+    for input in ["(x+1)^2", "2*x - -x", "5*(3+5)"] {
+        let (simplified, steps) =
+            simplify_with_steps(expression(input), &SimplifyOptions::default());
+        assert_eq!(
+            to_latex(&simplified.expression),
+            to_latex(&simplify_with_options(
+                expression(input),
+                &SimplifyOptions::default()
+            ))
+        );
+        let last = steps.last().unwrap();
+        assert!(
+            last.path.is_empty(),
+            "{} ended with a step at {:?}",
+            input,
+            last.path
+        );
+        assert_eq!(last.after, to_latex(&simplified.expression), "{}", input);
+    }
+}
+
+#[test]
+fn nothing_to_do_takes_no_steps() {
+    assert!(steps("x").is_empty());
+    assert!(steps("2 + 3*x").is_empty());
+}
+
+#[test]
+fn distributing_names_the_factors_in_the_order_they_come_out() {
+    // the sum distributed over is swapped out for the last factor
+    let steps = steps("a*(p+q)*(u+v)*(x+y)");
+    let step = steps.iter().find(|step| step.pass == "distribute").unwrap();
+    let (others, sum) = step
+        .description
+        .strip_prefix("distribute ")
+        .and_then(|rest| rest.split_once(" over "))
+        .unwrap();
+    assert_eq!(sum, "(p + q)");
+    assert!(
+        step.after
+            .starts_with(&format!("{} \\cdot p + {} \\cdot q", others, others)),
+        "{} doesn't match {}",
+        step.description,
+        step.after
+    );
+}
@@ -0,0 +1,79 @@
+// choosing the form the simplifier works towards
+mod common;
+
+use common::expression;
+use rustic_math::{simplify_with_options, to_latex, Expression, SimplifyOptions, Strategy};
+
+fn simplified(input: &str, strategy: &str) -> String {
+    let options = SimplifyOptions {
+        strategy: strategy.parse().unwrap(),
+        ..SimplifyOptions::default()
+    };
+    to_latex(&simplify_with_options(expression(input), &options))
+}
+
+#[test]
+fn expand() {
+    assert_eq!(simplified("(x+1)^2", "expand"), "x^{2} + 2 \\cdot x + 1");
+    assert_eq!(simplified("(x+1)*(x-1)", "expand"), "x^{2} - 1");
+}
+
+#[test]
+fn factor() {
+    assert_eq!(simplified("(x+1)^2", "factor"), "(x + 1)^{2}");
+    assert_eq!(simplified("x^2 - 1", "factor"), "(x - 1) \\cdot (x + 1)");
+}
+
+#[test]
+fn collect() {
+    assert_eq!(
+        simplified("x*y + x^2 + 2*x + 3*x*y", "collect(x)"),
+        "x^{2} + x \\cdot (4 \\cdot y + 2)"
+    );
+    assert_eq!(
+        simplified("a*x + b*x + c", "collect(x)"),
+        "x \\cdot (a + b) + c"
+    );
+}
+
+#[test]
+fn trig_reduce() {
+    assert_eq!(simplified("tan(x)*cos(x)", "trig_reduce"), "\\sin(x)");
+    assert_eq!(simplified("sec(x)*cos(x)", "trig_reduce"), "1");
+    assert_eq!(simplified("sin(x)^2 + cos(x)^2", "trig_reduce"), "1");
+}
+
+#[test]
+fn simplest_picks_the_cheapest_form() {
+    assert_eq!(simplified("x^2 + 2*x + 1", "simplest"), "(x + 1)^{2}");
+    assert_eq!(simplified("(x+1)*(x-1)", "simplest"), "x^{2} - 1");
+    assert_eq!(simplified("tan(x)*cos(x)", "simplest"), "\\sin(x)");
+
+    // a cost that prefers sums over everything else
+    let options = SimplifyOptions {
+        strategy: Strategy::Simplest,
+        cost: |expression| match expression {
+            Expression::Addition(_) => 0,
+            _ => 1,
+        },
+        ..SimplifyOptions::default()
+    };
+    assert_eq!(
+        to_latex(&simplify_with_options(expression("(x+1)^2"), &options)),
+        "x^{2} + 2 \\cdot x + 1"
+    );
+}
+
+#[test]
+fn parsing() {
+    assert_eq!(
+        "collect( y )".parse::<Strategy>().unwrap().to_string(),
+        "collect(y)"
+    );
+    assert_eq!(
+        "trig_reduce".parse::<Strategy>().unwrap().to_string(),
+        "trig_reduce"
+    );
+    assert!("collect()".parse::<Strategy>().is_err());
+    assert!("fastest".parse::<Strategy>().is_err());
+}
@@ -0,0 +1,55 @@
+// replacing variables with expressions and finding the ones an expression depends on
+mod common;
+
+use common::expression;
+use rustic_math::{free_variables, rename, substitute, substitute_all};
+
+#[test]
+fn substitutions_happen_at_once() {
+    let swapped = substitute_all(
+        &expression("x^2 + 2 * y - sin(x * y)"),
+        &[
+            ("x".to_string(), expression("y")),
+            ("y".to_string(), expression("x")),
+        ],
+    );
+    assert_eq!(swapped, expression("y^2 + 2 * x - sin(y * x)"));
+    // one after the other they would both end up x
+    let x = expression("x");
+    let one_by_one = substitute(
+        &substitute(&expression("x + y"), "x", &expression("y")),
+        "y",
+        &x,
+    );
+    assert_eq!(one_by_one, expression("x + x"));
+}
+
+#[test]
+fn substitute_reaches_every_node() {
+    assert_eq!(
+        substitute(
+            &expression("-x / sqrt(x) + cos(x)^x"),
+            "x",
+            &expression("a + 1")
+        ),
+        expression("-(a + 1) / sqrt(a + 1) + cos(a + 1)^(a + 1)")
+    );
+}
+
+#[test]
+fn rename_only_touches_that_variable() {
+    assert_eq!(
+        rename(&expression("x * y + tan(x) - y"), "x", "t"),
+        expression("t * y + tan(t) - y")
+    );
+    assert_eq!(rename(&expression("y + 1"), "x", "t"), expression("y + 1"));
+}
+
+#[test]
+fn free_variables_in_order() {
+    let variables: Vec<String> = free_variables(&expression("z * sin(y) / sqrt(x + 2) + x^w"))
+        .into_iter()
+        .collect();
+    assert_eq!(variables, ["w", "x", "y", "z"]);
+    assert!(free_variables(&expression("2 * pi + 1")).is_empty());
+}